@@ -0,0 +1,319 @@
+// Per-application overrides of a few settings, configured under `appRules` in
+// config.json. Rules are matched against the app the user is working in every
+// time a trigger fires (selecting text, the lookup IPC, writing, inserting a
+// translation); the first matching rule wins and its fields replace the global
+// settings until the next trigger.
+
+use active_win_pos_rs::{get_active_window, ActiveWindow};
+use debug_print::debug_println;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_config, Config};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum InsertionMode {
+    /// Simulate typing the text key by key.
+    #[default]
+    Typing,
+    /// Put the text on the clipboard and paste it; much faster, and immune
+    /// to autocomplete and IME interference in editors.
+    Paste,
+}
+
+/// Which apps a rule applies to. Every field that is set must match; `*`
+/// matches any run of characters and comparison ignores case.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMatcher {
+    pub app_name: Option<String>,
+    /// macOS bundle identifier, e.g. `com.tinyspeck.slackmacgap`.
+    pub bundle_id: Option<String>,
+    /// Executable file name, e.g. `Code.exe`.
+    pub process_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppRule {
+    pub name: Option<String>,
+    #[serde(rename = "match")]
+    pub matcher: AppMatcher,
+    pub target_language: Option<String>,
+    pub always_show_icons: Option<bool>,
+    pub use_compact_lookup: Option<bool>,
+    pub writing_newline_hotkey: Option<String>,
+    pub insertion_mode: Option<InsertionMode>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppInfo {
+    pub app_name: String,
+    pub bundle_id: String,
+    pub process_name: String,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppRuleMatch {
+    /// Position of the rule in `appRules`.
+    pub index: u32,
+    pub rule: AppRule,
+}
+
+static ACTIVE_RULE: Mutex<Option<AppRule>> = Mutex::new(None);
+
+impl AppInfo {
+    pub fn from_window(window: &ActiveWindow) -> Self {
+        Self {
+            app_name: window.app_name.clone(),
+            bundle_id: bundle_id_for_pid(window.process_id),
+            process_name: window
+                .process_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn bundle_id_for_pid(pid: u64) -> String {
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let running_app: id = msg_send![
+            class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: pid as i32
+        ];
+        if running_app == nil {
+            return String::new();
+        }
+        let bundle_id: id = msg_send![running_app, bundleIdentifier];
+        if bundle_id == nil {
+            return String::new();
+        }
+        let bytes: *const std::os::raw::c_char = msg_send![bundle_id, UTF8String];
+        if bytes.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(bytes)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn bundle_id_for_pid(_pid: u64) -> String {
+    String::new()
+}
+
+/// Case-insensitive match where `*` stands for any run of characters.
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let value = value.to_lowercase().chars().collect::<Vec<_>>();
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack = None;
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == value[v] {
+            p += 1;
+            v += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn rule_matches(matcher: &AppMatcher, app: &AppInfo) -> bool {
+    let fields = [
+        (&matcher.app_name, &app.app_name),
+        (&matcher.bundle_id, &app.bundle_id),
+        (&matcher.process_name, &app.process_name),
+    ];
+    let mut constrained = false;
+    for (pattern, value) in fields {
+        if let Some(pattern) = pattern {
+            constrained = true;
+            if !glob_matches(pattern, value) {
+                return false;
+            }
+        }
+    }
+    // A rule without any condition would silently apply everywhere.
+    constrained
+}
+
+fn find_rule<'a>(rules: &'a [AppRule], app: &AppInfo) -> Option<(usize, &'a AppRule)> {
+    rules
+        .iter()
+        .enumerate()
+        .find(|(_, rule)| rule_matches(&rule.matcher, app))
+}
+
+fn apply_rule(mut config: Config, rule: &AppRule) -> Config {
+    if rule.always_show_icons.is_some() {
+        config.always_show_icons = rule.always_show_icons;
+    }
+    if rule.use_compact_lookup.is_some() {
+        config.use_compact_lookup = rule.use_compact_lookup;
+    }
+    if rule.writing_newline_hotkey.is_some() {
+        config.writing_newline_hotkey = rule.writing_newline_hotkey.clone();
+    }
+    if rule.insertion_mode.is_some() {
+        config.insertion_mode = rule.insertion_mode;
+    }
+    config
+}
+
+/// Picks the rule for `app`, or clears the active rule when nothing matches.
+pub fn update_active_rule_for(app: Option<AppInfo>) {
+    let rules = get_config()
+        .ok()
+        .and_then(|config| config.app_rules)
+        .unwrap_or_default();
+    let rule = app.and_then(|app| {
+        let (index, rule) = find_rule(&rules, &app)?;
+        debug_println!(
+            "[app_rules] rule #{} matches app='{}' bundle='{}' process='{}'",
+            index,
+            app.app_name,
+            app.bundle_id,
+            app.process_name
+        );
+        Some(rule.clone())
+    });
+    *ACTIVE_RULE.lock() = rule;
+}
+
+/// Re-evaluates the rules against the frontmost app. Cheap when no rules are
+/// configured, so triggers can call it unconditionally.
+pub fn update_active_rule() {
+    let has_rules = get_config()
+        .ok()
+        .and_then(|config| config.app_rules)
+        .is_some_and(|rules| !rules.is_empty());
+    if !has_rules {
+        *ACTIVE_RULE.lock() = None;
+        return;
+    }
+    let app = get_active_window()
+        .ok()
+        .filter(|window| window.process_id != std::process::id() as u64)
+        .map(|window| AppInfo::from_window(&window));
+    // While the translator itself is frontmost keep the rule of the app the
+    // user came from.
+    if app.is_some() {
+        update_active_rule_for(app);
+    }
+}
+
+/// The settings in effect for the app of the most recent trigger.
+pub fn get_app_config() -> Result<Config, Box<dyn std::error::Error>> {
+    let config = get_config()?;
+    Ok(match &*ACTIVE_RULE.lock() {
+        Some(rule) => apply_rule(config, rule),
+        None => config,
+    })
+}
+
+/// The rule applied to the most recent trigger, if any. The translator and
+/// the writing flow use it to pick the rule's target language.
+#[tauri::command]
+#[specta::specta]
+pub fn get_active_app_rule() -> Option<AppRule> {
+    ACTIVE_RULE.lock().clone()
+}
+
+/// Shows which rule, if any, would apply to the given app.
+#[tauri::command]
+#[specta::specta]
+pub fn match_app_rule(app: AppInfo) -> Option<AppRuleMatch> {
+    let rules = get_config().ok()?.app_rules.unwrap_or_default();
+    find_rule(&rules, &app).map(|(index, rule)| AppRuleMatch {
+        index: index as u32,
+        rule: rule.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(app_name: &str, bundle_id: &str, process_name: &str) -> AppInfo {
+        AppInfo {
+            app_name: app_name.to_string(),
+            bundle_id: bundle_id.to_string(),
+            process_name: process_name.to_string(),
+        }
+    }
+
+    fn rule(matcher: AppMatcher) -> AppRule {
+        AppRule {
+            matcher,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn glob_matching_ignores_case() {
+        assert!(glob_matches("code*", "Code.exe"));
+        assert!(glob_matches("*slack*", "com.tinyspeck.slackmacgap"));
+        assert!(glob_matches("Terminal", "terminal"));
+        assert!(!glob_matches("code", "Code.exe"));
+        assert!(glob_matches("a*b*c", "aXXbYYc"));
+        assert!(!glob_matches("a*b*c", "aXXbYY"));
+    }
+
+    #[test]
+    fn first_rule_with_all_conditions_matching_wins() {
+        let rules = vec![
+            rule(AppMatcher::default()),
+            rule(AppMatcher {
+                app_name: Some("Slack".to_string()),
+                process_name: Some("firefox*".to_string()),
+                ..Default::default()
+            }),
+            rule(AppMatcher {
+                app_name: Some("Slack".to_string()),
+                ..Default::default()
+            }),
+            rule(AppMatcher {
+                bundle_id: Some("com.tinyspeck.*".to_string()),
+                ..Default::default()
+            }),
+        ];
+        let slack = app("Slack", "com.tinyspeck.slackmacgap", "Slack");
+        assert_eq!(find_rule(&rules, &slack).map(|(index, _)| index), Some(2));
+        assert!(find_rule(&rules, &app("Safari", "com.apple.Safari", "Safari")).is_none());
+    }
+
+    #[test]
+    fn rule_fields_override_global_settings() {
+        let config = Config {
+            use_compact_lookup: Some(false),
+            always_show_icons: Some(true),
+            ..Default::default()
+        };
+        let rule = AppRule {
+            use_compact_lookup: Some(true),
+            insertion_mode: Some(InsertionMode::Paste),
+            ..Default::default()
+        };
+        let config = apply_rule(config, &rule);
+        assert_eq!(config.use_compact_lookup, Some(true));
+        assert_eq!(config.always_show_icons, Some(true));
+        assert_eq!(config.insertion_mode, Some(InsertionMode::Paste));
+    }
+}
@@ -0,0 +1,693 @@
+// Offline speech recognition with sherpa-onnx, so voice messages and
+// recordings can be translated without uploading them. The models are
+// described by `asr-models.json` and downloaded, imported and deleted like
+// the local TTS voices, below their own directory. One recognizer stays
+// loaded at a time and unloads after `localTtsIdleUnload`, like the voices.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sherpa_onnx::{
+    OfflineModelConfig, OfflineRecognizer, OfflineRecognizerConfig, OfflineSenseVoiceModelConfig,
+    OfflineWhisperModelConfig,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::asr_audio::SAMPLE_RATE;
+use crate::tts::{
+    directory_size, extract_archive, file_nonempty, model_files, path_string, stage_import,
+    write_checksums,
+};
+
+const BUNDLED_CATALOG: &str = include_str!("../asr-models.json");
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum AsrFamily {
+    SenseVoice,
+    Whisper,
+}
+
+/// Paths relative to the model directory.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AsrModelFiles {
+    /// The single model of SenseVoice.
+    model: Option<String>,
+    encoder: Option<String>,
+    decoder: Option<String>,
+    tokens: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsrModelSpec {
+    id: String,
+    name: String,
+    /// Frontend language codes; empty for models that recognize any.
+    languages: Vec<String>,
+    family: AsrFamily,
+    /// The directory the archive extracts to, directly below the model root.
+    directory: String,
+    urls: Vec<String>,
+    /// SHA-256 of the archive. Without one the model is never downloaded and
+    /// has to be imported.
+    sha256: String,
+    files: AsrModelFiles,
+    /// Files that must exist for the model to count as installed.
+    required_files: Vec<String>,
+}
+
+impl AsrModelSpec {
+    fn downloadable(&self) -> bool {
+        !self.urls.is_empty() && !self.sha256.is_empty()
+    }
+
+    fn speaks(&self, lang: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|code| code == lang)
+    }
+}
+
+struct LoadedRecognizer {
+    /// The model id and the language it was loaded for.
+    key: (String, Option<String>),
+    recognizer: Arc<OfflineRecognizer>,
+    last_used: Instant,
+}
+
+static RECOGNIZER: Lazy<Mutex<Option<LoadedRecognizer>>> = Lazy::new(|| Mutex::new(None));
+static LOAD_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AsrDownloadProgress {
+    id: String,
+    name: String,
+    downloaded: u64,
+    total: u64,
+    bytes_per_second: f64,
+    phase: &'static str,
+    message: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AsrModelInfo {
+    pub id: String,
+    pub name: String,
+    /// Empty for models that recognize any language.
+    pub languages: Vec<String>,
+    pub installed: bool,
+    /// Whether the catalog pins a checksum, so the model can be downloaded.
+    pub downloadable: bool,
+    pub loaded: bool,
+    /// Bytes on disk, 0 when not installed.
+    pub size: f64,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AsrTranscript {
+    /// The id of the model that recognized the speech.
+    pub model: String,
+    pub duration_ms: f64,
+    /// The segments joined.
+    pub text: String,
+    pub segments: Vec<AsrSegment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AsrSegment {
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub text: String,
+}
+
+fn emit_download_progress(
+    app: &AppHandle,
+    spec: &AsrModelSpec,
+    downloaded: u64,
+    total: u64,
+    bytes_per_second: f64,
+    phase: &'static str,
+    message: Option<String>,
+) {
+    let _ = app.emit(
+        "asr-download-progress",
+        AsrDownloadProgress {
+            id: spec.id.clone(),
+            name: spec.name.clone(),
+            downloaded,
+            total,
+            bytes_per_second,
+            phase,
+            message,
+        },
+    );
+}
+
+fn model_root(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_dir(app).map(|path| path.join("asr"))
+}
+
+fn catalog() -> Result<Vec<AsrModelSpec>, String> {
+    serde_json::from_str(BUNDLED_CATALOG).map_err(|err| format!("invalid ASR model catalog: {err}"))
+}
+
+fn find_model(id: &str) -> Result<AsrModelSpec, String> {
+    catalog()?
+        .into_iter()
+        .find(|spec| spec.id == id)
+        .ok_or_else(|| format!("unknown ASR model: {id}"))
+}
+
+fn model_present(spec: &AsrModelSpec, root: &Path) -> bool {
+    let directory = root.join(&spec.directory);
+    spec.required_files
+        .iter()
+        .all(|name| file_nonempty(directory.join(name)))
+}
+
+fn download_key(id: &str) -> String {
+    format!("asr:{id}")
+}
+
+async fn download_model(app: &AppHandle, spec: &AsrModelSpec, root: &Path) -> Result<(), String> {
+    tokio::fs::create_dir_all(root)
+        .await
+        .map_err(|err| format!("create ASR model directory: {err}"))?;
+
+    let archive_path = root.join(format!("{}.tar.bz2.part", spec.directory));
+    let mut downloaded = 0_u64;
+    let mut total = 0_u64;
+    let result = async {
+        if !spec.downloadable() {
+            return Err(
+                "no archive checksum is pinned in the catalog; import it instead".to_string(),
+            );
+        }
+        let urls = spec.urls.iter().map(String::as_str).collect::<Vec<_>>();
+        crate::download::download_verified(
+            &download_key(&spec.id),
+            &urls,
+            &spec.sha256,
+            &archive_path,
+            |done, size, speed, phase| {
+                downloaded = done;
+                total = size;
+                emit_download_progress(app, spec, done, size, speed, phase, None);
+            },
+        )
+        .await?;
+
+        emit_download_progress(app, spec, downloaded, total, 0.0, "extracting", None);
+
+        let archive = archive_path.clone();
+        let destination = root.to_path_buf();
+        let model_directory = root.join(&spec.directory);
+        let extracted = tokio::task::spawn_blocking(move || {
+            if model_directory.exists() {
+                std::fs::remove_dir_all(&model_directory)
+                    .map_err(|err| format!("replace old ASR model: {err}"))?;
+            }
+            extract_archive(&archive, &destination)?;
+            write_checksums(&model_directory)
+        })
+        .await
+        .map_err(|err| format!("extract model task failed: {err}"))
+        .and_then(|result| result);
+        // Only a partial download is worth keeping, to resume it.
+        let _ = tokio::fs::remove_file(&archive_path).await;
+        extracted?;
+
+        if !model_present(spec, root) {
+            return Err("model files are missing after extraction".to_string());
+        }
+        Ok::<(), String>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            emit_download_progress(app, spec, downloaded, total, 0.0, "ready", None);
+            Ok(())
+        }
+        Err(err) if err == crate::download::CANCELLED => {
+            emit_download_progress(app, spec, 0, 0, 0.0, "cancelled", None);
+            Err(err)
+        }
+        Err(err) => {
+            let error = format!("download {} ASR model: {err}", spec.id);
+            emit_download_progress(app, spec, 0, 0, 0.0, "error", Some(error.clone()));
+            Err(error)
+        }
+    }
+}
+
+/// The language hint the model takes for frontend language `lang`: SenseVoice
+/// knows `zh`, `en`, `ja`, `ko` and `yue`, Whisper's small models no `yue`.
+/// Without one, or for one the model doesn't know, it detects the language.
+fn model_language(spec: &AsrModelSpec, lang: Option<&str>) -> Option<String> {
+    let lang = lang?.trim();
+    let primary = lang.split(['-', '_']).next()?.to_ascii_lowercase();
+    match (spec.family, primary.as_str()) {
+        (_, "") => None,
+        (AsrFamily::SenseVoice, "zh" | "en" | "ja" | "ko" | "yue") => Some(primary),
+        (AsrFamily::SenseVoice, _) => None,
+        (AsrFamily::Whisper, "yue" | "lzh") => Some("zh".to_string()),
+        (AsrFamily::Whisper, _) => Some(primary),
+    }
+}
+
+/// The model `id`, or the first installed one for `lang`, preferring those
+/// that list it over those that recognize anything.
+fn choose_model(
+    catalog: Vec<AsrModelSpec>,
+    root: &Path,
+    id: Option<&str>,
+    lang: Option<&str>,
+) -> Result<AsrModelSpec, String> {
+    if let Some(id) = id {
+        return catalog
+            .into_iter()
+            .find(|spec| spec.id == id)
+            .ok_or_else(|| format!("unknown ASR model: {id}"));
+    }
+    let mut installed = catalog
+        .into_iter()
+        .filter(|spec| model_present(spec, root))
+        .collect::<Vec<_>>();
+    if let Some(lang) = lang {
+        // Stable, so the catalog order decides among equals.
+        installed.sort_by_key(
+            |spec| match spec.languages.iter().any(|code| code == lang) {
+                true => 0,
+                false if spec.speaks(lang) => 1,
+                false => 2,
+            },
+        );
+    }
+    installed
+        .into_iter()
+        .next()
+        .ok_or_else(|| "no speech recognition model is installed".to_string())
+}
+
+fn create_recognizer(
+    spec: &AsrModelSpec,
+    root: &Path,
+    language: Option<String>,
+) -> Result<OfflineRecognizer, String> {
+    let directory = root.join(&spec.directory);
+    let file = |name: &Option<String>| name.as_ref().map(|name| path_string(directory.join(name)));
+    let mut model_config = OfflineModelConfig {
+        tokens: Some(path_string(directory.join(&spec.files.tokens))),
+        num_threads: crate::tts::engine_threads() as i32,
+        ..Default::default()
+    };
+    match spec.family {
+        AsrFamily::SenseVoice => {
+            model_config.sense_voice = OfflineSenseVoiceModelConfig {
+                model: file(&spec.files.model),
+                language,
+                // Punctuation and numbers as digits, ready to translate.
+                use_itn: true,
+            };
+        }
+        AsrFamily::Whisper => {
+            model_config.whisper = OfflineWhisperModelConfig {
+                encoder: file(&spec.files.encoder),
+                decoder: file(&spec.files.decoder),
+                language,
+                task: Some("transcribe".to_string()),
+                ..Default::default()
+            };
+        }
+    }
+    let config = OfflineRecognizerConfig {
+        model_config,
+        ..Default::default()
+    };
+    OfflineRecognizer::create(&config)
+        .ok_or_else(|| format!("failed to load {} ASR model", spec.id))
+}
+
+/// Starts the task that unloads the recognizer once it has been idle for
+/// `localTtsIdleUnload`; it stops when the recognizer is gone.
+fn watch_idle_recognizer() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let (idle_timeout, _) = crate::tts::engine_limits();
+            let mut loaded = RECOGNIZER.lock();
+            let unload = match loaded.as_ref() {
+                None => break,
+                // Transcription still holds it.
+                Some(current) if Arc::strong_count(&current.recognizer) > 1 => false,
+                Some(current) => {
+                    idle_timeout.is_some_and(|timeout| current.last_used.elapsed() >= timeout)
+                }
+            };
+            if unload {
+                *loaded = None;
+                break;
+            }
+        }
+    });
+}
+
+async fn ensure_recognizer(
+    app: &AppHandle,
+    spec: &AsrModelSpec,
+    language: Option<String>,
+) -> Result<Arc<OfflineRecognizer>, String> {
+    let key = (spec.id.clone(), language.clone());
+    let root = model_root(app)?;
+    if !model_present(spec, &root) {
+        let _download_guard = crate::download::lock(&download_key(&spec.id)).await;
+        if !model_present(spec, &root) {
+            download_model(app, spec, &root).await?;
+        }
+    }
+
+    let _load_guard = LOAD_LOCK.lock().await;
+    if let Some(loaded) = RECOGNIZER
+        .lock()
+        .as_mut()
+        .filter(|loaded| loaded.key == key)
+    {
+        loaded.last_used = Instant::now();
+        return Ok(loaded.recognizer.clone());
+    }
+    // Deleted while it was downloading.
+    if !model_present(spec, &root) {
+        return Err(format!("ASR model {} is not installed", spec.id));
+    }
+    let owned = spec.clone();
+    let recognizer =
+        tokio::task::spawn_blocking(move || create_recognizer(&owned, &root, language))
+            .await
+            .map_err(|err| format!("load ASR model task failed: {err}"))??;
+    let recognizer = Arc::new(recognizer);
+    let previous = RECOGNIZER.lock().replace(LoadedRecognizer {
+        key,
+        recognizer: recognizer.clone(),
+        last_used: Instant::now(),
+    });
+    // A watcher is already running while another recognizer was loaded.
+    if previous.is_none() {
+        watch_idle_recognizer();
+    }
+    Ok(recognizer)
+}
+
+/// Drops the recognizer of model `id` if it is loaded; transcription already
+/// holding it finishes first.
+fn unload(id: &str) {
+    let mut loaded = RECOGNIZER.lock();
+    if loaded.as_ref().is_some_and(|loaded| loaded.key.0 == id) {
+        *loaded = None;
+    }
+}
+
+fn ms(samples: usize) -> f64 {
+    samples as f64 * 1000.0 / SAMPLE_RATE as f64
+}
+
+/// Whether `ch` is written without spaces around it: Chinese characters, kana
+/// and CJK punctuation.
+fn unspaced(ch: char) -> bool {
+    crate::tts::is_han(ch) || matches!(ch as u32, 0x3000..=0x30FF | 0xFF00..=0xFFEF)
+}
+
+/// Joins segment texts with spaces, except next to Chinese or Japanese.
+fn join_segments(segments: &[AsrSegment]) -> String {
+    let mut text = String::new();
+    for segment in segments {
+        if let (Some(last), Some(first)) = (text.chars().last(), segment.text.chars().next()) {
+            if !unspaced(last) && !unspaced(first) {
+                text.push(' ');
+            }
+        }
+        text.push_str(&segment.text);
+    }
+    text
+}
+
+fn model_info(spec: AsrModelSpec, root: &Path) -> AsrModelInfo {
+    let installed = model_present(&spec, root);
+    AsrModelInfo {
+        downloadable: spec.downloadable(),
+        loaded: RECOGNIZER
+            .lock()
+            .as_ref()
+            .is_some_and(|loaded| loaded.key.0 == spec.id),
+        size: if installed {
+            directory_size(&root.join(&spec.directory)) as f64
+        } else {
+            0.0
+        },
+        installed,
+        id: spec.id,
+        name: spec.name,
+        languages: spec.languages,
+    }
+}
+
+/// Transcribes a WAV, MP3, Ogg (Opus or Vorbis) or M4A file into text with
+/// the time of each utterance, using model `model` or the installed one for
+/// `lang`. `lang` also tells the model the language to expect; without it
+/// the model detects it.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_audio_file(
+    app: AppHandle,
+    path: String,
+    lang: Option<String>,
+    model: Option<String>,
+) -> Result<AsrTranscript, String> {
+    let lang = lang.filter(|lang| !lang.trim().is_empty());
+    let root = model_root(&app)?;
+    let spec = choose_model(catalog()?, &root, model.as_deref(), lang.as_deref())?;
+    let language = model_language(&spec, lang.as_deref());
+
+    let samples =
+        tokio::task::spawn_blocking(move || crate::asr_audio::decode_file(Path::new(&path)))
+            .await
+            .map_err(|err| format!("decode audio task failed: {err}"))??;
+    let recognizer = ensure_recognizer(&app, &spec, language).await?;
+    let segments = tokio::task::spawn_blocking(move || {
+        let mut segments = Vec::new();
+        for range in crate::asr_audio::speech_segments(&samples, SAMPLE_RATE) {
+            let stream = recognizer.create_stream();
+            stream.accept_waveform(SAMPLE_RATE, &samples[range.clone()]);
+            recognizer.decode(&stream);
+            let text = stream
+                .get_result()
+                .map(|result| result.text.trim().to_string())
+                .unwrap_or_default();
+            if !text.is_empty() {
+                segments.push(AsrSegment {
+                    start_ms: ms(range.start),
+                    end_ms: ms(range.end),
+                    text,
+                });
+            }
+        }
+        (segments, ms(samples.len()))
+    })
+    .await
+    .map_err(|err| format!("recognize speech task failed: {err}"));
+    let (segments, duration_ms) = segments?;
+    if let Some(loaded) = RECOGNIZER.lock().as_mut() {
+        loaded.last_used = Instant::now();
+    }
+    Ok(AsrTranscript {
+        model: spec.id,
+        duration_ms,
+        text: join_segments(&segments),
+        segments,
+    })
+}
+
+/// The speech recognition models this build knows, with whether each is
+/// installed and loaded and its size on disk.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_asr_models(app: AppHandle) -> Result<Vec<AsrModelInfo>, String> {
+    let root = model_root(&app)?;
+    let catalog = catalog()?;
+    tokio::task::spawn_blocking(move || {
+        catalog
+            .into_iter()
+            .map(|spec| model_info(spec, &root))
+            .collect()
+    })
+    .await
+    .map_err(|err| format!("list ASR models task failed: {err}"))
+}
+
+/// Downloads a model ahead of its first use, reporting progress through
+/// `asr-download-progress` events. Does nothing when it is already installed.
+#[tauri::command]
+#[specta::specta]
+pub async fn download_asr_model(app: AppHandle, id: String) -> Result<(), String> {
+    let spec = find_model(&id)?;
+    let _download_guard = crate::download::lock(&download_key(&spec.id)).await;
+    let root = model_root(&app)?;
+    if model_present(&spec, &root) {
+        return Ok(());
+    }
+    download_model(&app, &spec, &root).await
+}
+
+/// Pauses a running model download, like `pause_tts_download`.
+#[tauri::command]
+#[specta::specta]
+pub fn pause_asr_download(id: String) -> Result<(), String> {
+    crate::download::pause(&download_key(&id))
+}
+
+/// Continues a paused model download.
+#[tauri::command]
+#[specta::specta]
+pub fn resume_asr_download(id: String) -> Result<(), String> {
+    crate::download::resume(&download_key(&id))
+}
+
+/// Stops a running or paused model download and deletes the partial file.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_asr_download(id: String) -> Result<(), String> {
+    crate::download::cancel(&download_key(&id))
+}
+
+/// Unloads a model and deletes its files, cancelling a download of it.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_asr_model(app: AppHandle, id: String) -> Result<(), String> {
+    let spec = find_model(&id)?;
+    let key = download_key(&spec.id);
+    // Fails when nothing is downloading, which is fine.
+    let _ = crate::download::cancel(&key);
+    let _download_guard = crate::download::lock(&key).await;
+    let _load_guard = LOAD_LOCK.lock().await;
+    unload(&spec.id);
+    let directory = model_root(&app)?.join(&spec.directory);
+    if directory.exists() {
+        tokio::fs::remove_dir_all(&directory)
+            .await
+            .map_err(|err| format!("delete ASR model {id}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Installs a catalog model from its release `.tar.bz2` or extracted
+/// directory, for models without a pinned checksum and machines that cannot
+/// download. The model is recognized by the directory name.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_asr_model(app: AppHandle, path: String) -> Result<AsrModelInfo, String> {
+    let root = model_root(&app)?;
+    let catalog = catalog()?;
+    let _load_guard = LOAD_LOCK.lock().await;
+
+    let import_root = root.clone();
+    let spec = tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&import_root)
+            .map_err(|err| format!("create ASR model directory: {err}"))?;
+        let staging = import_root.join(".import");
+        let _ = std::fs::remove_dir_all(&staging);
+        std::fs::create_dir_all(&staging)
+            .map_err(|err| format!("create import directory: {err}"))?;
+        let result = (|| {
+            let (staged, directory) = stage_import(Path::new(&path), &staging)?;
+            let spec = catalog
+                .into_iter()
+                .find(|spec| spec.directory == directory)
+                .ok_or_else(|| format!("{directory} is not a known speech recognition model"))?;
+            let files = model_files(&staged);
+            if let Some(missing) = spec
+                .required_files
+                .iter()
+                .find(|name| !files.contains(name))
+            {
+                return Err(format!("{missing} is missing"));
+            }
+            let destination = import_root.join(&spec.directory);
+            if destination.exists() {
+                std::fs::remove_dir_all(&destination)
+                    .map_err(|err| format!("replace old ASR model: {err}"))?;
+            }
+            std::fs::rename(&staged, &destination)
+                .map_err(|err| format!("move imported model: {err}"))?;
+            write_checksums(&destination)?;
+            Ok(spec)
+        })();
+        let _ = std::fs::remove_dir_all(&staging);
+        result
+    })
+    .await
+    .map_err(|err| format!("import ASR model task failed: {err}"))?
+    .map_err(|err| format!("import ASR model: {err}"))?;
+
+    unload(&spec.id);
+    Ok(model_info(spec, &root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_bundled_catalog() {
+        let catalog = catalog().unwrap();
+        for spec in &catalog {
+            let files = &spec.files;
+            let needed = match spec.family {
+                AsrFamily::SenseVoice => vec![files.model.as_ref()],
+                AsrFamily::Whisper => vec![files.encoder.as_ref(), files.decoder.as_ref()],
+            };
+            assert!(needed
+                .into_iter()
+                .chain([Some(&files.tokens)])
+                .all(|name| name.is_some_and(|name| spec.required_files.contains(name))));
+        }
+        let sense_voice = find_model("sense-voice").unwrap();
+        assert_eq!(
+            model_language(&sense_voice, Some("zh-Hant")).as_deref(),
+            Some("zh")
+        );
+        assert_eq!(model_language(&sense_voice, Some("fr")), None);
+        let whisper = find_model("whisper-base").unwrap();
+        assert_eq!(model_language(&whisper, Some("yue")).as_deref(), Some("zh"));
+        assert_eq!(
+            model_language(&whisper, Some("pt-BR")).as_deref(),
+            Some("pt")
+        );
+        assert_eq!(model_language(&whisper, None), None);
+    }
+
+    #[test]
+    fn joins_segments_by_script() {
+        let segment = |text: &str| AsrSegment {
+            start_ms: 0.0,
+            end_ms: 0.0,
+            text: text.to_string(),
+        };
+        assert_eq!(
+            join_segments(&[
+                segment("你好。"),
+                segment("我很好"),
+                segment("OK."),
+                segment("Bye")
+            ]),
+            "你好。我很好OK. Bye"
+        );
+    }
+}
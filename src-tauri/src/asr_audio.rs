@@ -0,0 +1,414 @@
+// Audio files for offline speech recognition, read into mono f32 at the
+// 16 kHz the models take. WAV and Ogg/Opus, what most voice messages are,
+// have their own decoders here, Opus through the libopus the export already
+// links; symphonia, which has no Opus, reads MP3, Ogg/Vorbis and M4A.
+//
+// Recordings are then cut into utterances at pauses, found by loudness
+// against the noise floor of the recording, so every segment gets its own
+// timestamps and stays short enough for Whisper's 30 s window.
+
+use audiopus::coder::Decoder as OpusDecoder;
+use audiopus::{Channels, SampleRate};
+use ogg::reading::PacketReader;
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// The sample rate of the recognition models.
+pub const SAMPLE_RATE: i32 = 16_000;
+
+/// Loudness is measured over frames this long.
+const FRAME_MS: usize = 30;
+/// Frames this much louder than the noise floor count as speech.
+const SPEECH_OVER_FLOOR_DB: f32 = 12.0;
+/// Frames this much quieter than the loudest one still count as speech, for
+/// recordings without pauses, whose floor is the speech itself.
+const SPEECH_UNDER_PEAK_DB: f32 = 20.0;
+/// Frames quieter than this never count as speech, however quiet the floor.
+const MIN_SPEECH_DB: f32 = -55.0;
+/// Pauses shorter than this stay inside an utterance.
+const MIN_PAUSE_MS: usize = 400;
+/// Bursts of sound shorter than this, like clicks, are dropped.
+const MIN_SPEECH_MS: usize = 150;
+/// Kept around every utterance so that soft onsets and endings survive.
+const PADDING_MS: usize = 200;
+/// Longer utterances are split at their quietest frame.
+const MAX_SEGMENT_MS: usize = 20_000;
+
+/// Reads `path` as mono samples at `SAMPLE_RATE`.
+pub fn decode_file(path: &Path) -> Result<Vec<f32>, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("read {}: {err}", path.display()))?;
+    let (samples, sample_rate) = if bytes.starts_with(b"RIFF") {
+        decode_wav(&bytes)?
+    } else if bytes.starts_with(b"OggS") && is_ogg_opus(&bytes) {
+        decode_ogg_opus(&bytes)?
+    } else {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        decode_compressed(bytes, extension)?
+    };
+    crate::tts_audio::resample(&samples, sample_rate, SAMPLE_RATE)
+}
+
+/// Averages interleaved `channels` into one.
+fn downmix(samples: Vec<f32>, channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples;
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Decodes PCM WAV of 8 to 32 bits and float WAV, with any number of
+/// channels.
+pub fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, i32), String> {
+    if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+        return Err("not a WAV file".to_string());
+    }
+    let u16_at = |offset: usize| {
+        bytes
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let u32_at = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    // (format, channels, sample rate, bits per sample)
+    let mut format = None;
+    let mut offset = 12;
+    while let (Some(id), Some(size)) = (bytes.get(offset..offset + 4), u32_at(offset + 4)) {
+        let body = offset + 8;
+        match id {
+            b"fmt " => {
+                let mut tag = u16_at(body).ok_or("truncated WAV format")?;
+                // WAVE_FORMAT_EXTENSIBLE keeps the real tag in its sub-format.
+                if tag == 0xFFFE {
+                    tag = u16_at(body + 24).ok_or("truncated WAV format")?;
+                }
+                format = Some((
+                    tag,
+                    u16_at(body + 2).ok_or("truncated WAV format")? as usize,
+                    u32_at(body + 4).ok_or("truncated WAV format")?,
+                    u16_at(body + 14).ok_or("truncated WAV format")?,
+                ));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) =
+                    format.ok_or("WAV data comes before its format")?;
+                // Streamed WAV leaves the size at its maximum; read to the end.
+                let end = body.saturating_add(size as usize).min(bytes.len());
+                let data = &bytes[body..end];
+                let samples = match (tag, bits) {
+                    (1, 8) => data
+                        .iter()
+                        .map(|&b| (f32::from(b) - 128.0) / 128.0)
+                        .collect(),
+                    (1, 16) => data
+                        .chunks_exact(2)
+                        .map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32_768.0)
+                        .collect(),
+                    (1, 24) => data
+                        .chunks_exact(3)
+                        .map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0)
+                        .collect(),
+                    (1, 32) => data
+                        .chunks_exact(4)
+                        .map(|b| {
+                            i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+                        })
+                        .collect(),
+                    (3, 32) => data
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect(),
+                    (3, 64) => data
+                        .chunks_exact(8)
+                        .map(|b| f64::from_le_bytes(b.try_into().unwrap_or_default()) as f32)
+                        .collect(),
+                    _ => return Err(format!("unsupported WAV encoding {tag} with {bits} bits")),
+                };
+                if channels == 0 || sample_rate == 0 {
+                    return Err("invalid WAV format".to_string());
+                }
+                return Ok((downmix(samples, channels), sample_rate as i32));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        offset = body.saturating_add(size as usize + (size as usize & 1));
+    }
+    Err("WAV file has no audio data".to_string())
+}
+
+fn is_ogg_opus(bytes: &[u8]) -> bool {
+    // The first page carries the identification header, right after the
+    // 27-byte page header and its segment table.
+    let segments = bytes.get(26).copied().unwrap_or(0) as usize;
+    bytes
+        .get(27 + segments..27 + segments + 8)
+        .is_some_and(|magic| magic == b"OpusHead")
+}
+
+/// Decodes Ogg/Opus at 48 kHz, as Opus always decodes, minus the pre-skip.
+pub fn decode_ogg_opus(bytes: &[u8]) -> Result<(Vec<f32>, i32), String> {
+    let mut reader = PacketReader::new(std::io::Cursor::new(bytes));
+    let mut next = || {
+        reader
+            .read_packet()
+            .map_err(|err| format!("read Ogg page: {err}"))
+    };
+    let head = next()?.ok_or("empty Ogg file")?.data;
+    if !head.starts_with(b"OpusHead") || head.len() < 19 {
+        return Err("not an Ogg/Opus file".to_string());
+    }
+    let channels = match head[9] {
+        1 => Channels::Mono,
+        2 => Channels::Stereo,
+        count => return Err(format!("Opus with {count} channels is not supported")),
+    };
+    let pre_skip = u16::from_le_bytes([head[10], head[11]]) as usize;
+    let mut decoder = OpusDecoder::new(SampleRate::Hz48000, channels)
+        .map_err(|err| format!("create Opus decoder: {err}"))?;
+    let channels = channels as usize;
+    // The comment header.
+    next()?;
+    let mut samples = Vec::new();
+    // Room for the longest Opus packet, 120 ms.
+    let mut output = vec![0.0_f32; 5_760 * channels];
+    let mut end_granule = None;
+    while let Some(packet) = next()? {
+        if packet.last_in_stream() {
+            end_granule = Some(packet.absgp_page() as usize);
+        }
+        if packet.data.is_empty() {
+            continue;
+        }
+        let input = audiopus::packet::Packet::try_from(&packet.data)
+            .map_err(|err| format!("invalid Opus packet: {err}"))?;
+        let output_signals = audiopus::MutSignals::try_from(&mut output)
+            .map_err(|err| format!("decode Opus: {err}"))?;
+        let decoded = decoder
+            .decode_float(Some(input), output_signals, false)
+            .map_err(|err| format!("decode Opus: {err}"))?;
+        samples.extend_from_slice(&output[..decoded * channels]);
+    }
+    let mut samples = downmix(samples, channels);
+    // The granule of the last page marks where the audio really ends.
+    if let Some(end) = end_granule {
+        samples.truncate(end.max(pre_skip));
+    }
+    samples.drain(..pre_skip.min(samples.len()));
+    Ok((samples, 48_000))
+}
+
+/// Decodes MP3, Ogg/Vorbis and AAC in M4A with symphonia; `extension` of
+/// the file helps it tell the container.
+pub fn decode_compressed(
+    bytes: Vec<u8>,
+    extension: Option<&str>,
+) -> Result<(Vec<f32>, i32), String> {
+    let source = MediaSourceStream::new(Box::new(std::io::Cursor::new(bytes)), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|err| format!("unsupported audio format: {err}"))?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("the file has no audio track")?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|err| format!("unsupported audio codec: {err}"))?;
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // The end of the file, or of the stream in chained Ogg.
+            Err(SymphoniaError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(err) => return Err(format!("read audio: {err}")),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A damaged frame; the rest is still worth recognizing.
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(err) => return Err(format!("decode audio: {err}")),
+        };
+        let spec = *decoded.spec();
+        sample_rate = Some(spec.rate);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(downmix(buffer.samples().to_vec(), spec.channels.count()));
+    }
+    let sample_rate = sample_rate.ok_or("the audio has no sample rate")?;
+    Ok((samples, sample_rate as i32))
+}
+
+/// The utterances of `samples` at `sample_rate`, as sample ranges; see the
+/// module comment.
+pub fn speech_segments(samples: &[f32], sample_rate: i32) -> Vec<Range<usize>> {
+    let frame = (sample_rate.max(1) as usize * FRAME_MS / 1000).max(1);
+    let loudness = samples
+        .chunks(frame)
+        .map(|chunk| {
+            let power =
+                chunk.iter().map(|sample| sample * sample).sum::<f32>() / chunk.len() as f32;
+            10.0 * power.max(1e-10).log10()
+        })
+        .collect::<Vec<_>>();
+    if loudness.is_empty() {
+        return Vec::new();
+    }
+    let mut sorted = loudness.clone();
+    sorted.sort_by(f32::total_cmp);
+    let floor = sorted[sorted.len() / 10];
+    let peak = sorted[sorted.len() - 1];
+    let threshold = (floor + SPEECH_OVER_FLOOR_DB)
+        .min(peak - SPEECH_UNDER_PEAK_DB)
+        .max(MIN_SPEECH_DB);
+
+    let frames = |ms: usize| ms.div_ceil(FRAME_MS);
+    let mut utterances: Vec<Range<usize>> = Vec::new();
+    let mut index = 0;
+    while index < loudness.len() {
+        if loudness[index] < threshold {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < loudness.len() && loudness[index] >= threshold {
+            index += 1;
+        }
+        match utterances.last_mut() {
+            Some(last) if start - last.end < frames(MIN_PAUSE_MS) => last.end = index,
+            _ => utterances.push(start..index),
+        }
+    }
+    utterances.retain(|utterance| utterance.len() >= frames(MIN_SPEECH_MS));
+
+    let mut segments = Vec::new();
+    for utterance in utterances {
+        let mut start = utterance.start.saturating_sub(frames(PADDING_MS));
+        let end = (utterance.end + frames(PADDING_MS)).min(loudness.len());
+        while end - start > frames(MAX_SEGMENT_MS) {
+            // The quietest frame in the second half of the longest segment.
+            let from = start + frames(MAX_SEGMENT_MS) / 2;
+            let to = start + frames(MAX_SEGMENT_MS);
+            let cut = (from..to)
+                .min_by(|a, b| loudness[*a].total_cmp(&loudness[*b]))
+                .unwrap_or(to);
+            segments.push(start..cut);
+            start = cut;
+        }
+        segments.push(start..end);
+    }
+    segments
+        .into_iter()
+        .map(|segment| segment.start * frame..(segment.end * frame).min(samples.len()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(seconds: f32) -> Vec<f32> {
+        (0..(seconds * SAMPLE_RATE as f32) as usize)
+            .map(|index| 0.3 * (index as f32 * 0.07).sin())
+            .collect()
+    }
+
+    fn silence(seconds: f32) -> Vec<f32> {
+        // A little noise, as recordings have.
+        (0..(seconds * SAMPLE_RATE as f32) as usize)
+            .map(|index| if index % 2 == 0 { 0.001 } else { -0.001 })
+            .collect()
+    }
+
+    #[test]
+    fn reads_pcm_and_float_wav() {
+        let wav = crate::tts_audio::encode_wav(&[0.0, 0.5, -0.5], 22_050);
+        let (samples, sample_rate) = decode_wav(&wav).unwrap();
+        assert_eq!(sample_rate, 22_050);
+        assert!(samples
+            .iter()
+            .zip([0.0, 0.5, -0.5])
+            .all(|(sample, expected)| (sample - expected).abs() < 1e-3));
+
+        // Stereo float with a chunk before `fmt `, as some recorders write.
+        let mut wav = b"RIFF\0\0\0\0WAVELIST\x03\0\0\0abc\0fmt \x10\0\0\0".to_vec();
+        wav.extend_from_slice(&[3, 0, 2, 0]);
+        wav.extend_from_slice(&8_000_u32.to_le_bytes());
+        wav.extend_from_slice(&64_000_u32.to_le_bytes());
+        wav.extend_from_slice(&[8, 0, 32, 0]);
+        wav.extend_from_slice(b"data\xff\xff\xff\xff");
+        for sample in [0.25_f32, 0.75, -1.0, 0.0] {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        assert_eq!(decode_wav(&wav), Ok((vec![0.5, -0.5], 8_000)));
+        assert!(decode_wav(b"RIFF\0\0\0\0WAVE").is_err());
+    }
+
+    #[test]
+    fn cuts_recordings_at_pauses() {
+        let samples = [
+            silence(1.0),
+            tone(2.0),
+            silence(0.2),
+            tone(1.0),
+            silence(1.5),
+            tone(1.0),
+        ]
+        .concat();
+        let seconds = |segments: Vec<Range<usize>>| {
+            segments
+                .into_iter()
+                .map(|segment| {
+                    let second =
+                        |sample: usize| (sample as f32 / SAMPLE_RATE as f32 * 10.0).round() / 10.0;
+                    (second(segment.start), second(segment.end))
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            seconds(speech_segments(&samples, SAMPLE_RATE)),
+            [(0.8, 4.4), (5.5, 6.7)]
+        );
+
+        let long = speech_segments(&tone(50.0), SAMPLE_RATE);
+        assert_eq!(long.first().map(|segment| segment.start), Some(0));
+        assert_eq!(
+            long.last().map(|segment| segment.end),
+            Some(50 * SAMPLE_RATE as usize)
+        );
+        assert!(long
+            .iter()
+            .all(|segment| segment.len() <= MAX_SEGMENT_MS * SAMPLE_RATE as usize / 1000));
+        assert!(speech_segments(&silence(2.0), SAMPLE_RATE).is_empty());
+    }
+}
@@ -1,9 +1,15 @@
+use once_cell::sync::{Lazy, OnceCell};
 use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
 use tauri::Manager;
 use tauri::{path::BaseDirectory, AppHandle};
 
 use serde::{Deserialize, Serialize};
 
+use crate::app_rules::{AppRule, InsertionMode};
+use crate::lang::LangDetectionBackend;
 use crate::APP_HANDLE;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, specta::Type, tauri_specta::Event)]
@@ -47,6 +53,414 @@ pub struct Config {
     pub hide_the_icon_in_the_dock: Option<bool>,
     pub proxy: Option<ProxyConfig>,
     pub use_compact_lookup: Option<bool>,
+    pub disable_collecting_statistics: Option<bool>,
+    pub insertion_mode: Option<InsertionMode>,
+    pub app_rules: Option<Vec<AppRule>>,
+    pub language_detection_allowlist: Option<Vec<String>>,
+    pub language_detection_backend: Option<LangDetectionBackend>,
+    /// Cap on model downloads in KiB/s; 0 or unset means unlimited.
+    pub model_download_rate_limit: Option<u64>,
+    /// Preferred local TTS voice by language, as ids from `list_tts_voices`.
+    pub local_tts_voices: Option<BTreeMap<String, String>>,
+    /// Size of the synthesized speech cache in MiB, 100 when unset; 0 turns
+    /// the cache off.
+    pub local_tts_cache_size: Option<u64>,
+    /// Seconds a local TTS engine stays loaded unused, 600 when unset; 0
+    /// keeps engines loaded.
+    pub local_tts_idle_unload: Option<u64>,
+    /// Local TTS engines kept loaded at most, 2 when unset.
+    pub local_tts_max_engines: Option<u32>,
+    /// Threads per local TTS engine; 0 or unset picks them by core count.
+    pub local_tts_threads: Option<u32>,
+    /// Sentences a local TTS engine synthesizes in one batch; 0 or unset
+    /// picks them by core count.
+    pub local_tts_max_sentences: Option<u32>,
+    /// Whether to load the local TTS model for the default target language
+    /// at startup.
+    pub local_tts_warm_up: Option<bool>,
+    pub default_target_language: Option<String>,
+}
+
+/// Environment variable that points at a policy file in a non-default place.
+const POLICY_PATH_ENV: &str = "NEXTAI_TRANSLATOR_POLICY";
+/// Environment equivalents of `--config-dir` and `--profile`.
+const CONFIG_DIR_ENV: &str = "NEXTAI_TRANSLATOR_CONFIG_DIR";
+const PROFILE_ENV: &str = "NEXTAI_TRANSLATOR_PROFILE";
+/// Every other `NEXTAI_*` variable overrides a setting, e.g.
+/// `NEXTAI_HOTKEY=Alt+T` or `NEXTAI_PROXY__SERVER=10.0.0.1` (`__` descends
+/// into nested objects). `NEXTAI_TRANSLATOR_*` is reserved for the app itself.
+const ENV_OVERRIDE_PREFIX: &str = "NEXTAI_";
+const RESERVED_ENV_PREFIX: &str = "NEXTAI_TRANSLATOR_";
+
+/// The layer a setting's effective value comes from, in increasing order of
+/// precedence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigSource {
+    PolicyDefault,
+    File,
+    Environment,
+    CommandLine,
+    Policy,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValueSource {
+    /// Dot-separated path of the setting, e.g. `proxy.server`.
+    pub key: String,
+    pub source: ConfigSource,
+}
+
+/// Settings given at launch, through the command line or the environment.
+/// Override values are kept as raw strings and only interpreted once the
+/// value they replace is known.
+#[derive(Debug, Default)]
+struct LaunchOverrides {
+    config_dir: Option<PathBuf>,
+    profile: Option<String>,
+    environment: Vec<(String, String)>,
+    command_line: Vec<(String, String)>,
+}
+
+static LAUNCH_OVERRIDES: OnceCell<LaunchOverrides> = OnceCell::new();
+
+fn launch_overrides() -> &'static LaunchOverrides {
+    LAUNCH_OVERRIDES.get_or_init(LaunchOverrides::default)
+}
+
+/// Reads `--config-dir <dir>`, `--profile <name>` and any number of
+/// `--set key=value` from the process arguments, plus the `NEXTAI_*`
+/// environment. Must run before the first config read.
+pub fn init_launch_overrides(args: &[String]) {
+    let mut overrides = LaunchOverrides {
+        config_dir: std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from),
+        profile: std::env::var(PROFILE_ENV).ok(),
+        environment: env_overrides(std::env::vars()),
+        ..Default::default()
+    };
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        if !matches!(flag, "--config-dir" | "--profile" | "--set") {
+            continue;
+        }
+        let value = match inline_value.or_else(|| args.next().cloned()) {
+            Some(value) => value,
+            None => {
+                println!("missing value for {}", flag);
+                continue;
+            }
+        };
+        match flag {
+            "--config-dir" => overrides.config_dir = Some(PathBuf::from(value)),
+            "--profile" => overrides.profile = Some(value),
+            _ => match value.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => overrides
+                    .command_line
+                    .push((key.trim().to_string(), value.to_string())),
+                _ => println!("ignoring --set {}: expected key=value", value),
+            },
+        }
+    }
+    if let Some(profile) = overrides.profile.take() {
+        if is_valid_profile(&profile) {
+            overrides.profile = Some(profile);
+        } else {
+            println!(
+                "ignoring profile {:?}: expected a plain folder name",
+                profile
+            );
+        }
+    }
+    if LAUNCH_OVERRIDES.set(overrides).is_err() {
+        println!("launch overrides were initialized twice");
+    }
+}
+
+/// A profile names a folder under `profiles/`, so it must not be able to
+/// point anywhere else.
+fn is_valid_profile(profile: &str) -> bool {
+    let mut components = Path::new(profile).components();
+    !profile.contains(['/', '\\'])
+        && matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        )
+}
+
+fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut overrides = vars
+        .filter(|(name, _)| {
+            name.starts_with(ENV_OVERRIDE_PREFIX) && !name.starts_with(RESERVED_ENV_PREFIX)
+        })
+        .map(|(name, value)| {
+            let path = name[ENV_OVERRIDE_PREFIX.len()..]
+                .split("__")
+                .map(env_segment_to_key)
+                .collect::<Vec<_>>()
+                .join(".");
+            (path, value)
+        })
+        .filter(|(path, _)| !path.is_empty())
+        .collect::<Vec<_>>();
+    // Environment iteration order is unspecified; make layering deterministic.
+    overrides.sort();
+    overrides
+}
+
+/// `API_URL` -> `apiUrl`. Keys are matched case-insensitively later, so this
+/// still lands on `apiURL`.
+fn env_segment_to_key(segment: &str) -> String {
+    let mut key = String::new();
+    for (index, word) in segment.split('_').filter(|w| !w.is_empty()).enumerate() {
+        let word = word.to_lowercase();
+        if index == 0 {
+            key.push_str(&word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                key.extend(first.to_uppercase());
+                key.push_str(chars.as_str());
+            }
+        }
+    }
+    key
+}
+
+/// Settings an administrator pushes to every user of a machine. Both maps use
+/// the same camelCase keys as `config.json`: `locked` values always win over
+/// whatever the user configured, `defaults` only fill in keys the user never
+/// set.
+#[derive(Debug, Default, Deserialize)]
+struct Policy {
+    #[serde(default)]
+    locked: Map<String, Value>,
+    #[serde(default)]
+    defaults: Map<String, Value>,
+}
+
+// The policy is read once per process: it is owned by the administrator, not
+// by the settings UI, so it cannot change underneath a running app.
+static POLICY: Lazy<Policy> = Lazy::new(load_policy);
+
+fn policy_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(POLICY_PATH_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Some(PathBuf::from("/etc/nextai-translator/policy.json"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from(
+            "/Library/Application Support/nextai-translator/policy.json",
+        ))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let program_data =
+            std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        Some(
+            PathBuf::from(program_data)
+                .join("nextai-translator")
+                .join("policy.json"),
+        )
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+fn load_policy() -> Policy {
+    let path = match policy_path() {
+        Some(path) if path.exists() => path,
+        _ => return Policy::default(),
+    };
+    // A broken policy file must not keep the app from starting; the
+    // administrator finds the reason in the log.
+    let policy = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str::<Policy>(&content).map_err(|e| e.to_string()));
+    match policy {
+        Ok(policy) => {
+            println!("loaded policy file {}", path.display());
+            policy
+        }
+        Err(e) => {
+            println!("ignoring policy file {}: {}", path.display(), e);
+            Policy::default()
+        }
+    }
+}
+
+fn parse_object(content: &str) -> Map<String, Value> {
+    match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn same_key(a: &str, b: &str) -> bool {
+    let normalize = |key: &str| key.replace(['_', '-'], "").to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+/// Sets the value at a dot-separated path, creating intermediate objects and
+/// reusing existing keys that differ only in case. `value` receives the value
+/// being replaced. Returns the path actually written.
+fn set_path(
+    map: &mut Map<String, Value>,
+    path: &str,
+    value: impl FnOnce(Option<&Value>) -> Value,
+) -> String {
+    let segments = path.split('.').collect::<Vec<_>>();
+    let mut resolved = Vec::with_capacity(segments.len());
+    let mut current = map;
+    for (index, segment) in segments.iter().enumerate() {
+        let key = current
+            .keys()
+            .find(|key| same_key(key, segment))
+            .cloned()
+            .unwrap_or_else(|| segment.to_string());
+        resolved.push(key.clone());
+        if index == segments.len() - 1 {
+            let value = value(current.get(&key));
+            current.insert(key, value);
+            break;
+        }
+        let entry = current
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        current = entry.as_object_mut().unwrap();
+    }
+    resolved.join(".")
+}
+
+/// Interprets an override string as JSON (numbers, booleans, objects) unless
+/// the value it replaces is a string - `port` is `"8080"`, not `8080`.
+fn override_value(previous: Option<&Value>, raw: &str) -> Value {
+    if let Some(Value::String(_)) = previous {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn get_path<'a>(map: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut segments = path.split('.');
+    let mut value = map.get(segments.next()?)?;
+    for segment in segments {
+        value = value.as_object()?.get(segment)?;
+    }
+    Some(value)
+}
+
+fn remove_path(map: &mut Map<String, Value>, path: &str) {
+    match path.rsplit_once('.') {
+        None => {
+            map.remove(path);
+        }
+        Some((parent, key)) => {
+            let mut current = map;
+            for segment in parent.split('.') {
+                match current.get_mut(segment).and_then(Value::as_object_mut) {
+                    Some(next) => current = next,
+                    None => return,
+                }
+            }
+            current.remove(key);
+        }
+    }
+}
+
+fn record_source(sources: &mut BTreeMap<String, ConfigSource>, path: String, source: ConfigSource) {
+    let prefix = format!("{}.", path);
+    sources.retain(|key, _| !key.starts_with(&prefix));
+    sources.insert(path, source);
+}
+
+/// Builds the settings in effect from all layers: policy defaults, the user's
+/// `config.json`, `NEXTAI_*` variables, `--set` arguments and finally the
+/// locked policy values. Also returns which layer supplied each value.
+fn layer_config(
+    policy: &Policy,
+    overrides: &LaunchOverrides,
+    file: Map<String, Value>,
+) -> (Map<String, Value>, BTreeMap<String, ConfigSource>) {
+    let mut effective = Map::new();
+    let mut sources = BTreeMap::new();
+    let top_level_layers = [
+        (&policy.defaults, ConfigSource::PolicyDefault),
+        (&file, ConfigSource::File),
+    ];
+    for (layer, source) in top_level_layers {
+        for (key, value) in layer {
+            effective.insert(key.clone(), value.clone());
+            record_source(&mut sources, key.clone(), source);
+        }
+    }
+    let path_layers = [
+        (&overrides.environment, ConfigSource::Environment),
+        (&overrides.command_line, ConfigSource::CommandLine),
+    ];
+    for (layer, source) in path_layers {
+        for (path, raw) in layer {
+            let path = set_path(&mut effective, path, |previous| {
+                override_value(previous, raw)
+            });
+            record_source(&mut sources, path, source);
+        }
+    }
+    for (key, value) in &policy.locked {
+        effective.insert(key.clone(), value.clone());
+        record_source(&mut sources, key.clone(), ConfigSource::Policy);
+    }
+    (effective, sources)
+}
+
+/// Drops values the UI is about to save for settings that another layer
+/// overrides, keeping whatever the file held for them before. Otherwise a
+/// one-off `--set` or a locked policy value would leak into `config.json`.
+/// Policy defaults the user left alone are dropped too, so that later changes
+/// to the default still reach them.
+fn strip_overridden(
+    mut incoming: Map<String, Value>,
+    previous: &Map<String, Value>,
+    sources: &BTreeMap<String, ConfigSource>,
+    policy_defaults: &Map<String, Value>,
+) -> Map<String, Value> {
+    let unchanged_defaults = sources
+        .iter()
+        .filter(|(_, source)| **source == ConfigSource::PolicyDefault)
+        .filter(|(path, _)| get_path(&incoming, path) == get_path(policy_defaults, path))
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    for path in unchanged_defaults {
+        remove_path(&mut incoming, &path);
+    }
+    let overridden = sources.iter().filter(|(_, source)| {
+        matches!(
+            source,
+            ConfigSource::Environment | ConfigSource::CommandLine | ConfigSource::Policy
+        )
+    });
+    for (path, _) in overridden {
+        match get_path(previous, path) {
+            Some(value) => {
+                set_path(&mut incoming, path, |_| value.clone());
+            }
+            None => remove_path(&mut incoming, path),
+        }
+    }
+    incoming
 }
 
 static CONFIG_CACHE: Mutex<Option<Config>> = Mutex::new(None);
@@ -71,7 +485,7 @@ pub fn _get_config_by_app(app: &AppHandle) -> Result<Config, Box<dyn std::error:
     if let Some(config_cache) = &*CONFIG_CACHE.lock() {
         return Ok(config_cache.clone());
     }
-    let config_content = get_config_content_by_app(app)?;
+    let config_content = get_effective_config_content_by_app(app)?;
     // A config that fails typed deserialization (e.g. a field written with a
     // wrong type by some older version) must never prevent startup: the very
     // first get_config() happens while building the tray, before any window
@@ -86,6 +500,19 @@ pub fn _get_config_by_app(app: &AppHandle) -> Result<Config, Box<dyn std::error:
     Ok(config)
 }
 
+/// Whether usage events may be sent to the analytics backend. Also asked by
+/// the panic hook, which can run before the app is set up; nothing is sent
+/// until the settings in effect are known.
+pub fn is_telemetry_enabled() -> bool {
+    let Some(app) = APP_HANDLE.get() else {
+        return false;
+    };
+    !get_config_by_app(app)
+        .ok()
+        .and_then(|config| config.disable_collecting_statistics)
+        .unwrap_or(false)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn clear_config_cache() {
@@ -96,13 +523,98 @@ pub fn clear_config_cache() {
 #[specta::specta]
 pub fn get_config_content() -> String {
     if let Some(app) = APP_HANDLE.get() {
-        return get_config_content_by_app(app).unwrap_or_else(|_| "{}".to_string());
+        return get_effective_config_content_by_app(app).unwrap_or_else(|_| "{}".to_string());
     } else {
         return "{}".to_string();
     }
 }
 
-pub fn get_config_content_by_app(app: &AppHandle) -> Result<String, String> {
+/// Top-level settings keys fixed by the administrator's policy file. The
+/// settings UI renders them read-only.
+#[tauri::command]
+#[specta::specta]
+pub fn get_locked_config_keys() -> Vec<String> {
+    POLICY.locked.keys().cloned().collect()
+}
+
+/// Persists settings edited in the UI. Values for locked keys are discarded.
+#[tauri::command]
+#[specta::specta]
+pub fn save_config_content(content: String) -> Result<(), String> {
+    let app = APP_HANDLE.get().ok_or("app is not initialized")?;
+    let incoming = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => return Err("config must be a JSON object".to_string()),
+        Err(e) => return Err(format!("failed to parse config: {}", e)),
+    };
+    let previous = parse_object(&get_config_content_by_app(app)?);
+    let (_, sources) = layer_config(&POLICY, launch_overrides(), previous.clone());
+    let config = strip_overridden(incoming, &previous, &sources, &POLICY.defaults);
+    let content = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    write_config_file(&get_config_dir_by_app(app), &content)?;
+    clear_config_cache();
+    Ok(())
+}
+
+/// Which layer supplies each setting currently in effect, for debugging
+/// launch overrides and policies.
+#[tauri::command]
+#[specta::specta]
+pub fn get_config_sources() -> Result<Vec<ConfigValueSource>, String> {
+    let app = APP_HANDLE.get().ok_or("app is not initialized")?;
+    let file = parse_object(&get_config_content_by_app(app)?);
+    let (_, sources) = layer_config(&POLICY, launch_overrides(), file);
+    Ok(sources
+        .into_iter()
+        .map(|(key, source)| ConfigValueSource { key, source })
+        .collect())
+}
+
+/// `config.json` with policy and launch overrides applied, i.e. the settings
+/// actually in effect.
+pub fn get_effective_config_content_by_app(app: &AppHandle) -> Result<String, String> {
+    let file = parse_object(&get_config_content_by_app(app)?);
+    let (effective, _) = layer_config(&POLICY, launch_overrides(), file);
+    serde_json::to_string(&effective).map_err(|e| e.to_string())
+}
+
+// Write-then-rename so config.json is replaced atomically: a truncated file
+// left behind by a crash mid-write would otherwise be reset to defaults on the
+// next launch.
+fn write_config_file(config_dir: &Path, content: &str) -> Result<(), String> {
+    let tmp_path = config_dir.join("config.json.tmp");
+    std::fs::write(&tmp_path, content).map_err(|e| format!("failed to write config: {}", e))?;
+    std::fs::rename(&tmp_path, config_dir.join("config.json"))
+        .map_err(|e| format!("failed to replace config: {}", e))
+}
+
+pub fn get_config_dir_by_app(app: &AppHandle) -> PathBuf {
+    let overrides = launch_overrides();
+    let config_dir = match (&overrides.config_dir, crate::paths::portable_config_dir()) {
+        (Some(config_dir), _) => config_dir.clone(),
+        (None, Some(portable_config_dir)) => portable_config_dir,
+        (None, None) => get_default_config_dir_by_app(app),
+    };
+    let config_dir = match &overrides.profile {
+        Some(profile) => config_dir.join("profiles").join(profile),
+        None => config_dir,
+    };
+    // A mistyped `--config-dir` or a read-only folder must not keep the app
+    // from starting.
+    match std::fs::create_dir_all(&config_dir) {
+        Ok(()) => config_dir,
+        Err(e) => {
+            println!(
+                "cannot use config directory {} ({}), using the default one",
+                config_dir.display(),
+                e
+            );
+            get_default_config_dir_by_app(app)
+        }
+    }
+}
+
+fn get_default_config_dir_by_app(app: &AppHandle) -> PathBuf {
     let app_paths = app.path();
     let app_config_dir = app_paths
         .resolve("xyz.yetone.apps.openai-translator", BaseDirectory::Config)
@@ -130,6 +642,12 @@ pub fn get_config_content_by_app(app: &AppHandle) -> Result<String, String> {
             std::fs::create_dir_all(&app_config_dir).unwrap();
         }
     }
+    app_config_dir
+}
+
+/// Raw contents of the user's `config.json`, without the policy applied.
+pub fn get_config_content_by_app(app: &AppHandle) -> Result<String, String> {
+    let app_config_dir = get_config_dir_by_app(app);
     let config_path = app_config_dir.join("config.json");
     if config_path.exists() {
         match std::fs::read_to_string(&config_path) {
@@ -156,3 +674,125 @@ pub fn get_config_content_by_app(app: &AppHandle) -> Result<String, String> {
         Ok("{}".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    fn policy() -> Policy {
+        Policy {
+            locked: object(json!({ "automaticCheckForUpdates": false })),
+            defaults: object(json!({ "apiURL": "https://llm.example.com" })),
+        }
+    }
+
+    fn overrides() -> LaunchOverrides {
+        LaunchOverrides {
+            environment: env_overrides(
+                [
+                    ("NEXTAI_API_URL", "https://env.example.com"),
+                    ("NEXTAI_PROXY__PORT", "3128"),
+                    ("NEXTAI_TRANSLATOR_POLICY", "/tmp/policy.json"),
+                    ("PATH", "/usr/bin"),
+                ]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+            ),
+            command_line: vec![("alwaysShowIcons".to_string(), "false".to_string())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn profiles_stay_inside_the_profiles_folder() {
+        assert!(is_valid_profile("work"));
+        assert!(is_valid_profile("work.2"));
+        for profile in ["", ".", "..", "../x", "a/b", "a\\b", "/etc"] {
+            assert!(!is_valid_profile(profile), "{profile:?}");
+        }
+    }
+
+    #[test]
+    fn locked_values_override_user_and_defaults_fill_gaps() {
+        let file = object(json!({ "automaticCheckForUpdates": true, "hotkey": "Alt+T" }));
+        let (effective, _) = layer_config(&policy(), &LaunchOverrides::default(), file);
+        assert_eq!(
+            Value::Object(effective),
+            json!({
+                "apiURL": "https://llm.example.com",
+                "automaticCheckForUpdates": false,
+                "hotkey": "Alt+T",
+            })
+        );
+        let file = object(json!({ "apiURL": "https://mine.example.com" }));
+        let (effective, _) = layer_config(&policy(), &LaunchOverrides::default(), file);
+        assert_eq!(effective["apiURL"], json!("https://mine.example.com"));
+    }
+
+    #[test]
+    fn launch_overrides_layer_between_file_and_policy() {
+        let file = object(json!({ "proxy": { "server": "proxy", "port": "8080" } }));
+        let (effective, sources) = layer_config(&policy(), &overrides(), file);
+        assert_eq!(effective["apiURL"], json!("https://env.example.com"));
+        assert_eq!(
+            effective["proxy"],
+            json!({ "server": "proxy", "port": "3128" })
+        );
+        assert_eq!(effective["alwaysShowIcons"], json!(false));
+        assert!(!effective.contains_key("translatorPolicy"));
+        assert_eq!(sources["apiURL"], ConfigSource::Environment);
+        assert_eq!(sources["proxy"], ConfigSource::File);
+        assert_eq!(sources["proxy.port"], ConfigSource::Environment);
+        assert_eq!(sources["alwaysShowIcons"], ConfigSource::CommandLine);
+        assert_eq!(sources["automaticCheckForUpdates"], ConfigSource::Policy);
+    }
+
+    #[test]
+    fn saving_keeps_previous_values_of_overridden_keys() {
+        let previous =
+            object(json!({ "automaticCheckForUpdates": true, "proxy": { "port": "8080" } }));
+        let (effective, sources) = layer_config(&policy(), &overrides(), previous.clone());
+        let mut incoming = effective;
+        incoming.insert("hotkey".to_string(), json!("Alt+T"));
+        assert_eq!(
+            Value::Object(strip_overridden(
+                incoming,
+                &previous,
+                &sources,
+                &policy().defaults
+            )),
+            json!({
+                "automaticCheckForUpdates": true,
+                "hotkey": "Alt+T",
+                "proxy": { "port": "8080" },
+            })
+        );
+    }
+
+    #[test]
+    fn saving_leaves_untouched_policy_defaults_out_of_the_file() {
+        let previous = object(json!({ "hotkey": "Alt+T" }));
+        let (effective, sources) =
+            layer_config(&policy(), &LaunchOverrides::default(), previous.clone());
+        let saved = |incoming| {
+            Value::Object(strip_overridden(
+                incoming,
+                &previous,
+                &sources,
+                &policy().defaults,
+            ))
+        };
+        assert_eq!(saved(effective.clone()), json!({ "hotkey": "Alt+T" }));
+        let mut changed = effective;
+        changed.insert("apiURL".to_string(), json!("https://mine.example.com"));
+        assert_eq!(saved(changed)["apiURL"], json!("https://mine.example.com"));
+    }
+}
@@ -0,0 +1,282 @@
+// Checks a settings candidate before the settings UI saves it. Bad hotkeys,
+// proxies or newline keys otherwise only show up later: as panics in
+// `fetch_stream`, shortcuts that silently never fire, or writing that types
+// garbage instead of a line break.
+
+use serde_json::{json, Map, Value};
+use std::net::IpAddr;
+use std::str::FromStr;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::config::Config;
+use crate::writing::parse_newline_key;
+
+const HOTKEY_FIELDS: &[&str] = &[
+    "hotkey",
+    "displayWindowHotkey",
+    "ocrHotkey",
+    "quickTranslatorHotkey",
+    "writingHotkey",
+];
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFieldError {
+    /// Dot-separated path of the offending setting, e.g. `proxy.port`; empty
+    /// when the whole document is unusable.
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigFieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+fn non_empty_str<'a>(config: &'a Map<String, Value>, field: &str) -> Option<&'a str> {
+    config
+        .get(field)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Reports keys whose values have the wrong type for the backend. Each key is
+/// deserialized on its own so the error can name it.
+fn validate_types(config: &Map<String, Value>, errors: &mut Vec<ConfigFieldError>) {
+    for (key, value) in config {
+        if let Err(e) = serde_json::from_value::<Config>(json!({ key.clone(): value.clone() })) {
+            errors.push(ConfigFieldError::new(key, e.to_string()));
+        }
+    }
+}
+
+fn validate_hotkeys(config: &Map<String, Value>, errors: &mut Vec<ConfigFieldError>) {
+    for field in HOTKEY_FIELDS {
+        if let Some(hotkey) = non_empty_str(config, field) {
+            if let Err(e) = Shortcut::from_str(hotkey) {
+                errors.push(ConfigFieldError::new(field, e.to_string()));
+            }
+        }
+    }
+}
+
+fn validate_newline_hotkey(config: &Map<String, Value>, errors: &mut Vec<ConfigFieldError>) {
+    let Some(hotkey) = non_empty_str(config, "writingNewlineHotkey") else {
+        return;
+    };
+    for key in hotkey.split('+').map(str::trim) {
+        if parse_newline_key(key).is_none() {
+            errors.push(ConfigFieldError::new(
+                "writingNewlineHotkey",
+                format!(
+                    "unknown key {:?}; use a single character or one of ctrl, alt, shift, meta, caps_lock, escape, enter",
+                    key
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_port(port: &str) -> Result<(), String> {
+    match port.parse::<u32>() {
+        Ok(1..=65535) => Ok(()),
+        Ok(_) => Err(format!("port {} is out of range 1-65535", port)),
+        Err(_) => Err(format!("port {:?} is not a number", port)),
+    }
+}
+
+fn validate_proxy_server(server: &str) -> Result<(), String> {
+    if server.contains("://") {
+        return Err("enter the host only; the protocol is a separate setting".to_string());
+    }
+    let url = reqwest::Url::parse(&format!("http://{}", server))
+        .map_err(|e| format!("invalid proxy host {:?}: {}", server, e))?;
+    if url.port().is_some() || url.path() != "/" || url.query().is_some() {
+        return Err(format!(
+            "invalid proxy host {:?}: expected a bare host name or IP address",
+            server
+        ));
+    }
+    Ok(())
+}
+
+/// One `no_proxy` entry: `*`, a host name optionally starting with `.` or
+/// `*.`, an IP address or a CIDR block - the forms `reqwest::NoProxy`
+/// understands.
+fn validate_no_proxy_entry(entry: &str) -> Result<(), String> {
+    if entry == "*" {
+        return Ok(());
+    }
+    if let Some((address, prefix)) = entry.split_once('/') {
+        let max_prefix = match address.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => 32,
+            Ok(IpAddr::V6(_)) => 128,
+            Err(_) => return Err(format!("{:?} is not a valid CIDR block", entry)),
+        };
+        return match prefix.parse::<u8>() {
+            Ok(prefix) if prefix <= max_prefix => Ok(()),
+            _ => Err(format!("{:?} has an invalid prefix length", entry)),
+        };
+    }
+    if entry.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+    let host = entry
+        .strip_prefix("*.")
+        .or_else(|| entry.strip_prefix('.'))
+        .unwrap_or(entry);
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    if host.split('.').all(valid_label) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{:?} is not a host name, IP address or CIDR block",
+            entry
+        ))
+    }
+}
+
+fn validate_proxy(config: &Map<String, Value>, errors: &mut Vec<ConfigFieldError>) {
+    let Some(proxy) = config.get("proxy").and_then(Value::as_object) else {
+        return;
+    };
+    let enabled = proxy
+        .get("enabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let server = non_empty_str(proxy, "server");
+    let port = non_empty_str(proxy, "port");
+    if enabled {
+        for (field, value) in [
+            ("protocol", non_empty_str(proxy, "protocol")),
+            ("server", server),
+            ("port", port),
+        ] {
+            if value.is_none() {
+                errors.push(ConfigFieldError::new(
+                    &format!("proxy.{}", field),
+                    "required when the proxy is enabled",
+                ));
+            }
+        }
+    }
+    if let Some(server) = server {
+        if let Err(message) = validate_proxy_server(server) {
+            errors.push(ConfigFieldError::new("proxy.server", message));
+        }
+    }
+    if let Some(port) = port {
+        if let Err(message) = validate_port(port) {
+            errors.push(ConfigFieldError::new("proxy.port", message));
+        }
+    }
+    if let Some(no_proxy) = proxy.get("noProxy").and_then(Value::as_str) {
+        for entry in no_proxy.split(',').map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            if let Err(message) = validate_no_proxy_entry(entry) {
+                errors.push(ConfigFieldError::new("proxy.noProxy", message));
+            }
+        }
+    }
+}
+
+fn validate(content: &str) -> Vec<ConfigFieldError> {
+    let config = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(config)) => config,
+        Ok(_) => return vec![ConfigFieldError::new("", "config must be a JSON object")],
+        Err(e) => return vec![ConfigFieldError::new("", format!("invalid JSON: {}", e))],
+    };
+    let mut errors = Vec::new();
+    validate_types(&config, &mut errors);
+    validate_hotkeys(&config, &mut errors);
+    validate_newline_hotkey(&config, &mut errors);
+    validate_proxy(&config, &mut errors);
+    errors
+}
+
+/// Checks a settings candidate and returns one entry per problem, so the
+/// settings UI can flag the offending fields before saving. An empty list
+/// means the candidate is fine.
+#[tauri::command]
+#[specta::specta]
+pub fn validate_config(content: String) -> Vec<ConfigFieldError> {
+    validate(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(content: Value) -> Vec<String> {
+        validate(&content.to_string())
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_config() {
+        let config = json!({
+            "hotkey": "CmdOrCtrl+Shift+T",
+            "ocrHotkey": "",
+            "writingNewlineHotkey": "shift+enter",
+            "proxy": {
+                "enabled": true,
+                "protocol": "HTTP",
+                "server": "proxy.corp.example",
+                "port": "3128",
+                "noProxy": "localhost, .internal.example, 10.0.0.0/8, ::1",
+            },
+        });
+        assert_eq!(fields(config), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_each_bad_field() {
+        let config = json!({
+            "hotkey": "Shift+Nope",
+            "writingNewlineHotkey": "shift+return",
+            "alwaysShowIcons": "yes",
+            "proxy": {
+                "enabled": true,
+                "server": "http://proxy:8080",
+                "port": "70000",
+                "noProxy": "localhost, 10.0.0.0/40, bad host",
+            },
+        });
+        assert_eq!(
+            fields(config),
+            vec![
+                "alwaysShowIcons",
+                "hotkey",
+                "writingNewlineHotkey",
+                "proxy.protocol",
+                "proxy.server",
+                "proxy.port",
+                "proxy.noProxy",
+                "proxy.noProxy",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_non_object_documents() {
+        assert_eq!(validate("[]")[0].field, "");
+        assert_eq!(validate("{")[0].field, "");
+    }
+}
@@ -0,0 +1,308 @@
+// Downloads of the offline models (TTS voices, language identification). Each
+// model lists several mirrors and a pinned SHA-256; a mirror is only accepted
+// when the bytes it served match. Partial downloads are kept and resumed with
+// HTTP Range requests, so a flaky connection does not start over from zero.
+
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::watch;
+
+/// Returned when a download was cancelled, so callers can tell it from a
+/// failure.
+pub const CANCELLED: &str = "download cancelled";
+
+/// What the pause/resume/cancel commands ask of a running download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Control {
+    Run,
+    Pause,
+    Cancel,
+}
+
+/// Running downloads by key.
+static CONTROLS: Lazy<Mutex<HashMap<String, watch::Sender<Control>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Unregisters a download when it ends, however it ends.
+struct ControlGuard(String);
+
+impl Drop for ControlGuard {
+    fn drop(&mut self) {
+        CONTROLS.lock().remove(&self.0);
+    }
+}
+
+enum Interrupt {
+    Paused,
+    /// The server refused to resume the partial file, which is removed.
+    Restart,
+    Cancelled,
+    Failed(String),
+}
+
+fn set_control(key: &str, control: Control) -> Result<(), String> {
+    CONTROLS
+        .lock()
+        .get(key)
+        .ok_or_else(|| "no download is in progress".to_string())?
+        .send_replace(control);
+    Ok(())
+}
+
+pub fn pause(key: &str) -> Result<(), String> {
+    set_control(key, Control::Pause)
+}
+
+pub fn resume(key: &str) -> Result<(), String> {
+    set_control(key, Control::Run)
+}
+
+pub fn cancel(key: &str) -> Result<(), String> {
+    set_control(key, Control::Cancel)
+}
+
+/// Per-model locks, see `lock`.
+static LOCKS: Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Serializes downloading and replacing the files of the model `key`, without
+/// holding up other models or engines loading meanwhile; a paused download
+/// keeps only its own model waiting.
+pub async fn lock(key: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    let lock = LOCKS.lock().entry(key.to_string()).or_default().clone();
+    lock.lock_owned().await
+}
+
+/// The configured cap in bytes per second; `modelDownloadRateLimit` is in
+/// KiB/s and 0 or unset means unlimited.
+fn rate_limit() -> Option<u64> {
+    crate::config::get_config()
+        .ok()
+        .and_then(|config| config.model_download_rate_limit)
+        .filter(|limit| *limit > 0)
+        .map(|limit| limit * 1024)
+}
+
+/// Hashes what an earlier attempt left at `destination`, returning its size.
+async fn hash_partial(destination: &Path, hasher: &mut Sha256) -> Result<u64, String> {
+    let mut file = match tokio::fs::File::open(destination).await {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(format!("open partial download: {err}")),
+    };
+    let mut buffer = vec![0_u8; 64 * 1024];
+    let mut size = 0_u64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .await
+            .map_err(|err| format!("read partial download: {err}"))?;
+        if read == 0 {
+            return Ok(size);
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+}
+
+async fn download_from(
+    client: &reqwest::Client,
+    url: &str,
+    sha256: &str,
+    destination: &Path,
+    control: &mut watch::Receiver<Control>,
+    progress: &mut impl FnMut(u64, u64, f64, &'static str),
+) -> Result<(), Interrupt> {
+    let mut hasher = Sha256::new();
+    let resumed_from = hash_partial(destination, &mut hasher)
+        .await
+        .map_err(Interrupt::Failed)?;
+    if resumed_from > 0 && format!("{:x}", hasher.clone().finalize()) == sha256 {
+        progress(resumed_from, resumed_from, 0.0, "downloading");
+        return Ok(());
+    }
+
+    let mut request = client.get(url);
+    if resumed_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={resumed_from}-"));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| Interrupt::Failed(format!("request failed: {err}")))?;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // Longer than the file, so not a prefix of it.
+        let _ = tokio::fs::remove_file(destination).await;
+        return Err(if resumed_from > 0 {
+            Interrupt::Restart
+        } else {
+            Interrupt::Failed("server rejected the request".to_string())
+        });
+    }
+    let response = response
+        .error_for_status()
+        .map_err(|err| Interrupt::Failed(format!("server returned an error: {err}")))?;
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { resumed_from } else { 0 };
+    if !resumed {
+        hasher = Sha256::new();
+    }
+    let total = response
+        .content_length()
+        .map(|length| length + downloaded)
+        .unwrap_or(0);
+    let mut output = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(destination)
+        .await
+        .map_err(|err| Interrupt::Failed(format!("create download file: {err}")))?;
+    let mut stream = response.bytes_stream();
+    let limit = rate_limit();
+    let started_at = Instant::now();
+    let started_from = downloaded;
+    let mut last_reported = downloaded;
+    let mut last_report_at = Instant::now();
+
+    progress(downloaded, total, 0.0, "downloading");
+
+    loop {
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            changed = control.changed() => {
+                let state = if changed.is_ok() { *control.borrow_and_update() } else { Control::Cancel };
+                match state {
+                    Control::Run => continue,
+                    Control::Pause => {
+                        let _ = output.flush().await;
+                        progress(downloaded, total, 0.0, "paused");
+                        return Err(Interrupt::Paused);
+                    }
+                    Control::Cancel => return Err(Interrupt::Cancelled),
+                }
+            }
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk = chunk.map_err(|err| Interrupt::Failed(format!("download: {err}")))?;
+        output
+            .write_all(&chunk)
+            .await
+            .map_err(|err| Interrupt::Failed(format!("write download file: {err}")))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        let elapsed = last_report_at.elapsed();
+        if elapsed.as_millis() >= 200 || (total > 0 && downloaded >= total) {
+            let speed = (downloaded - last_reported) as f64 / elapsed.as_secs_f64().max(0.001);
+            progress(downloaded, total, speed, "downloading");
+            last_reported = downloaded;
+            last_report_at = Instant::now();
+        }
+        if let Some(limit) = limit {
+            let due = Duration::from_secs_f64((downloaded - started_from) as f64 / limit as f64);
+            if let Some(wait) = due.checked_sub(started_at.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+    if downloaded != last_reported {
+        let elapsed = last_report_at.elapsed();
+        let speed = (downloaded - last_reported) as f64 / elapsed.as_secs_f64().max(0.001);
+        progress(downloaded, total, speed, "downloading");
+    }
+    output
+        .flush()
+        .await
+        .map_err(|err| Interrupt::Failed(format!("flush download file: {err}")))?;
+
+    let actual_sha = format!("{:x}", hasher.finalize());
+    if actual_sha != sha256 {
+        let _ = tokio::fs::remove_file(destination).await;
+        return Err(Interrupt::Failed(format!(
+            "checksum mismatch: expected {sha256}, got {actual_sha}"
+        )));
+    }
+    Ok(())
+}
+
+/// Downloads the first mirror in `urls` whose content matches `sha256` to
+/// `destination` through the configured proxy, reporting `(downloaded,
+/// total, bytes_per_second, phase)` at most every 200 ms. The phase is
+/// `downloading` or `paused`.
+///
+/// `key` identifies the download for `pause`, `resume` and `cancel`. An
+/// interrupted download stays at `destination` and the next call resumes it;
+/// it is only removed when cancelled or when its checksum is wrong.
+pub async fn download_verified(
+    key: &str,
+    urls: &[&str],
+    sha256: &str,
+    destination: &Path,
+    mut progress: impl FnMut(u64, u64, f64, &'static str),
+) -> Result<(), String> {
+    let mut client_builder = reqwest::Client::builder().connect_timeout(Duration::from_secs(30));
+    if let Some(proxy) = crate::fetch::configured_proxy()? {
+        client_builder = client_builder.proxy(proxy);
+    }
+    let client = client_builder
+        .build()
+        .map_err(|err| format!("create download client: {err}"))?;
+
+    let (sender, mut control) = watch::channel(Control::Run);
+    {
+        let mut controls = CONTROLS.lock();
+        if controls.contains_key(key) {
+            return Err("this model is already downloading".to_string());
+        }
+        controls.insert(key.to_string(), sender);
+    }
+    let _guard = ControlGuard(key.to_string());
+    let mut last_error = String::from("no mirror was available");
+
+    for url in urls {
+        loop {
+            match download_from(
+                &client,
+                url,
+                sha256,
+                destination,
+                &mut control,
+                &mut progress,
+            )
+            .await
+            {
+                Ok(()) => return Ok(()),
+                Err(Interrupt::Paused) => {
+                    let resumed = control
+                        .wait_for(|state| *state != Control::Pause)
+                        .await
+                        .map(|state| *state == Control::Run)
+                        .unwrap_or(false);
+                    if resumed {
+                        continue;
+                    }
+                }
+                // Without the partial file there is no range to reject.
+                Err(Interrupt::Restart) => continue,
+                Err(Interrupt::Failed(err)) => {
+                    last_error = format!("{url}: {err}");
+                    break;
+                }
+                Err(Interrupt::Cancelled) => {}
+            }
+            let _ = tokio::fs::remove_file(destination).await;
+            return Err(CANCELLED.to_string());
+        }
+    }
+    Err(last_error)
+}
@@ -0,0 +1,585 @@
+// Inference for supervised fastText models, enough to run the published
+// language-identification models (`lid.176.bin` and the quantized
+// `lid.176.ftz`) without the C++ library. Mirrors fastText 0.9: the same
+// tokenization, subword hashing, pruned/quantized matrices and losses, so the
+// probabilities match `fasttext predict`.
+
+use std::collections::HashMap;
+
+const MAGIC: i32 = 793_712_314;
+const EOS: &str = "</s>";
+const LABEL_PREFIX: &str = "__label__";
+/// Centroids per sub-quantizer in product quantization.
+const KSUB: usize = 256;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "unexpected end of model file".to_string())?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32s(&mut self, len: usize) -> Result<Vec<f32>, String> {
+        let bytes = self.take(len.checked_mul(4).ok_or("matrix too large")?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.bytes[self.position..];
+        let len = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| "unterminated string in model file".to_string())?;
+        let value = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.position += len + 1;
+        Ok(value)
+    }
+
+    fn size(&mut self) -> Result<usize, String> {
+        usize::try_from(self.i64()?).map_err(|_| "negative size in model file".to_string())
+    }
+}
+
+struct ProductQuantizer {
+    dim: usize,
+    nsubq: usize,
+    dsub: usize,
+    lastdsub: usize,
+    centroids: Vec<f32>,
+}
+
+impl ProductQuantizer {
+    fn load(reader: &mut Reader) -> Result<Self, String> {
+        let dim = reader.i32()? as usize;
+        let nsubq = reader.i32()? as usize;
+        let dsub = reader.i32()? as usize;
+        let lastdsub = reader.i32()? as usize;
+        if nsubq == 0 || dim != (nsubq - 1) * dsub + lastdsub {
+            return Err("inconsistent product quantizer".to_string());
+        }
+        let centroids = reader.f32s(dim * KSUB)?;
+        Ok(Self {
+            dim,
+            nsubq,
+            dsub,
+            lastdsub,
+            centroids,
+        })
+    }
+
+    fn centroid(&self, m: usize, code: u8) -> &[f32] {
+        let code = code as usize;
+        if m == self.nsubq - 1 {
+            let start = m * KSUB * self.dsub + code * self.lastdsub;
+            &self.centroids[start..start + self.lastdsub]
+        } else {
+            let start = (m * KSUB + code) * self.dsub;
+            &self.centroids[start..start + self.dsub]
+        }
+    }
+}
+
+enum Matrix {
+    Dense {
+        cols: usize,
+        data: Vec<f32>,
+    },
+    Quantized {
+        codes: Vec<u8>,
+        pq: ProductQuantizer,
+        /// Per-row norm codes and their quantizer, when norms are quantized
+        /// separately.
+        norms: Option<(Vec<u8>, ProductQuantizer)>,
+    },
+}
+
+impl Matrix {
+    fn load(reader: &mut Reader, quantized: bool) -> Result<(Self, usize), String> {
+        if !quantized {
+            let rows = reader.size()?;
+            let cols = reader.size()?;
+            let data = reader.f32s(rows.checked_mul(cols).ok_or("matrix too large")?)?;
+            return Ok((Self::Dense { cols, data }, rows));
+        }
+        let qnorm = reader.u8()? != 0;
+        let rows = reader.size()?;
+        let _cols = reader.size()?;
+        let code_size = reader.i32()? as usize;
+        let codes = reader.take(code_size)?.to_vec();
+        let pq = ProductQuantizer::load(reader)?;
+        let norms = if qnorm {
+            let norm_codes = reader.take(rows)?.to_vec();
+            Some((norm_codes, ProductQuantizer::load(reader)?))
+        } else {
+            None
+        };
+        if codes.len() < rows * pq.nsubq {
+            return Err("quantized matrix is truncated".to_string());
+        }
+        Ok((Self::Quantized { codes, pq, norms }, rows))
+    }
+
+    fn cols(&self) -> usize {
+        match self {
+            Self::Dense { cols, .. } => *cols,
+            Self::Quantized { pq, .. } => pq.dim,
+        }
+    }
+
+    /// Calls `f(column, value)` for every entry of `row`.
+    fn for_each_in_row(&self, row: usize, mut f: impl FnMut(usize, f32)) {
+        match self {
+            Self::Dense { cols, data } => {
+                for (column, value) in data[row * cols..(row + 1) * cols].iter().enumerate() {
+                    f(column, *value);
+                }
+            }
+            Self::Quantized { codes, pq, norms } => {
+                let norm = norms
+                    .as_ref()
+                    .map_or(1.0, |(norm_codes, npq)| npq.centroid(0, norm_codes[row])[0]);
+                let code = &codes[row * pq.nsubq..(row + 1) * pq.nsubq];
+                for (m, code) in code.iter().enumerate() {
+                    for (n, value) in pq.centroid(m, *code).iter().enumerate() {
+                        f(m * pq.dsub + n, norm * value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_row_to(&self, vector: &mut [f32], row: usize) {
+        self.for_each_in_row(row, |column, value| vector[column] += value);
+    }
+
+    fn dot_row(&self, vector: &[f32], row: usize) -> f32 {
+        let mut sum = 0.0;
+        self.for_each_in_row(row, |column, value| sum += vector[column] * value);
+        sum
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Loss {
+    HierarchicalSoftmax,
+    Softmax,
+    OneVsAll,
+}
+
+struct Node {
+    left: i32,
+    right: i32,
+    count: i64,
+}
+
+/// Huffman tree over the labels, built exactly like fastText's so the output
+/// matrix rows line up with its inner nodes. `counts` is sorted descending.
+fn build_tree(counts: &[i64]) -> Vec<Node> {
+    let osz = counts.len();
+    let mut tree = (0..2 * osz - 1)
+        .map(|i| Node {
+            left: -1,
+            right: -1,
+            count: counts.get(i).copied().unwrap_or(1_000_000_000_000_000),
+        })
+        .collect::<Vec<_>>();
+    let mut leaf = osz as i64 - 1;
+    let mut node = osz;
+    for i in osz..2 * osz - 1 {
+        let mut pick = [0_usize; 2];
+        for slot in &mut pick {
+            if leaf >= 0 && tree[leaf as usize].count < tree[node].count {
+                *slot = leaf as usize;
+                leaf -= 1;
+            } else {
+                *slot = node;
+                node += 1;
+            }
+        }
+        tree[i].left = pick[0] as i32;
+        tree[i].right = pick[1] as i32;
+        tree[i].count = tree[pick[0]].count + tree[pick[1]].count;
+    }
+    tree
+}
+
+fn hash(bytes: &[u8]) -> u32 {
+    let mut h: u32 = 2_166_136_261;
+    for byte in bytes {
+        h ^= *byte as i8 as u32;
+        h = h.wrapping_mul(16_777_619);
+    }
+    h
+}
+
+fn std_log(x: f32) -> f32 {
+    (x + 1e-5).ln()
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+pub struct FastText {
+    dim: usize,
+    minn: usize,
+    maxn: usize,
+    bucket: u32,
+    word_ngrams: usize,
+    loss: Loss,
+    words: HashMap<String, usize>,
+    nwords: usize,
+    labels: Vec<String>,
+    /// Remapping of hashed subword ids after pruning; `None` when the model
+    /// was not pruned.
+    prune_index: Option<HashMap<i32, i32>>,
+    input: Matrix,
+    input_rows: usize,
+    output: Matrix,
+    tree: Vec<Node>,
+}
+
+impl FastText {
+    pub fn load(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.i32()? != MAGIC {
+            return Err("not a fastText model".to_string());
+        }
+        let version = reader.i32()?;
+        if !(11..=12).contains(&version) {
+            return Err(format!("unsupported fastText model version {version}"));
+        }
+
+        let dim = reader.i32()? as usize;
+        let _ws = reader.i32()?;
+        let _epoch = reader.i32()?;
+        let _min_count = reader.i32()?;
+        let _neg = reader.i32()?;
+        let word_ngrams = reader.i32()?.max(1) as usize;
+        let loss = match reader.i32()? {
+            1 => Loss::HierarchicalSoftmax,
+            3 => Loss::Softmax,
+            4 => Loss::OneVsAll,
+            other => return Err(format!("unsupported fastText loss {other}")),
+        };
+        if reader.i32()? != 3 {
+            return Err("not a supervised fastText model".to_string());
+        }
+        let bucket = reader.i32()?.max(0) as u32;
+        let minn = reader.i32()?.max(0) as usize;
+        let maxn = reader.i32()?.max(0) as usize;
+        // Version 11 supervised models were trained without subwords.
+        let maxn = if version == 11 { 0 } else { maxn };
+        let _lr_update_rate = reader.i32()?;
+        let _sampling_threshold = reader.f64()?;
+
+        let size = reader.i32()?.max(0) as usize;
+        let nwords = reader.i32()?.max(0) as usize;
+        let _nlabels = reader.i32()?;
+        let _ntokens = reader.i64()?;
+        let prune_size = reader.i64()?;
+        let mut words = HashMap::with_capacity(nwords);
+        let mut labels = Vec::new();
+        let mut label_counts = Vec::new();
+        for index in 0..size {
+            let entry = reader.string()?;
+            let count = reader.i64()?;
+            if reader.u8()? == 0 {
+                words.insert(entry, index);
+            } else {
+                labels.push(entry.trim_start_matches(LABEL_PREFIX).to_string());
+                label_counts.push(count);
+            }
+        }
+        let prune_index = if prune_size >= 0 {
+            let mut map = HashMap::with_capacity(prune_size as usize);
+            for _ in 0..prune_size {
+                let from = reader.i32()?;
+                let to = reader.i32()?;
+                map.insert(from, to);
+            }
+            Some(map)
+        } else {
+            None
+        };
+        if labels.is_empty() {
+            return Err("model has no labels".to_string());
+        }
+
+        let quantized_input = reader.u8()? != 0;
+        let (input, input_rows) = Matrix::load(&mut reader, quantized_input)?;
+        let quantized_output = reader.u8()? != 0;
+        let (output, output_rows) = Matrix::load(&mut reader, quantized_output)?;
+        let expected_output_rows = match loss {
+            Loss::HierarchicalSoftmax => labels.len() - 1,
+            _ => labels.len(),
+        };
+        if output_rows != expected_output_rows {
+            return Err("output matrix does not match the labels".to_string());
+        }
+        if input.cols() != dim || output.cols() != dim {
+            return Err("matrices do not match the model dimension".to_string());
+        }
+        let tree = if loss == Loss::HierarchicalSoftmax {
+            build_tree(&label_counts)
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            dim,
+            minn,
+            maxn,
+            bucket,
+            word_ngrams,
+            loss,
+            words,
+            nwords,
+            labels,
+            prune_index,
+            input,
+            input_rows,
+            output,
+            tree,
+        })
+    }
+
+    fn push_hash(&self, ids: &mut Vec<usize>, id: i32) {
+        let id = match &self.prune_index {
+            None => id,
+            Some(map) => match map.get(&id) {
+                Some(id) => *id,
+                None => return,
+            },
+        };
+        let row = self.nwords + id as usize;
+        if id >= 0 && row < self.input_rows {
+            ids.push(row);
+        }
+    }
+
+    /// Character n-grams of `<word>` between `minn` and `maxn` characters.
+    fn push_subwords(&self, ids: &mut Vec<usize>, word: &str) {
+        if self.maxn == 0 || self.bucket == 0 {
+            return;
+        }
+        let word = format!("<{word}>");
+        let bytes = word.as_bytes();
+        let continuation = |byte: u8| byte & 0xC0 == 0x80;
+        for i in 0..bytes.len() {
+            if continuation(bytes[i]) {
+                continue;
+            }
+            let mut j = i;
+            let mut n = 1;
+            while j < bytes.len() && n <= self.maxn {
+                j += 1;
+                while j < bytes.len() && continuation(bytes[j]) {
+                    j += 1;
+                }
+                if n >= self.minn && !(n == 1 && (i == 0 || j == bytes.len())) {
+                    self.push_hash(ids, (hash(&bytes[i..j]) % self.bucket) as i32);
+                }
+                n += 1;
+            }
+        }
+    }
+
+    fn input_ids(&self, text: &str) -> Vec<usize> {
+        let mut ids = Vec::new();
+        let mut hashes = Vec::new();
+        let tokens = text
+            .split([' ', '\n', '\r', '\t', '\x0b', '\x0c', '\0'])
+            .filter(|token| !token.is_empty())
+            .chain(std::iter::once(EOS));
+        for token in tokens {
+            if token.starts_with(LABEL_PREFIX) {
+                continue;
+            }
+            match self.words.get(token) {
+                Some(&id) => {
+                    ids.push(id);
+                    if token != EOS {
+                        self.push_subwords(&mut ids, token);
+                    }
+                }
+                None => self.push_subwords(&mut ids, token),
+            }
+            hashes.push(hash(token.as_bytes()) as i32);
+        }
+        if self.bucket > 0 {
+            for i in 0..hashes.len() {
+                let mut h = hashes[i] as u64;
+                for next in hashes
+                    .iter()
+                    .take((i + self.word_ngrams).min(hashes.len()))
+                    .skip(i + 1)
+                {
+                    h = h.wrapping_mul(116_049_371).wrapping_add(*next as u64);
+                    self.push_hash(&mut ids, (h % self.bucket as u64) as i32);
+                }
+            }
+        }
+        ids
+    }
+
+    fn tree_search(&self, hidden: &[f32], node: usize, score: f32, out: &mut Vec<(usize, f32)>) {
+        let osz = self.labels.len();
+        let current = &self.tree[node];
+        if current.left < 0 && current.right < 0 {
+            out.push((node, score.exp()));
+            return;
+        }
+        let f = sigmoid(self.output.dot_row(hidden, node - osz));
+        self.tree_search(hidden, current.left as usize, score + std_log(1.0 - f), out);
+        self.tree_search(hidden, current.right as usize, score + std_log(f), out);
+    }
+
+    /// Probability of every label for `text`, most likely first. Empty when
+    /// the text yields no features.
+    pub fn predict(&self, text: &str) -> Vec<(&str, f32)> {
+        let ids = self.input_ids(text);
+        if ids.is_empty() {
+            return Vec::new();
+        }
+        let mut hidden = vec![0.0; self.dim];
+        for id in &ids {
+            self.input.add_row_to(&mut hidden, *id);
+        }
+        for value in &mut hidden {
+            *value /= ids.len() as f32;
+        }
+
+        let mut scores = match self.loss {
+            Loss::HierarchicalSoftmax => {
+                let mut scores = Vec::with_capacity(self.labels.len());
+                self.tree_search(&hidden, self.tree.len() - 1, 0.0, &mut scores);
+                scores
+            }
+            Loss::Softmax => {
+                let logits = (0..self.labels.len())
+                    .map(|row| self.output.dot_row(&hidden, row))
+                    .collect::<Vec<_>>();
+                let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let exps = logits
+                    .iter()
+                    .map(|logit| (logit - max).exp())
+                    .collect::<Vec<_>>();
+                let sum = exps.iter().sum::<f32>();
+                exps.into_iter()
+                    .enumerate()
+                    .map(|(index, value)| (index, value / sum))
+                    .collect()
+            }
+            Loss::OneVsAll => (0..self.labels.len())
+                .map(|row| (row, sigmoid(self.output.dot_row(&hidden, row))))
+                .collect(),
+        };
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+            .into_iter()
+            .map(|(index, probability)| (self.labels[index].as_str(), probability))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tiny dense softmax model with two words and two labels, serialized
+    /// the way fastText writes it.
+    fn tiny_model() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let i32s = |bytes: &mut Vec<u8>, values: &[i32]| {
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        };
+        i32s(&mut bytes, &[MAGIC, 12]);
+        // dim, ws, epoch, minCount, neg, wordNgrams, loss, model, bucket,
+        // minn, maxn, lrUpdateRate
+        i32s(&mut bytes, &[2, 5, 5, 1, 5, 1, 3, 3, 0, 0, 0, 100]);
+        bytes.extend_from_slice(&1e-4_f64.to_le_bytes());
+        i32s(&mut bytes, &[5, 3, 2]);
+        bytes.extend_from_slice(&100_i64.to_le_bytes());
+        bytes.extend_from_slice(&(-1_i64).to_le_bytes());
+        for (entry, kind) in [
+            ("</s>", 0_u8),
+            ("hello", 0),
+            ("bonjour", 0),
+            ("__label__en", 1),
+            ("__label__fr", 1),
+        ] {
+            bytes.extend_from_slice(entry.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&10_i64.to_le_bytes());
+            bytes.push(kind);
+        }
+        let matrix = |bytes: &mut Vec<u8>, rows: i64, values: &[f32]| {
+            bytes.push(0);
+            bytes.extend_from_slice(&rows.to_le_bytes());
+            bytes.extend_from_slice(&2_i64.to_le_bytes());
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        };
+        matrix(&mut bytes, 3, &[0.0, 0.0, 3.0, 0.0, 0.0, 3.0]);
+        matrix(&mut bytes, 2, &[1.0, 0.0, 0.0, 1.0]);
+        bytes
+    }
+
+    #[test]
+    fn predicts_with_a_dense_softmax_model() {
+        let model = FastText::load(&tiny_model()).unwrap();
+        let english = model.predict("hello");
+        assert_eq!(english[0].0, "en");
+        assert!(english[0].1 > 0.8);
+        assert_eq!(model.predict("bonjour bonjour")[0].0, "fr");
+        assert!((english.iter().map(|(_, p)| p).sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(FastText::load(b"RIFF....WAVE").is_err());
+        assert!(FastText::load(&tiny_model()[..40]).is_err());
+    }
+
+    #[test]
+    fn hierarchical_softmax_tree_pairs_smallest_counts_first() {
+        let tree = build_tree(&[50, 30, 20]);
+        assert_eq!(tree.len(), 5);
+        assert_eq!((tree[3].left, tree[3].right), (2, 1));
+        assert_eq!((tree[4].left, tree[4].right), (3, 0));
+        assert_eq!(tree[4].count, 100);
+    }
+}
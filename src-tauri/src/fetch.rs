@@ -39,6 +39,41 @@ pub(crate) struct AbortEventPayload {
     id: String,
 }
 
+/// The proxy from the settings, when one is enabled and complete.
+pub(crate) fn configured_proxy() -> Result<Option<reqwest::Proxy>, String> {
+    let Some(proxy_config) = get_config().ok().and_then(|config| config.proxy) else {
+        return Ok(None);
+    };
+    if !(proxy_config.enabled.unwrap_or(false)
+        && proxy_config.protocol.is_some()
+        && proxy_config.server.is_some()
+        && proxy_config.port.is_some())
+    {
+        return Ok(None);
+    }
+    let proxy_url = format!(
+        "{}:{}",
+        proxy_config.server.unwrap_or_default(),
+        proxy_config.port.unwrap_or_default()
+    );
+    let proxy_url = match proxy_config.protocol.unwrap() {
+        ProxyProtocol::HTTP => format!("http://{}", proxy_url),
+        ProxyProtocol::HTTPS => format!("https://{}", proxy_url),
+    };
+    let mut proxy = reqwest::Proxy::all(&proxy_url)
+        .map_err(|err| format!("invalid proxy {}: {}", proxy_url, err))?;
+    if let Some(basic_auth) = proxy_config.basic_auth {
+        let username = basic_auth.username.unwrap_or_default();
+        if username.len() > 0 {
+            proxy = proxy.basic_auth(&username, &basic_auth.password.unwrap_or_default());
+        }
+    }
+    if let Some(no_proxy) = proxy_config.no_proxy {
+        proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&no_proxy));
+    }
+    Ok(Some(proxy))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn fetch_stream(id: String, url: String, options_str: String) -> Result<String, String> {
@@ -50,36 +85,8 @@ pub async fn fetch_stream(id: String, url: String, options_str: String) -> Resul
 
     let mut client_builder = Client::builder().default_headers(headers);
 
-    if let Ok(config) = get_config() {
-        if let Some(proxy_config) = config.proxy {
-            if proxy_config.enabled.unwrap_or(false)
-                && proxy_config.protocol.is_some()
-                && proxy_config.server.is_some()
-                && proxy_config.port.is_some()
-            {
-                let proxy_url = format!(
-                    "{}:{}",
-                    proxy_config.server.unwrap_or_default(),
-                    proxy_config.port.unwrap_or_default()
-                );
-                let proxy_url = match proxy_config.protocol.unwrap() {
-                    ProxyProtocol::HTTP => format!("http://{}", proxy_url),
-                    ProxyProtocol::HTTPS => format!("https://{}", proxy_url),
-                };
-                let mut proxy = reqwest::Proxy::all(&proxy_url).unwrap();
-                if let Some(basic_auth) = proxy_config.basic_auth {
-                    let username = basic_auth.username.unwrap_or_default();
-                    if username.len() > 0 {
-                        proxy =
-                            proxy.basic_auth(&username, &basic_auth.password.unwrap_or_default());
-                    }
-                }
-                if let Some(no_proxy) = proxy_config.no_proxy {
-                    proxy = proxy.no_proxy(Some(reqwest::NoProxy::from_string(&no_proxy).unwrap()));
-                }
-                client_builder = client_builder.proxy(proxy);
-            }
-        }
+    if let Some(proxy) = configured_proxy()? {
+        client_builder = client_builder.proxy(proxy);
     }
 
     let client = client_builder
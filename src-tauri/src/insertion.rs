@@ -6,6 +6,7 @@ use debug_print::debug_println;
 use enigo::{Enigo, Keyboard, Settings};
 use parking_lot::Mutex;
 
+use crate::app_rules::{self, AppInfo, InsertionMode};
 use crate::utils::INPUT_LOCK;
 
 static PREVIOUS_ACTIVE_WINDOW: Mutex<Option<ActiveWindow>> = Mutex::new(None);
@@ -229,12 +230,40 @@ fn focus_previous_window() -> Result<(), String> {
     }
 }
 
+/// Inserts `text` at the cursor through the clipboard, restoring the
+/// previous clipboard text afterwards.
+pub fn paste_text(enigo: &mut Enigo, text: &str) -> Result<(), String> {
+    use arboard::Clipboard;
+
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    let previous = clipboard.get_text().ok();
+    clipboard.set_text(text).map_err(|e| e.to_string())?;
+    thread::sleep(Duration::from_millis(50));
+    {
+        let _input_lock = INPUT_LOCK.lock();
+        crate::utils::paste(enigo);
+    }
+    // Give the target app time to read the clipboard before restoring it.
+    thread::sleep(Duration::from_millis(150));
+    if let Some(previous) = previous {
+        let _ = clipboard.set_text(previous);
+    }
+    Ok(())
+}
+
 fn replace_input_with_text(text: &str) -> Result<(), String> {
     if text.is_empty() {
         return Ok(());
     }
 
     let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+    let insertion_mode = app_rules::get_app_config()
+        .ok()
+        .and_then(|config| config.insertion_mode)
+        .unwrap_or_default();
+    if insertion_mode == InsertionMode::Paste {
+        return paste_text(&mut enigo, text);
+    }
     {
         let _input_lock = INPUT_LOCK.lock();
         enigo.text(text).map_err(|e| e.to_string())?;
@@ -247,6 +276,11 @@ fn replace_input_with_text(text: &str) -> Result<(), String> {
 #[tauri::command]
 #[specta::specta]
 pub async fn insert_translation_into_previous_input(text: String) -> Result<(), String> {
+    let previous_app = PREVIOUS_ACTIVE_WINDOW
+        .lock()
+        .as_ref()
+        .map(AppInfo::from_window);
+    app_rules::update_active_rule_for(previous_app);
     focus_previous_window()?;
     thread::sleep(Duration::from_millis(200));
     replace_input_with_text(&text)?;
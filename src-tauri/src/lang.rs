@@ -1,10 +1,789 @@
-use whatlang::detect;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use whatlang::{detect_script, Detector, Lang, Script};
 
+use crate::fasttext::FastText;
+
+const LID_FILE: &str = "lid.176.ftz";
+const LID_URLS: &[&str] =
+    &["https://dl.fbaipublicfiles.com/fasttext/supervised-models/lid.176.ftz"];
+/// SHA-256 of `lid.176.ftz`. Downloads are refused while it is empty; a copy
+/// of the file placed in the model directory is used either way.
+const LID_SHA256: &str = "";
+/// fastText probabilities at or above this count as reliable.
+const FASTTEXT_RELIABLE: f64 = 0.5;
+
+/// Alternatives reported when the caller does not ask for a number.
+const DEFAULT_ALTERNATIVES: u32 = 3;
+/// Every alternative costs one more detection pass.
+const MAX_ALTERNATIVES: u32 = 8;
+
+/// whatlang's languages with the code the frontend uses for them and their
+/// ISO 639-1 code. The app code only differs where the frontend has its own
+/// convention (`zh-Hans`, `jw`).
+const LANGS: &[(Lang, &str, &str)] = &[
+    (Lang::Afr, "af", "af"),
+    (Lang::Aka, "ak", "ak"),
+    (Lang::Amh, "am", "am"),
+    (Lang::Ara, "ar", "ar"),
+    (Lang::Aze, "az", "az"),
+    (Lang::Bel, "be", "be"),
+    (Lang::Ben, "bn", "bn"),
+    (Lang::Bul, "bg", "bg"),
+    (Lang::Cat, "ca", "ca"),
+    (Lang::Ces, "cs", "cs"),
+    (Lang::Cmn, "zh-Hans", "zh"),
+    (Lang::Dan, "da", "da"),
+    (Lang::Deu, "de", "de"),
+    (Lang::Ell, "el", "el"),
+    (Lang::Eng, "en", "en"),
+    (Lang::Epo, "eo", "eo"),
+    (Lang::Est, "et", "et"),
+    (Lang::Fin, "fi", "fi"),
+    (Lang::Fra, "fr", "fr"),
+    (Lang::Guj, "gu", "gu"),
+    (Lang::Heb, "he", "he"),
+    (Lang::Hin, "hi", "hi"),
+    (Lang::Hrv, "hr", "hr"),
+    (Lang::Hun, "hu", "hu"),
+    (Lang::Hye, "hy", "hy"),
+    (Lang::Ind, "id", "id"),
+    (Lang::Ita, "it", "it"),
+    (Lang::Jav, "jw", "jv"),
+    (Lang::Jpn, "ja", "ja"),
+    (Lang::Kan, "kn", "kn"),
+    (Lang::Kat, "ka", "ka"),
+    (Lang::Khm, "km", "km"),
+    (Lang::Kor, "ko", "ko"),
+    (Lang::Lat, "la", "la"),
+    (Lang::Lav, "lv", "lv"),
+    (Lang::Lit, "lt", "lt"),
+    (Lang::Mal, "ml", "ml"),
+    (Lang::Mar, "mr", "mr"),
+    (Lang::Mkd, "mk", "mk"),
+    (Lang::Mya, "my", "my"),
+    (Lang::Nep, "ne", "ne"),
+    (Lang::Nld, "nl", "nl"),
+    (Lang::Nob, "nb", "nb"),
+    (Lang::Ori, "or", "or"),
+    (Lang::Pan, "pa", "pa"),
+    (Lang::Pes, "fa", "fa"),
+    (Lang::Pol, "pl", "pl"),
+    (Lang::Por, "pt", "pt"),
+    (Lang::Ron, "ro", "ro"),
+    (Lang::Rus, "ru", "ru"),
+    (Lang::Sin, "si", "si"),
+    (Lang::Slk, "sk", "sk"),
+    (Lang::Slv, "sl", "sl"),
+    (Lang::Sna, "sn", "sn"),
+    (Lang::Spa, "es", "es"),
+    (Lang::Srp, "sr", "sr"),
+    (Lang::Swe, "sv", "sv"),
+    (Lang::Tam, "ta", "ta"),
+    (Lang::Tel, "te", "te"),
+    (Lang::Tgl, "tl", "tl"),
+    (Lang::Tha, "th", "th"),
+    (Lang::Tuk, "tk", "tk"),
+    (Lang::Tur, "tr", "tr"),
+    (Lang::Ukr, "uk", "uk"),
+    (Lang::Urd, "ur", "ur"),
+    (Lang::Uzb, "uz", "uz"),
+    (Lang::Vie, "vi", "vi"),
+    (Lang::Yid, "yi", "yi"),
+    (Lang::Zul, "zu", "zu"),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum LangDetectionBackend {
+    /// Trigram statistics; built in, but unreliable for a few words.
+    #[default]
+    Whatlang,
+    /// fastText's `lid.176` model, much better on short text. Falls back to
+    /// whatlang until the model has been downloaded.
+    FastText,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LangModelStatus {
+    pub backend: LangDetectionBackend,
+    pub installed: bool,
+    pub loaded: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LangModelDownloadProgress {
+    downloaded: u64,
+    total: u64,
+    bytes_per_second: f64,
+    phase: &'static str,
+    message: Option<String>,
+}
+
+static LID_MODEL: Lazy<Mutex<Option<Arc<FastText>>>> = Lazy::new(|| Mutex::new(None));
+/// Set once a model file failed to load, so it is not parsed on every call.
+static LID_LOAD_FAILED: AtomicBool = AtomicBool::new(false);
+static LID_DOWNLOADING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LangCandidate {
+    /// Language code as used by the frontend, e.g. `zh-Hans` or `en`.
+    pub lang: String,
+    #[serde(rename = "iso639_1")]
+    pub iso639_1: String,
+    #[serde(rename = "iso639_3")]
+    pub iso639_3: String,
+    /// Between 0 and 1.
+    pub confidence: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LangDetection {
+    /// Language code as used by the frontend, e.g. `zh-Hans` or `en`.
+    pub lang: String,
+    #[serde(rename = "iso639_1")]
+    pub iso639_1: String,
+    #[serde(rename = "iso639_3")]
+    pub iso639_3: String,
+    /// Writing system of the text, e.g. `Latin` or `Mandarin`.
+    pub script: String,
+    /// Between 0 and 1.
+    pub confidence: f64,
+    pub is_reliable: bool,
+    /// The next most likely languages, best first.
+    pub alternatives: Vec<LangCandidate>,
+}
+
+fn codes(lang: Lang) -> (&'static str, &'static str) {
+    LANGS
+        .iter()
+        .find(|(known, _, _)| *known == lang)
+        .map(|(_, app, iso639_1)| (*app, *iso639_1))
+        .unwrap_or_else(|| (lang.code(), ""))
+}
+
+/// Accepts app codes (`zh-Hans`), ISO 639-1 (`zh`) and ISO 639-3 (`cmn`).
+/// All Chinese variants map to Mandarin, the only one whatlang knows.
+fn parse_lang(code: &str) -> Option<Lang> {
+    let code = code.trim();
+    if code.eq_ignore_ascii_case("zh") || code.to_ascii_lowercase().starts_with("zh-") {
+        return Some(Lang::Cmn);
+    }
+    LANGS
+        .iter()
+        .find(|(_, app, iso639_1)| {
+            app.eq_ignore_ascii_case(code) || iso639_1.eq_ignore_ascii_case(code)
+        })
+        .map(|(lang, _, _)| *lang)
+        .or_else(|| Lang::from_code(code.to_lowercase()))
+}
+
+fn parse_allowlist(codes: &[String]) -> Result<Vec<Lang>, String> {
+    codes
+        .iter()
+        .filter(|code| !code.trim().is_empty())
+        .map(|code| parse_lang(code).ok_or_else(|| format!("unsupported language: {code}")))
+        .collect()
+}
+
+/// A detector restricted to the allowlist, minus languages already reported.
+fn detector(allowlist: Option<&[Lang]>, excluded: &[Lang]) -> Option<Detector> {
+    match allowlist {
+        Some(allowlist) => {
+            let remaining = allowlist
+                .iter()
+                .filter(|lang| !excluded.contains(lang))
+                .copied()
+                .collect::<Vec<_>>();
+            (!remaining.is_empty()).then(|| Detector::with_allowlist(remaining))
+        }
+        None => Some(Detector::with_denylist(excluded.to_vec())),
+    }
+}
+
+fn candidate(lang: Lang, confidence: f64) -> LangCandidate {
+    let (app, iso639_1) = codes(lang);
+    LangCandidate {
+        lang: app.to_string(),
+        iso639_1: iso639_1.to_string(),
+        iso639_3: lang.code().to_string(),
+        confidence,
+    }
+}
+
+/// Detects the language of `text` with whatlang. Alternatives are found by
+/// detecting again with the languages reported so far excluded, so their
+/// confidences are each relative to the languages left at that point.
+fn detect_whatlang(
+    text: &str,
+    allowlist: Option<&[Lang]>,
+    alternatives: u32,
+) -> Option<LangDetection> {
+    let info = detector(allowlist, &[])?.detect(text)?;
+    let mut excluded = vec![info.lang()];
+    let mut others = Vec::new();
+    while others.len() < alternatives.min(MAX_ALTERNATIVES) as usize {
+        let Some(next) = detector(allowlist, &excluded).and_then(|detector| detector.detect(text))
+        else {
+            break;
+        };
+        excluded.push(next.lang());
+        others.push(candidate(next.lang(), next.confidence()));
+    }
+    let best = candidate(info.lang(), info.confidence());
+    Some(LangDetection {
+        lang: best.lang,
+        iso639_1: best.iso639_1,
+        iso639_3: best.iso639_3,
+        script: info.script().name().to_string(),
+        confidence: info.confidence(),
+        is_reliable: info.is_reliable(),
+        alternatives: others,
+    })
+}
+
+/// App, ISO 639-1 and ISO 639-3 codes for a `lid.176` label, which is an
+/// ISO 639-1 code where one exists and ISO 639-3 otherwise.
+fn fasttext_codes(label: &str) -> (String, String, String, Option<Lang>) {
+    if let Some((lang, app, iso639_1)) = LANGS.iter().find(|(_, _, iso639_1)| *iso639_1 == label) {
+        return (
+            app.to_string(),
+            iso639_1.to_string(),
+            lang.code().to_string(),
+            Some(*lang),
+        );
+    }
+    if label.len() == 3 {
+        let lang = Lang::from_code(label);
+        return (label.to_string(), String::new(), label.to_string(), lang);
+    }
+    (label.to_string(), label.to_string(), String::new(), None)
+}
+
+fn detect_fasttext(
+    model: &FastText,
+    text: &str,
+    allowlist: Option<&[Lang]>,
+    alternatives: u32,
+) -> Option<LangDetection> {
+    let mut predictions = model
+        .predict(&text.replace(['\n', '\r'], " "))
+        .into_iter()
+        .map(|(label, probability)| (fasttext_codes(label), probability as f64))
+        .filter(|((_, _, _, lang), _)| {
+            allowlist.map_or(true, |allowlist| {
+                lang.is_some_and(|lang| allowlist.contains(&lang))
+            })
+        })
+        .collect::<Vec<_>>();
+    // Within an allowlist the probabilities are relative to the allowed
+    // languages.
+    if allowlist.is_some() {
+        let sum = predictions.iter().map(|(_, p)| p).sum::<f64>();
+        if sum > 0.0 {
+            for (_, probability) in &mut predictions {
+                *probability /= sum;
+            }
+        }
+    }
+    let mut predictions = predictions.into_iter();
+    let ((lang, iso639_1, iso639_3, _), confidence) = predictions.next()?;
+    Some(LangDetection {
+        lang,
+        iso639_1,
+        iso639_3,
+        script: detect_script(text)
+            .map(|script| script.name().to_string())
+            .unwrap_or_default(),
+        confidence,
+        is_reliable: confidence >= FASTTEXT_RELIABLE,
+        alternatives: predictions
+            .take(alternatives.min(MAX_ALTERNATIVES) as usize)
+            .map(
+                |((lang, iso639_1, iso639_3, _), confidence)| LangCandidate {
+                    lang,
+                    iso639_1,
+                    iso639_3,
+                    confidence,
+                },
+            )
+            .collect(),
+    })
+}
+
+/// Detects the language of `text` with the fastText model when one is given
+/// and has an answer, else with whatlang.
+pub fn detect(
+    model: Option<&FastText>,
+    text: &str,
+    allowlist: Option<&[Lang]>,
+    alternatives: u32,
+) -> Option<LangDetection> {
+    model
+        .and_then(|model| detect_fasttext(model, text, allowlist, alternatives))
+        .or_else(|| detect_whatlang(text, allowlist, alternatives))
+}
+
+fn lid_model_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_dir(app).map(|path| path.join("lang").join(LID_FILE))
+}
+
+fn configured_backend() -> LangDetectionBackend {
+    crate::config::get_config()
+        .ok()
+        .and_then(|config| config.language_detection_backend)
+        .unwrap_or_default()
+}
+
+fn emit_lid_progress(
+    app: &AppHandle,
+    downloaded: u64,
+    total: u64,
+    bytes_per_second: f64,
+    phase: &'static str,
+    message: Option<String>,
+) {
+    let _ = app.emit(
+        "lang-model-download-progress",
+        LangModelDownloadProgress {
+            downloaded,
+            total,
+            bytes_per_second,
+            phase,
+            message,
+        },
+    );
+}
+
+async fn download_lid_model(app: &AppHandle) -> Result<(), String> {
+    if LID_SHA256.is_empty() {
+        return Err(
+            "no checksum is pinned for the language-ID model; place lid.176.ftz in the model directory instead"
+                .to_string(),
+        );
+    }
+    let path = lid_model_path(app)?;
+    if let Some(directory) = path.parent() {
+        tokio::fs::create_dir_all(directory)
+            .await
+            .map_err(|err| format!("create language model directory: {err}"))?;
+    }
+    let part = path.with_extension("ftz.part");
+    let result = crate::download::download_verified(
+        "lang",
+        LID_URLS,
+        LID_SHA256,
+        &part,
+        |done, size, speed, phase| emit_lid_progress(app, done, size, speed, phase, None),
+    )
+    .await;
+    if let Err(err) = result {
+        let error = format!("download language model: {err}");
+        emit_lid_progress(app, 0, 0, 0.0, "error", Some(error.clone()));
+        return Err(error);
+    }
+    tokio::fs::rename(&part, &path)
+        .await
+        .map_err(|err| format!("install language model: {err}"))?;
+    LID_LOAD_FAILED.store(false, Ordering::SeqCst);
+    *LID_MODEL.lock() = None;
+    emit_lid_progress(app, 0, 0, 0.0, "ready", None);
+    Ok(())
+}
+
+/// The fastText model when that backend is configured and the model is on
+/// disk. A missing model is downloaded in the background; until then, and
+/// whenever this returns `None`, detection uses whatlang.
+fn lid_model(app: &AppHandle) -> Option<Arc<FastText>> {
+    if configured_backend() != LangDetectionBackend::FastText {
+        return None;
+    }
+    if let Some(model) = LID_MODEL.lock().clone() {
+        return Some(model);
+    }
+    if LID_LOAD_FAILED.load(Ordering::SeqCst) {
+        return None;
+    }
+    let path = lid_model_path(app).ok()?;
+    if !path.is_file() {
+        if !LID_SHA256.is_empty() && !LID_DOWNLOADING.swap(true, Ordering::SeqCst) {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = download_lid_model(&app).await {
+                    println!("[lang] {err}");
+                }
+                LID_DOWNLOADING.store(false, Ordering::SeqCst);
+            });
+        }
+        return None;
+    }
+    match std::fs::read(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| FastText::load(&bytes))
+    {
+        Ok(model) => {
+            let model = Arc::new(model);
+            *LID_MODEL.lock() = Some(model.clone());
+            Some(model)
+        }
+        Err(err) => {
+            println!("[lang] failed to load {}: {err}", path.display());
+            LID_LOAD_FAILED.store(true, Ordering::SeqCst);
+            None
+        }
+    }
+}
+
+/// Detects the language of `text` offline. `allowlist` limits the candidates
+/// (app or ISO 639 codes) and falls back to `languageDetectionAllowlist` from
+/// the config; `alternatives` is how many runner-up languages to report.
+/// Returns `None` when the text has no recognizable language.
+#[tauri::command]
+#[specta::specta]
+pub fn detect_lang(
+    app: AppHandle,
+    text: String,
+    allowlist: Option<Vec<String>>,
+    alternatives: Option<u32>,
+) -> Result<Option<LangDetection>, String> {
+    let allowlist = resolve_allowlist(allowlist)?;
+    let model = lid_model(&app);
+    Ok(detect(
+        model.as_deref(),
+        &text,
+        allowlist.as_deref(),
+        alternatives.unwrap_or(DEFAULT_ALTERNATIVES),
+    ))
+}
+
+/// Which detector is configured and whether the fastText model is on disk and
+/// in memory.
+#[tauri::command]
+#[specta::specta]
+pub fn get_lang_model_status(app: AppHandle) -> LangModelStatus {
+    LangModelStatus {
+        backend: configured_backend(),
+        installed: lid_model_path(&app).is_ok_and(|path| path.is_file()),
+        loaded: LID_MODEL.lock().is_some(),
+    }
+}
+
+/// Downloads the fastText language-ID model, reporting progress through
+/// `lang-model-download-progress` events.
+#[tauri::command]
+#[specta::specta]
+pub async fn download_lang_model(app: AppHandle) -> Result<(), String> {
+    if LID_DOWNLOADING.swap(true, Ordering::SeqCst) {
+        return Err("the language model is already downloading".to_string());
+    }
+    let result = download_lid_model(&app).await;
+    LID_DOWNLOADING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// The explicit allowlist, else the configured one; `None` allows everything.
+fn resolve_allowlist(allowlist: Option<Vec<String>>) -> Result<Option<Vec<Lang>>, String> {
+    Ok(allowlist
+        .or_else(|| {
+            crate::config::get_config()
+                .ok()
+                .and_then(|config| config.language_detection_allowlist)
+        })
+        .map(|codes| parse_allowlist(&codes))
+        .transpose()?
+        .filter(|allowlist| !allowlist.is_empty()))
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LangSegment {
+    pub text: String,
+    /// Offsets of the run in UTF-16 code units, as JavaScript indexes
+    /// strings, end exclusive.
+    pub start: u32,
+    pub end: u32,
+    /// Language code as used by the frontend; empty when the run has no
+    /// letters, e.g. only digits or code.
+    pub lang: String,
+    /// Writing system of the run, e.g. `Latin`; empty like `lang`.
+    pub script: String,
+    pub is_reliable: bool,
+}
+
+/// ASCII tokens such as `getUserName()`, `snake_case`, `a.b.c` or URLs. Their
+/// letters say nothing about the language around them.
+fn is_code_like(token: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "()", "::", "->", "=>", "://", "_", "=", "{", "}", "[", "]", "<", ">", "\\", "`", "$",
+    ];
+    if !token.is_ascii() || !token.bytes().any(|b| b.is_ascii_alphabetic()) {
+        return false;
+    }
+    if MARKERS.iter().any(|marker| token.contains(marker)) {
+        return true;
+    }
+    let bytes = token.as_bytes();
+    bytes.windows(3).any(|window| {
+        matches!(window[1], b'.' | b'/' | b'@' | b'(')
+            && window[0].is_ascii_alphanumeric()
+            && window[2].is_ascii_alphanumeric()
+    }) || bytes
+        .windows(2)
+        .skip(1)
+        .any(|pair| pair[0].is_ascii_lowercase() && pair[1].is_ascii_uppercase())
+}
+
+/// Script of a letter, with kana folded into Han so Japanese sentences stay
+/// in one run; `None` for anything that should not start a new run.
+fn char_script(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    match detect_script(c.encode_utf8(&mut [0; 4]))? {
+        Script::Hiragana | Script::Katakana => Some(Script::Mandarin),
+        script => Some(script),
+    }
+}
+
+/// Splits `text` into runs of one script. Punctuation, digits, whitespace and
+/// code-like tokens never start a run: they stay with the run before them, or
+/// the first run when they lead the text. Also returns which characters
+/// belong to code-like tokens.
+fn script_runs(text: &str) -> (Vec<(usize, usize)>, Vec<bool>) {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut neutral = vec![false; chars.len()];
+    let mut token_start = None;
+    for i in 0..=chars.len() {
+        let boundary = chars.get(i).map_or(true, |c| c.is_whitespace());
+        match (boundary, token_start) {
+            (false, None) => token_start = Some(i),
+            (true, Some(start)) => {
+                let token = chars[start..i].iter().collect::<String>();
+                if is_code_like(&token) {
+                    neutral[start..i].fill(true);
+                }
+                token_start = None;
+            }
+            _ => {}
+        }
+    }
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut current = None;
+    for (i, c) in chars.iter().enumerate() {
+        let script = if neutral[i] { None } else { char_script(*c) };
+        match (script, current) {
+            (Some(script), Some(previous)) if script != previous => {
+                runs.push((runs.last().map_or(0, |run| run.1), i));
+                current = Some(script);
+            }
+            (Some(script), None) => current = Some(script),
+            _ => {}
+        }
+    }
+    runs.push((runs.last().map_or(0, |run| run.1), chars.len()));
+    (runs, neutral)
+}
+
+/// Splits `text` into contiguous runs labeled with language and script.
+/// Adjacent runs of the same language are merged.
+pub fn segment(
+    model: Option<&FastText>,
+    text: &str,
+    allowlist: Option<&[Lang]>,
+) -> Vec<LangSegment> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let chars = text.chars().collect::<Vec<_>>();
+    let utf16 = std::iter::once(0)
+        .chain(chars.iter().scan(0, |offset, c| {
+            *offset += c.len_utf16();
+            Some(*offset)
+        }))
+        .collect::<Vec<_>>();
+    let english_allowed = allowlist.map_or(true, |allowlist| allowlist.contains(&Lang::Eng));
+    let mut segments: Vec<LangSegment> = Vec::new();
+    let (runs, code) = script_runs(text);
+    for (start, end) in runs {
+        let run = chars[start..end].iter().collect::<String>();
+        // Code would otherwise outvote the few words around it.
+        let prose = (start..end)
+            .map(|i| if code[i] { ' ' } else { chars[i] })
+            .collect::<String>();
+        let (lang, script, is_reliable) = match detect(model, &prose, allowlist, 0) {
+            Some(detection) => {
+                // Latin words inside CJK prose are nearly always English, but
+                // too short for the detectors to tell.
+                let lang = if !detection.is_reliable
+                    && detection.script == Script::Latin.name()
+                    && prose.is_ascii()
+                    && english_allowed
+                {
+                    codes(Lang::Eng).0.to_string()
+                } else {
+                    detection.lang
+                };
+                (lang, detection.script, detection.is_reliable)
+            }
+            None => (String::new(), String::new(), false),
+        };
+        match segments.last_mut() {
+            Some(last) if last.lang == lang => {
+                last.text.push_str(&run);
+                last.end = utf16[end] as u32;
+                last.is_reliable &= is_reliable;
+            }
+            _ => segments.push(LangSegment {
+                text: run,
+                start: utf16[start] as u32,
+                end: utf16[end] as u32,
+                lang,
+                script,
+                is_reliable,
+            }),
+        }
+    }
+    segments
+}
+
+/// Splits mixed-language text, e.g. English terms inside Chinese prose, into
+/// runs labeled with language and script, so callers can skip runs already in
+/// the target language or route each run to a matching voice. `allowlist`
+/// works as for `detect_lang`.
 #[tauri::command]
 #[specta::specta]
-pub fn detect_lang(text: String) -> String {
-    match detect(&text) {
-        Some(info) => info.lang().to_string(),
-        None => "".to_string(),
+pub fn segment_lang(
+    app: AppHandle,
+    text: String,
+    allowlist: Option<Vec<String>>,
+) -> Result<Vec<LangSegment>, String> {
+    let allowlist = resolve_allowlist(allowlist)?;
+    let model = lid_model(&app);
+    Ok(segment(model.as_deref(), &text, allowlist.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_whatlang_codes_to_app_and_iso_codes() {
+        let detection = detect(None, "这是一个用于测试语言检测的句子。", None, 0).unwrap();
+        assert_eq!(detection.lang, "zh-Hans");
+        assert_eq!(detection.iso639_1, "zh");
+        assert_eq!(detection.iso639_3, "cmn");
+        assert_eq!(detection.script, "Mandarin");
+        assert!(detection.alternatives.is_empty());
+
+        assert!(Lang::all().iter().all(|lang| !codes(*lang).1.is_empty()));
+        assert_eq!(parse_lang("zh-Hant"), Some(Lang::Cmn));
+        assert_eq!(parse_lang("jv"), Some(Lang::Jav));
+        assert_eq!(parse_lang("jw"), Some(Lang::Jav));
+        assert_eq!(parse_lang("deu"), Some(Lang::Deu));
+        assert!(parse_allowlist(&["xx".to_string()]).is_err());
+
+        assert_eq!(fasttext_codes("zh").0, "zh-Hans");
+        assert_eq!(fasttext_codes("zh").2, "cmn");
+        assert_eq!(
+            fasttext_codes("yue"),
+            ("yue".to_string(), String::new(), "yue".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn allowlist_limits_result_and_alternatives() {
+        let allowlist = [Lang::Deu, Lang::Nld];
+        let detection = detect(None, "Das ist ein kurzer Satz", Some(&allowlist), 5).unwrap();
+        assert_eq!(detection.lang, "de");
+        assert_eq!(
+            detection
+                .alternatives
+                .iter()
+                .map(|candidate| candidate.lang.as_str())
+                .collect::<Vec<_>>(),
+            vec!["nl"]
+        );
+    }
+
+    #[test]
+    fn alternatives_exclude_the_best_match() {
+        let detection =
+            detect(None, "The quick brown fox jumps over the lazy dog", None, 3).unwrap();
+        assert_eq!(detection.lang, "en");
+        assert_eq!(detection.alternatives.len(), 3);
+        assert!(detection
+            .alternatives
+            .iter()
+            .all(|candidate| candidate.lang != "en"));
+    }
+
+    fn langs(text: &str) -> Vec<(String, String)> {
+        segment(None, text, None)
+            .into_iter()
+            .map(|segment| (segment.lang, segment.text))
+            .collect()
+    }
+
+    #[test]
+    fn segments_english_terms_inside_chinese() {
+        assert_eq!(
+            langs("我今天用 React hooks 重写了这个组件，效果很好。"),
+            vec![
+                ("zh-Hans".to_string(), "我今天用 ".to_string()),
+                ("en".to_string(), "React hooks ".to_string()),
+                (
+                    "zh-Hans".to_string(),
+                    "重写了这个组件，效果很好。".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn segments_quoted_japanese_inside_english() {
+        let segments = segment(
+            None,
+            "He wrote 「今日はとても暑いですね」 on the card.",
+            None,
+        );
+        assert_eq!(
+            segments
+                .iter()
+                .map(|segment| segment.lang.as_str())
+                .collect::<Vec<_>>(),
+            vec!["en", "ja", "en"]
+        );
+        assert_eq!(segments[1].start, 10);
+        assert_eq!(segments[1].end, 23);
+        let segments = segment(None, "🙂 Hello 你好世界", None);
+        assert_eq!(
+            segments.last().map(|last| (last.start, last.end)),
+            Some((9, 13))
+        );
+    }
+
+    #[test]
+    fn code_digits_and_punctuation_do_not_split_runs() {
+        assert_eq!(
+            langs("请调用 getUserName() 函数，返回 42 个结果"),
+            vec![(
+                "zh-Hans".to_string(),
+                "请调用 getUserName() 函数，返回 42 个结果".to_string()
+            )]
+        );
+        assert_eq!(
+            langs("12345 + 678"),
+            vec![(String::new(), "12345 + 678".to_string())]
+        );
+        assert!(segment(None, "", None).is_empty());
     }
 }
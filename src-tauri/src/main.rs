@@ -3,14 +3,27 @@
     windows_subsystem = "windows"
 )]
 
+mod app_rules;
+mod asr;
+mod asr_audio;
 mod ax_context;
 mod config;
+mod config_validation;
+mod download;
+mod fasttext;
 mod fetch;
 mod insertion;
 mod lang;
 mod ocr;
+mod opencc;
+mod paths;
+mod pronunciation;
 mod tray;
 mod tts;
+mod tts_audio;
+mod tts_catalog;
+mod tts_markup;
+mod tts_normalize;
 mod utils;
 mod windows;
 mod writing;
@@ -34,12 +47,29 @@ use tauri_specta::Event;
 use tray::{PinnedFromTrayEvent, PinnedFromWindowEvent};
 use windows::{get_translator_window, CheckUpdateEvent, CheckUpdateResultEvent};
 
+use crate::app_rules::{get_active_app_rule, match_app_rule};
+use crate::asr::{
+    cancel_asr_download, delete_asr_model, download_asr_model, import_asr_model, list_asr_models,
+    pause_asr_download, resume_asr_download, transcribe_audio_file,
+};
 use crate::ax_context::{read_ax_context_narrow, read_ax_context_wide};
-use crate::config::{clear_config_cache, get_config_content, ConfigUpdatedEvent};
+use crate::config::{
+    clear_config_cache, get_config_content, get_config_sources, get_locked_config_keys,
+    init_launch_overrides, is_telemetry_enabled, save_config_content, ConfigUpdatedEvent,
+};
+use crate::config_validation::validate_config;
 use crate::fetch::fetch_stream;
-use crate::lang::detect_lang;
+use crate::lang::{detect_lang, download_lang_model, get_lang_model_status, segment_lang};
 use crate::ocr::{cut_image, finish_ocr, screenshot, start_ocr};
-use crate::tts::synthesize_local_tts;
+use crate::opencc::convert_chinese;
+use crate::paths::{get_app_directories, init_portable_mode};
+use crate::pronunciation::transcribe_pronunciation;
+use crate::tts::{
+    cancel_tts_download, clear_tts_cache, delete_tts_model, download_tts_model, export_local_tts,
+    get_tts_storage, import_tts_model, list_loaded_tts_engines, list_tts_models, list_tts_voices,
+    pause_tts_download, resume_tts_download, synthesize_local_tts, synthesize_local_tts_stream,
+    verify_tts_model,
+};
 use crate::windows::{
     get_translator_window_always_on_top, get_writing_indicator_pending_lang,
     hide_inline_lookup_window, hide_quick_translator_window, hide_translator_window,
@@ -140,7 +170,8 @@ fn launch_ipc_server(server: &Server) {
     for mut req in server.incoming_requests() {
         let mut selected_text = String::new();
         req.as_reader().read_to_string(&mut selected_text).unwrap();
-        let use_compact = config::get_config()
+        app_rules::update_active_rule();
+        let use_compact = app_rules::get_app_config()
             .ok()
             .and_then(|c| c.use_compact_lookup)
             .unwrap_or(false);
@@ -177,11 +208,6 @@ fn bind_mouse_hook() {
     let hook_result = mouse_manager.hook(Box::new(|event| {
         match event {
             mouce::common::MouseEvent::Press(mouce::common::MouseButton::Left) => {
-                let config = config::get_config().unwrap();
-                let always_show_icons = config.always_show_icons.unwrap_or(true);
-                if !always_show_icons {
-                    return;
-                }
                 let current_press_time = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
@@ -189,12 +215,6 @@ fn bind_mouse_hook() {
                 *PREVIOUS_PRESS_TIME.lock() = current_press_time;
             }
             mouce::common::MouseEvent::Release(mouce::common::MouseButton::Left) => {
-                let config = config::get_config().unwrap();
-                let always_show_icons = config.always_show_icons.unwrap_or(true);
-                if !always_show_icons {
-                    windows::delete_thumb();
-                    return;
-                }
                 let current_release_time = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
@@ -291,6 +311,15 @@ fn bind_mouse_hook() {
                         return;
                     }
                     std::thread::spawn(move || {
+                        // The app rule is only resolved for selections, as
+                        // looking up the frontmost window on every click is
+                        // too slow for the hook.
+                        app_rules::update_active_rule();
+                        let config = app_rules::get_app_config().unwrap();
+                        if !config.always_show_icons.unwrap_or(true) {
+                            windows::delete_thumb();
+                            return;
+                        }
                         #[cfg(target_os = "macos")]
                         {
                             if !utils::is_valid_selected_frame().unwrap_or(false) {
@@ -315,7 +344,7 @@ fn bind_mouse_hook() {
                     windows::close_thumb();
                     let selected_text = (*SELECTED_TEXT.lock()).to_string();
                     if !selected_text.is_empty() {
-                        let use_compact = config::get_config()
+                        let use_compact = app_rules::get_app_config()
                             .ok()
                             .and_then(|c| c.use_compact_lookup)
                             .unwrap_or(false);
@@ -357,7 +386,10 @@ fn main() {
     }
 
     let _ = init_tokio_runtime();
-    let silently = env::args().any(|arg| arg == "--silently");
+    let args = env::args().collect::<Vec<_>>();
+    let silently = args.iter().any(|arg| arg == "--silently");
+    init_portable_mode(&args);
+    init_launch_overrides(&args);
 
     let mut sys = System::new();
     sys.refresh_cpu(); // Refreshing CPU information.
@@ -369,6 +401,13 @@ fn main() {
     let specta_builder = tauri_specta::Builder::<tauri::Wry>::new()
         .commands(tauri_specta::collect_commands![
             get_config_content,
+            save_config_content,
+            get_locked_config_keys,
+            get_config_sources,
+            get_app_directories,
+            get_active_app_rule,
+            match_app_rule,
+            validate_config,
             get_update_result,
             clear_config_cache,
             show_translator_window_command,
@@ -387,6 +426,11 @@ fn main() {
             insert_translation_into_previous_input,
             remember_active_window_command,
             detect_lang,
+            segment_lang,
+            get_lang_model_status,
+            download_lang_model,
+            convert_chinese,
+            transcribe_pronunciation,
             screenshot,
             hide_translator_window,
             hide_inline_lookup_window,
@@ -399,6 +443,28 @@ fn main() {
             finish_ocr,
             cut_image,
             synthesize_local_tts,
+            synthesize_local_tts_stream,
+            export_local_tts,
+            clear_tts_cache,
+            list_loaded_tts_engines,
+            list_tts_models,
+            list_tts_voices,
+            download_tts_model,
+            pause_tts_download,
+            resume_tts_download,
+            cancel_tts_download,
+            verify_tts_model,
+            delete_tts_model,
+            get_tts_storage,
+            import_tts_model,
+            transcribe_audio_file,
+            list_asr_models,
+            download_asr_model,
+            pause_asr_download,
+            resume_asr_download,
+            cancel_asr_download,
+            delete_asr_model,
+            import_asr_model,
             recover_webview_visibility,
         ])
         .events(tauri_specta::collect_events![
@@ -427,6 +493,9 @@ fn main() {
         .plugin(
             tauri_plugin_aptabase::Builder::new("A-US-9856842764")
                 .with_panic_hook(Box::new(|client, info, msg| {
+                    if !is_telemetry_enabled() {
+                        return;
+                    }
                     let location = info
                         .location()
                         .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column()))
@@ -463,6 +532,13 @@ fn main() {
             specta_builder_setup.mount_events(app);
             let app_handle = app.handle();
             APP_HANDLE.get_or_init(|| app.handle().clone());
+            if paths::is_portable() {
+                // The screenshot window loads captures through the asset
+                // protocol, whose static scope only covers the OS cache dirs.
+                let cache_dir = paths::app_cache_dir(app_handle)?;
+                app.asset_protocol_scope()
+                    .allow_directory(&cache_dir, true)?;
+            }
             tray::create_tray(&app_handle)?;
             app_handle.plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
             app_handle.plugin(tauri_plugin_updater::Builder::new().build())?;
@@ -564,6 +640,7 @@ fn main() {
                 ALWAYS_ON_TOP.store(*pinned, Ordering::Release);
                 tray::create_tray(&handle).unwrap();
             });
+            tts::warm_up(app_handle);
 
             let handle = app_handle.clone();
             ConfigUpdatedEvent::listen_any(app_handle, move |_event| {
@@ -588,11 +665,15 @@ fn main() {
 
     app.run(|app, event| match event {
         tauri::RunEvent::Exit { .. } => {
-            let _ = app.track_event("app_exited", None);
+            if is_telemetry_enabled() {
+                let _ = app.track_event("app_exited", None);
+            }
             app.flush_events_blocking();
         }
         tauri::RunEvent::Ready => {
-            let _ = app.track_event("app_started", None);
+            if is_telemetry_enabled() {
+                let _ = app.track_event("app_started", None);
+            }
             bind_mouse_hook();
             let handle = app.clone();
             tauri::async_runtime::spawn(async move {
@@ -3,8 +3,6 @@ use crate::insertion::remember_active_window;
 use debug_print::debug_println;
 #[cfg(any(target_os = "macos", target_os = "windows"))]
 use std::path::Path;
-use tauri::path::BaseDirectory;
-use tauri::Manager;
 
 #[tauri::command(async)]
 #[specta::specta]
@@ -17,9 +15,7 @@ pub fn cut_image(left: u32, top: u32, width: u32, height: u32) {
             return;
         }
     };
-    let image_dir = match app_handle
-        .path()
-        .resolve("ocr_images", BaseDirectory::AppCache)
+    let image_dir = match crate::paths::app_cache_dir(app_handle).map(|dir| dir.join("ocr_images"))
     {
         Ok(dir) => dir,
         Err(e) => {
@@ -70,7 +66,7 @@ pub fn cut_image(left: u32, top: u32, width: u32, height: u32) {
 
 #[tauri::command]
 #[specta::specta]
-pub fn screenshot(x: i32, y: i32) {
+pub fn screenshot(x: i32, y: i32) -> Option<String> {
     use screenshots::{Compression, Screen};
     use std::fs;
 
@@ -78,7 +74,7 @@ pub fn screenshot(x: i32, y: i32) {
         Ok(screens) => screens,
         Err(e) => {
             eprintln!("Failed to get screens: {:?}", e);
-            return;
+            return None;
         }
     };
     // Silently doing nothing when no display matches leaves a stale
@@ -98,7 +94,7 @@ pub fn screenshot(x: i32, y: i32) {
                 Some(screen) => screen,
                 None => {
                     eprintln!("No screens available");
-                    return;
+                    return None;
                 }
             }
         }
@@ -107,23 +103,21 @@ pub fn screenshot(x: i32, y: i32) {
         Some(handle) => handle,
         None => {
             eprintln!("APP_HANDLE not initialized");
-            return;
+            return None;
         }
     };
-    let image_dir = match app_handle
-        .path()
-        .resolve("ocr_images", BaseDirectory::AppCache)
+    let image_dir = match crate::paths::app_cache_dir(app_handle).map(|dir| dir.join("ocr_images"))
     {
         Ok(dir) => dir,
         Err(e) => {
             eprintln!("Failed to resolve ocr_images directory: {:?}", e);
-            return;
+            return None;
         }
     };
     if !image_dir.exists() {
         if let Err(e) = std::fs::create_dir_all(&image_dir) {
             eprintln!("Failed to create ocr_images directory: {:?}", e);
-            return;
+            return None;
         }
     }
     let image_file_path = image_dir.join("fullscreen.png");
@@ -134,20 +128,22 @@ pub fn screenshot(x: i32, y: i32) {
         Ok(img) => img,
         Err(e) => {
             eprintln!("Failed to capture screen: {:?}", e);
-            return;
+            return None;
         }
     };
     let buffer = match image.to_png(Compression::Fast) {
         Ok(buf) => buf,
         Err(e) => {
             eprintln!("Failed to convert image to PNG: {:?}", e);
-            return;
+            return None;
         }
     };
     debug_println!("image_file_path: {:?}", image_file_path);
     if let Err(e) = fs::write(&image_file_path, buffer) {
         eprintln!("Failed to write screenshot file: {:?}", e);
+        return None;
     }
+    Some(image_file_path.to_string_lossy().into_owned())
 }
 
 #[cfg(target_os = "linux")]
@@ -397,9 +393,7 @@ fn do_finish_ocr() {
             return;
         }
     };
-    let image_dir = match app_handle
-        .path()
-        .resolve("ocr_images", BaseDirectory::AppCache)
+    let image_dir = match crate::paths::app_cache_dir(app_handle).map(|dir| dir.join("ocr_images"))
     {
         Ok(dir) => dir,
         Err(e) => {
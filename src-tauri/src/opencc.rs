@@ -0,0 +1,295 @@
+// Conversion between Simplified Chinese and the Traditional standards of
+// Taiwan and Hong Kong, following OpenCC: the text is split into the longest
+// known phrases, then every piece runs through a chain of dictionaries with
+// longest-prefix matching. Fast and deterministic, so it can replace an LLM
+// round trip for zh-Hans <-> zh-Hant and clean up LLM output.
+
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dict {
+    StCharacters,
+    StPhrases,
+    TsCharacters,
+    TsPhrases,
+    TwPhrases,
+    TwPhrasesRev,
+    TwVariants,
+    TwVariantsRev,
+    TwVariantsRevPhrases,
+    HkVariants,
+    HkVariantsRev,
+    HkVariantsRevPhrases,
+}
+
+const DICT_COUNT: usize = 12;
+
+impl Dict {
+    fn source(self) -> &'static str {
+        macro_rules! dict {
+            ($name:literal) => {
+                include_str!(concat!("../dictionaries/opencc/", $name, ".txt"))
+            };
+        }
+        match self {
+            Self::StCharacters => dict!("STCharacters"),
+            Self::StPhrases => dict!("STPhrases"),
+            Self::TsCharacters => dict!("TSCharacters"),
+            Self::TsPhrases => dict!("TSPhrases"),
+            Self::TwPhrases => dict!("TWPhrases"),
+            Self::TwPhrasesRev => dict!("TWPhrasesRev"),
+            Self::TwVariants => dict!("TWVariants"),
+            Self::TwVariantsRev => dict!("TWVariantsRev"),
+            Self::TwVariantsRevPhrases => dict!("TWVariantsRevPhrases"),
+            Self::HkVariants => dict!("HKVariants"),
+            Self::HkVariantsRev => dict!("HKVariantsRev"),
+            Self::HkVariantsRevPhrases => dict!("HKVariantsRevPhrases"),
+        }
+    }
+
+    /// Parsed on first use; `STPhrases` alone has ~50k entries.
+    fn table(self) -> &'static Table {
+        // A named constant, as inline `const` blocks in array repeats need
+        // Rust 1.79.
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: OnceCell<Table> = OnceCell::new();
+        static TABLES: [OnceCell<Table>; DICT_COUNT] = [EMPTY; DICT_COUNT];
+        TABLES[self as usize].get_or_init(|| Table::parse(self.source()))
+    }
+}
+
+struct Table {
+    entries: HashMap<String, String>,
+    /// Longest key, in characters.
+    max_len: usize,
+}
+
+impl Table {
+    /// `key<TAB>value [alternatives…]` lines; the first value is the default.
+    fn parse(source: &str) -> Self {
+        let mut entries = HashMap::new();
+        let mut max_len = 0;
+        for line in source.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, values)) = line.split_once('\t') else {
+                continue;
+            };
+            let Some(value) = values.split(' ').find(|value| !value.is_empty()) else {
+                continue;
+            };
+            max_len = max_len.max(key.chars().count());
+            entries.insert(key.to_string(), value.to_string());
+        }
+        Self { entries, max_len }
+    }
+
+    /// Longest key that `text[start..]` begins with, as (end, value).
+    fn match_prefix(
+        &self,
+        text: &str,
+        boundaries: &[usize],
+        start: usize,
+    ) -> Option<(usize, &str)> {
+        let longest = self.max_len.min(boundaries.len() - 1 - start);
+        (1..=longest).rev().find_map(|len| {
+            let key = &text[boundaries[start]..boundaries[start + len]];
+            self.entries
+                .get(key)
+                .map(|value| (start + len, value.as_str()))
+        })
+    }
+}
+
+/// Dictionaries consulted together; like OpenCC, the first one with any
+/// match wins, so phrases take precedence over single characters.
+fn match_group(
+    group: &[Dict],
+    text: &str,
+    boundaries: &[usize],
+    start: usize,
+) -> Option<(usize, &'static str)> {
+    group
+        .iter()
+        .find_map(|dict| dict.table().match_prefix(text, boundaries, start))
+}
+
+fn char_boundaries(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(text.len()))
+        .collect()
+}
+
+fn convert_with(group: &[Dict], text: &str) -> String {
+    let boundaries = char_boundaries(text);
+    let mut output = String::with_capacity(text.len());
+    let mut position = 0;
+    while position + 1 < boundaries.len() {
+        match match_group(group, text, &boundaries, position) {
+            Some((end, value)) => {
+                output.push_str(value);
+                position = end;
+            }
+            None => {
+                output.push_str(&text[boundaries[position]..boundaries[position + 1]]);
+                position += 1;
+            }
+        }
+    }
+    output
+}
+
+/// Splits `text` into the longest phrases of `dict`, keeping unmatched
+/// characters together, so that later steps cannot break a phrase apart.
+fn segment(dict: Dict, text: &str) -> Vec<&str> {
+    let boundaries = char_boundaries(text);
+    let mut segments = Vec::new();
+    let mut pending = 0;
+    let mut position = 0;
+    while position + 1 < boundaries.len() {
+        match dict.table().match_prefix(text, &boundaries, position) {
+            Some((end, _)) => {
+                if pending < position {
+                    segments.push(&text[boundaries[pending]..boundaries[position]]);
+                }
+                segments.push(&text[boundaries[position]..boundaries[end]]);
+                position = end;
+                pending = end;
+            }
+            None => position += 1,
+        }
+    }
+    if pending < position {
+        segments.push(&text[boundaries[pending]..]);
+    }
+    segments
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    Simplified,
+    /// OpenCC's standard Traditional forms.
+    Traditional,
+    Taiwan,
+    HongKong,
+}
+
+impl Variant {
+    fn parse(code: &str) -> Result<Self, String> {
+        match code.trim().to_ascii_lowercase().as_str() {
+            "zh-hans" | "zh-cn" | "zh-sg" | "zh-my" | "zh" => Ok(Self::Simplified),
+            "zh-hant" => Ok(Self::Traditional),
+            "zh-tw" => Ok(Self::Taiwan),
+            "zh-hk" | "zh-mo" => Ok(Self::HongKong),
+            _ => Err(format!("unsupported Chinese variant: {code}")),
+        }
+    }
+
+    /// Steps from this variant to standard Traditional.
+    fn steps_to_traditional(self) -> &'static [&'static [Dict]] {
+        match self {
+            Self::Simplified => &[&[Dict::StPhrases, Dict::StCharacters]],
+            Self::Traditional => &[],
+            Self::Taiwan => &[&[
+                Dict::TwPhrasesRev,
+                Dict::TwVariantsRevPhrases,
+                Dict::TwVariantsRev,
+            ]],
+            Self::HongKong => &[&[Dict::HkVariantsRevPhrases, Dict::HkVariantsRev]],
+        }
+    }
+
+    /// Steps from standard Traditional to this variant.
+    fn steps_from_traditional(self) -> &'static [&'static [Dict]] {
+        match self {
+            Self::Simplified => &[&[Dict::TsPhrases, Dict::TsCharacters]],
+            Self::Traditional => &[],
+            Self::Taiwan => &[&[Dict::TwPhrases], &[Dict::TwVariants]],
+            Self::HongKong => &[&[Dict::HkVariants]],
+        }
+    }
+}
+
+/// Converts `text` between Chinese variants. Going through standard
+/// Traditional yields OpenCC's `s2twp`, `tw2sp`, `s2hk`, `hk2s`, `s2t` and
+/// `t2s`, and regional conversions such as zh-TW -> zh-HK.
+pub fn convert(text: &str, from: &str, to: &str) -> Result<String, String> {
+    let from = Variant::parse(from)?;
+    let to = Variant::parse(to)?;
+    if from == to {
+        return Ok(text.to_string());
+    }
+    let steps = from
+        .steps_to_traditional()
+        .iter()
+        .chain(to.steps_from_traditional())
+        .collect::<Vec<_>>();
+    let segmentation = match (from, to) {
+        (Variant::Simplified, _) => Dict::StPhrases,
+        (_, Variant::Simplified) => Dict::TsPhrases,
+        _ => steps[0][0],
+    };
+    Ok(segment(segmentation, text)
+        .into_iter()
+        .map(|segment| {
+            steps.iter().fold(segment.to_string(), |text, group| {
+                convert_with(group, &text)
+            })
+        })
+        .collect())
+}
+
+/// Converts Chinese text between Simplified (`zh-Hans`, `zh-CN`), standard
+/// Traditional (`zh-Hant`), Taiwan (`zh-TW`) and Hong Kong (`zh-HK`) forms,
+/// including regional vocabulary such as 软件 -> 軟體 for Taiwan.
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_chinese(text: String, from: String, to: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || convert(&text, &from, &to))
+        .await
+        .map_err(|err| format!("convert Chinese task failed: {err}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_simplified_and_traditional() {
+        assert_eq!(
+            convert("头发和发展", "zh-Hans", "zh-Hant").unwrap(),
+            "頭髮和發展"
+        );
+        assert_eq!(
+            convert("頭髮和發展", "zh-Hant", "zh-Hans").unwrap(),
+            "头发和发展"
+        );
+        assert_eq!(
+            convert("Hello, 世界 123", "zh-CN", "zh-Hant").unwrap(),
+            "Hello, 世界 123"
+        );
+    }
+
+    #[test]
+    fn uses_regional_vocabulary() {
+        assert_eq!(
+            convert("鼠标和软件", "zh-CN", "zh-TW").unwrap(),
+            "滑鼠和軟體"
+        );
+        assert_eq!(
+            convert("滑鼠和軟體", "zh-TW", "zh-CN").unwrap(),
+            "鼠标和软件"
+        );
+        assert_eq!(convert("软件", "zh-CN", "zh-HK").unwrap(), "軟件");
+        assert_eq!(convert("軟體", "zh-TW", "zh-HK").unwrap(), "軟件");
+    }
+
+    #[test]
+    fn rejects_unknown_variants() {
+        assert!(convert("中文", "zh-Hans", "ja").is_err());
+        assert_eq!(convert("中文", "zh-TW", "zh-tw").unwrap(), "中文");
+    }
+}
@@ -0,0 +1,112 @@
+// Where the app keeps its files on disk. Normally these are the per-user OS
+// directories Tauri resolves. In portable mode - switched on by a `portable`
+// marker file next to the executable or by `--portable` - everything goes to a
+// `portable-data` folder next to the executable instead, so the app can run
+// from a USB stick or a locked-down shared machine without leaving anything
+// behind in the user profile.
+
+use debug_print::debug_println;
+use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DATA_DIR: &str = "portable-data";
+
+static PORTABLE_ROOT: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+#[derive(Clone, Debug, serde::Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDirectories {
+    pub portable: bool,
+    pub config: String,
+    pub data: String,
+    pub cache: String,
+}
+
+/// Decides once, before anything touches the disk, whether this process runs
+/// in portable mode.
+pub fn init_portable_mode(args: &[String]) {
+    let root = executable_dir().and_then(|dir| {
+        let enabled =
+            args.iter().any(|arg| arg == PORTABLE_FLAG) || dir.join(PORTABLE_MARKER).exists();
+        enabled.then(|| dir.join(PORTABLE_DATA_DIR))
+    });
+    if let Some(root) = &root {
+        debug_println!("portable mode: keeping all data in {}", root.display());
+    }
+    let _ = PORTABLE_ROOT.set(root);
+}
+
+fn portable_root() -> Option<&'static Path> {
+    PORTABLE_ROOT.get().and_then(|root| root.as_deref())
+}
+
+fn portable_dir(name: &str) -> Option<PathBuf> {
+    portable_root().map(|root| root.join(name))
+}
+
+/// The folder users think of as "where the app is": the executable's
+/// directory, or on macOS the directory holding the `.app` bundle.
+fn executable_dir() -> Option<PathBuf> {
+    let executable = std::env::current_exe().ok()?;
+    let directory = executable.parent()?;
+    #[cfg(target_os = "macos")]
+    if let Some(bundle) = directory
+        .ancestors()
+        .find(|path| path.extension().is_some_and(|extension| extension == "app"))
+    {
+        return bundle.parent().map(Path::to_path_buf);
+    }
+    Some(directory.to_path_buf())
+}
+
+pub fn is_portable() -> bool {
+    portable_root().is_some()
+}
+
+/// Config directory in portable mode; `None` means the regular per-user one.
+pub fn portable_config_dir() -> Option<PathBuf> {
+    portable_dir("config")
+}
+
+/// WebView profile directory (local storage, IndexedDB, caches) in portable
+/// mode; `None` leaves it to the platform default.
+pub fn webview_data_dir() -> Option<PathBuf> {
+    portable_dir("webview")
+}
+
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match portable_dir("data") {
+        Some(dir) => Ok(dir),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|err| format!("resolve app data directory: {err}")),
+    }
+}
+
+pub fn app_cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match portable_dir("cache") {
+        Some(dir) => Ok(dir),
+        None => app
+            .path()
+            .app_cache_dir()
+            .map_err(|err| format!("resolve app cache directory: {err}")),
+    }
+}
+
+/// Where config, models and caches currently live, e.g. for an "open
+/// data folder" button or for support requests.
+#[tauri::command]
+#[specta::specta]
+pub fn get_app_directories(app: AppHandle) -> Result<AppDirectories, String> {
+    let display = |path: PathBuf| path.to_string_lossy().into_owned();
+    Ok(AppDirectories {
+        portable: is_portable(),
+        config: display(crate::config::get_config_dir_by_app(&app)),
+        data: display(app_data_dir(&app)?),
+        cache: display(app_cache_dir(&app)?),
+    })
+}
@@ -0,0 +1,580 @@
+// Phonetic readings for ruby display, built from data that is already local:
+// the Chinese TTS model's `lexicon.txt` gives pinyin for Chinese words, the
+// English model's lexicon gives ARPAbet, converted to IPA, for English ones;
+// KAKASI's bundled dictionary gives kana and romaji for Japanese.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::AppHandle;
+
+use crate::tts::is_han;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum PronunciationSystem {
+    /// Hanyu Pinyin with tone marks, one space-separated syllable per character.
+    Pinyin,
+    /// Hiragana, with romaji in `romanization`.
+    Kana,
+    Ipa,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PronunciationToken {
+    pub text: String,
+    /// Offsets in UTF-16 code units, as JavaScript indexes strings, end
+    /// exclusive. The tokens cover the whole input, so they can be rendered
+    /// in order.
+    pub start: u32,
+    pub end: u32,
+    /// `None` with `reading` for punctuation, spaces and unknown words.
+    pub system: Option<PronunciationSystem>,
+    pub reading: Option<String>,
+    pub romanization: Option<String>,
+}
+
+static LEXICONS: Lazy<Mutex<HashMap<PathBuf, Arc<Lexicon>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `word phone…` lines. Tones are either a digit after each phone (`zh
+/// ong1`, `HH AH0`) or, as MeloTTS writes them, a second list of the same
+/// length (`zh ong 1 1`).
+pub struct Lexicon {
+    entries: HashMap<String, Box<str>>,
+    /// Longest Chinese key, in characters.
+    max_han_len: usize,
+}
+
+impl Lexicon {
+    pub fn parse(source: &str) -> Self {
+        let mut entries = HashMap::new();
+        let mut max_han_len = 0;
+        for line in source.lines() {
+            let Some((word, phones)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            if word.chars().any(is_han) {
+                max_han_len = max_han_len.max(word.chars().count());
+            }
+            entries
+                .entry(word.to_lowercase())
+                .or_insert_with(|| phones.trim().into());
+        }
+        Self {
+            entries,
+            max_han_len,
+        }
+    }
+
+    fn phones(&self, word: &str) -> Option<Vec<(&str, Option<u8>)>> {
+        let tokens = self
+            .entries
+            .get(word)?
+            .split_whitespace()
+            .collect::<Vec<_>>();
+        let half = tokens.len() / 2;
+        let is_tone = |token: &&str| token.parse::<u8>().is_ok();
+        if half > 0
+            && tokens.len() % 2 == 0
+            && tokens[half..].iter().all(is_tone)
+            && !tokens[..half].iter().any(is_tone)
+        {
+            return Some(
+                tokens[..half]
+                    .iter()
+                    .zip(&tokens[half..])
+                    .map(|(phone, tone)| (*phone, tone.parse().ok()))
+                    .collect(),
+            );
+        }
+        Some(
+            tokens
+                .into_iter()
+                .map(|token| match token.char_indices().last() {
+                    Some((index, digit)) if index > 0 && digit.is_ascii_digit() => {
+                        (&token[..index], digit.to_digit(10).map(|tone| tone as u8))
+                    }
+                    _ => (token, None),
+                })
+                .collect(),
+        )
+    }
+}
+
+const INITIALS: &[&str] = &[
+    "b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "j", "q", "x", "zh", "ch", "sh", "r",
+    "z", "c", "s", "y", "w",
+];
+
+/// Placeholders MeloTTS uses for syllables without an initial.
+const ZERO_INITIALS: &[&str] = &["AA", "EE", "OO"];
+
+const TONE_MARKS: &[(char, [char; 4])] = &[
+    ('a', ['ā', 'á', 'ǎ', 'à']),
+    ('e', ['ē', 'é', 'ě', 'è']),
+    ('i', ['ī', 'í', 'ǐ', 'ì']),
+    ('o', ['ō', 'ó', 'ǒ', 'ò']),
+    ('u', ['ū', 'ú', 'ǔ', 'ù']),
+    ('ü', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+];
+
+/// The standard spelling of an initial and a final in the strict form TTS
+/// lexicons use (`j v`, `y ia`, `w uei`, `sh ir`, `l iou`).
+fn pinyin_syllable(initial: &str, final_: &str, tone: Option<u8>) -> String {
+    let final_ = match final_ {
+        "ir" | "i0" => "i",
+        other => other,
+    };
+    let final_ = match initial {
+        "y" if final_.len() > 1 && !matches!(final_, "in" | "ing") => {
+            final_.strip_prefix('i').unwrap_or(final_)
+        }
+        "w" if final_.len() > 1 => final_.strip_prefix('u').unwrap_or(final_),
+        "" | "y" | "w" => final_,
+        _ => match final_ {
+            "iou" => "iu",
+            "uei" => "ui",
+            "uen" => "un",
+            other => other,
+        },
+    };
+    let final_ = if matches!(initial, "j" | "q" | "x" | "y") {
+        final_.replace('v', "u")
+    } else {
+        final_.replace('v', "ü")
+    };
+    let syllable = format!("{initial}{final_}");
+    match tone {
+        Some(tone @ 1..=4) => mark_tone(&syllable, tone),
+        _ => syllable,
+    }
+}
+
+/// Puts the tone mark on `a` or `e`, on the `o` of `ou`, else on the last
+/// vowel.
+fn mark_tone(syllable: &str, tone: u8) -> String {
+    let chars = syllable.chars().collect::<Vec<_>>();
+    let target = chars
+        .iter()
+        .position(|c| matches!(c, 'a' | 'e'))
+        .or_else(|| chars.windows(2).position(|pair| pair == ['o', 'u']))
+        .or_else(|| {
+            chars
+                .iter()
+                .rposition(|c| TONE_MARKS.iter().any(|(vowel, _)| vowel == c))
+        });
+    let Some(target) = target else {
+        return syllable.to_string();
+    };
+    chars
+        .iter()
+        .enumerate()
+        .map(|(index, c)| {
+            if index != target {
+                return *c;
+            }
+            TONE_MARKS
+                .iter()
+                .find(|(vowel, _)| vowel == c)
+                .map(|(_, marks)| marks[tone as usize - 1])
+                .unwrap_or(*c)
+        })
+        .collect()
+}
+
+fn pinyin(phones: &[(&str, Option<u8>)]) -> String {
+    let mut syllables = Vec::new();
+    let mut initial = None;
+    for (phone, tone) in phones {
+        if ZERO_INITIALS.contains(phone) {
+            initial = Some("");
+        } else if INITIALS.contains(phone) && initial.is_none() {
+            initial = Some(*phone);
+        } else {
+            syllables.push(pinyin_syllable(initial.take().unwrap_or(""), phone, *tone));
+        }
+    }
+    syllables.join(" ")
+}
+
+fn arpabet_ipa(phone: &str, stress: Option<u8>) -> Option<&'static str> {
+    Some(match phone {
+        "aa" => "ɑ",
+        "ae" => "æ",
+        "ah" if stress == Some(0) => "ə",
+        "ah" => "ʌ",
+        "ao" => "ɔ",
+        "aw" => "aʊ",
+        "ay" => "aɪ",
+        "b" => "b",
+        "ch" => "tʃ",
+        "d" => "d",
+        "dh" => "ð",
+        "eh" => "ɛ",
+        "er" if stress == Some(0) => "ɚ",
+        "er" => "ɝ",
+        "ey" => "eɪ",
+        "f" => "f",
+        "g" => "ɡ",
+        "hh" => "h",
+        "ih" => "ɪ",
+        "iy" => "i",
+        "jh" => "dʒ",
+        "k" => "k",
+        "l" => "l",
+        "m" => "m",
+        "n" => "n",
+        "ng" => "ŋ",
+        "ow" => "oʊ",
+        "oy" => "ɔɪ",
+        "p" => "p",
+        "r" => "ɹ",
+        "s" => "s",
+        "sh" => "ʃ",
+        "t" => "t",
+        "th" => "θ",
+        "uh" => "ʊ",
+        "uw" => "u",
+        "v" => "v",
+        "w" => "w",
+        "y" => "j",
+        "z" => "z",
+        "zh" => "ʒ",
+        _ => return None,
+    })
+}
+
+/// IPA in espeak-ng's style, with stress marks before the stressed vowel.
+/// Separate tone lists store the ARPAbet stress plus one, as MeloTTS does.
+fn ipa(phones: &[(&str, Option<u8>)], separate_tones: bool) -> Option<String> {
+    let mut output = String::new();
+    for (phone, tone) in phones {
+        let phone = phone.to_ascii_lowercase();
+        let is_vowel = phone.starts_with(['a', 'e', 'i', 'o', 'u']);
+        let stress = match (is_vowel, separate_tones) {
+            (false, _) => None,
+            (true, true) => tone.and_then(|tone| tone.checked_sub(1)),
+            (true, false) => *tone,
+        };
+        match stress {
+            Some(1) => output.push('ˈ'),
+            Some(2) => output.push('ˌ'),
+            _ => {}
+        }
+        output.push_str(arpabet_ipa(&phone, stress)?);
+    }
+    Some(output)
+}
+
+fn english_ipa(lexicon: &Lexicon, word: &str) -> Option<String> {
+    let entry = lexicon.entries.get(&word.to_lowercase())?;
+    let phones = lexicon.phones(&word.to_lowercase())?;
+    let separate_tones = entry.split_whitespace().count() == phones.len() * 2;
+    ipa(&phones, separate_tones)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Han,
+    Hiragana,
+    Katakana,
+    Latin,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        '\u{3041}'..='\u{309F}' => CharClass::Hiragana,
+        '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+            CharClass::Katakana
+        }
+        '々' | '〆' => CharClass::Han,
+        c if is_han(c) => CharClass::Han,
+        c if c.is_ascii_alphabetic() => CharClass::Latin,
+        _ => CharClass::Other,
+    }
+}
+
+/// `range` is in characters; `utf16` maps character to UTF-16 offsets.
+fn token(
+    chars: &[char],
+    utf16: &[usize],
+    range: Range<usize>,
+    system: Option<PronunciationSystem>,
+    reading: Option<String>,
+    romanization: Option<String>,
+) -> PronunciationToken {
+    PronunciationToken {
+        text: chars[range.clone()].iter().collect(),
+        start: utf16[range.start] as u32,
+        end: utf16[range.end] as u32,
+        system: reading.as_ref().and(system),
+        reading,
+        romanization,
+    }
+}
+
+/// Splits `text` into words with their readings. Han characters are read as
+/// Japanese when `japanese` is set and as Chinese otherwise; Chinese and
+/// English words without an entry in their lexicon get no reading.
+pub fn transcribe(
+    text: &str,
+    japanese: bool,
+    chinese: Option<&Lexicon>,
+    english: Option<&Lexicon>,
+) -> Vec<PronunciationToken> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let utf16 = std::iter::once(0)
+        .chain(chars.iter().scan(0, |offset, c| {
+            *offset += c.len_utf16();
+            Some(*offset)
+        }))
+        .collect::<Vec<_>>();
+    let classes = chars.iter().map(|c| char_class(*c)).collect::<Vec<_>>();
+    let run_end = |start: usize, class: CharClass| {
+        (start..chars.len())
+            .find(|index| classes[*index] != class)
+            .unwrap_or(chars.len())
+    };
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    while position < chars.len() {
+        let class = classes[position];
+        let (end, system, reading, romanization) = match class {
+            CharClass::Han if !japanese => {
+                let limit = chinese
+                    .map(|lexicon| lexicon.max_han_len)
+                    .unwrap_or(1)
+                    .max(1)
+                    .min(run_end(position, class) - position);
+                let matched = chinese.and_then(|lexicon| {
+                    (1..=limit).rev().find_map(|len| {
+                        let word = chars[position..position + len].iter().collect::<String>();
+                        lexicon
+                            .phones(&word)
+                            .map(|phones| (position + len, pinyin(&phones)))
+                    })
+                });
+                match matched {
+                    Some((end, pinyin)) => {
+                        (end, Some(PronunciationSystem::Pinyin), Some(pinyin), None)
+                    }
+                    None => (position + 1, None, None, None),
+                }
+            }
+            CharClass::Han | CharClass::Hiragana | CharClass::Katakana if japanese => {
+                // Kanji with the kana after them, so okurigana and particles
+                // give KAKASI the context to pick a reading.
+                let mut end = run_end(position, class);
+                if class == CharClass::Han
+                    && end < chars.len()
+                    && classes[end] == CharClass::Hiragana
+                {
+                    end = run_end(end, CharClass::Hiragana);
+                }
+                let word = chars[position..end].iter().collect::<String>();
+                let converted = kakasi::convert(&word);
+                (
+                    end,
+                    Some(PronunciationSystem::Kana),
+                    Some(converted.hiragana),
+                    Some(converted.romaji.trim().to_string()),
+                )
+            }
+            CharClass::Latin => {
+                let mut end = run_end(position, class);
+                // Contractions such as "don't".
+                while end + 1 < chars.len()
+                    && matches!(chars[end], '\'' | '’')
+                    && classes[end + 1] == CharClass::Latin
+                {
+                    end = run_end(end + 1, class);
+                }
+                let word = chars[position..end]
+                    .iter()
+                    .map(|c| if *c == '’' { '\'' } else { *c })
+                    .collect::<String>();
+                let reading = english.and_then(|lexicon| english_ipa(lexicon, &word));
+                (end, Some(PronunciationSystem::Ipa), reading, None)
+            }
+            _ => {
+                let end = (position..chars.len())
+                    .find(|index| {
+                        let class = classes[*index];
+                        class == CharClass::Latin
+                            || class == CharClass::Han
+                            || (japanese && class != CharClass::Other)
+                    })
+                    .unwrap_or(chars.len())
+                    .max(position + 1);
+                (end, None, None, None)
+            }
+        };
+        tokens.push(token(
+            &chars,
+            &utf16,
+            position..end,
+            system,
+            reading,
+            romanization,
+        ));
+        position = end;
+    }
+    tokens
+}
+
+/// `None` when the model with the lexicon is not downloaded.
+fn load_lexicon(path: PathBuf) -> Result<Option<Arc<Lexicon>>, String> {
+    if let Some(lexicon) = LEXICONS.lock().get(&path) {
+        return Ok(Some(lexicon.clone()));
+    }
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let source =
+        std::fs::read_to_string(&path).map_err(|err| format!("read {}: {err}", path.display()))?;
+    let lexicon = Arc::new(Lexicon::parse(&source));
+    LEXICONS.lock().insert(path, lexicon.clone());
+    Ok(Some(lexicon))
+}
+
+/// Phonetic readings of `text` word by word, for ruby-style display: pinyin
+/// with tone marks for Chinese, kana and romaji for Japanese, IPA for English.
+/// `lang` decides how Han characters are read; without it, text containing
+/// kana is read as Japanese. Chinese readings come from the lexicon of the
+/// Chinese TTS model, so that model must be downloaded. English readings come
+/// from the English model's lexicon; with a model that has none, English words
+/// in other text get no reading and English-only text is an error.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_pronunciation(
+    app: AppHandle,
+    text: String,
+    lang: Option<String>,
+) -> Result<Vec<PronunciationToken>, String> {
+    let japanese = match lang.as_deref() {
+        Some(lang) => lang == "ja" || lang.starts_with("ja-"),
+        None => text
+            .chars()
+            .any(|c| matches!(char_class(c), CharClass::Hiragana | CharClass::Katakana)),
+    };
+    tokio::task::spawn_blocking(move || {
+        let needs_chinese = !japanese && text.chars().any(is_han);
+        let needs_english = text.chars().any(|c| char_class(c) == CharClass::Latin);
+        let chinese = if needs_chinese {
+            load_lexicon(crate::tts::chinese_lexicon_path(&app)?)?
+        } else {
+            None
+        };
+        if needs_chinese && chinese.is_none() {
+            return Err("pinyin needs the local Chinese TTS model; download it first".to_string());
+        }
+        let english = if needs_english {
+            match crate::tts::english_lexicon_path(&app)? {
+                Some(path) => load_lexicon(path)?,
+                None if needs_chinese || japanese => None,
+                None => {
+                    return Err("the English TTS model has no lexicon to read IPA from".to_string())
+                }
+            }
+        } else {
+            None
+        };
+        if needs_english && english.is_none() && !needs_chinese && !japanese {
+            return Err("IPA needs the local English TTS model; download it first".to_string());
+        }
+        Ok(transcribe(
+            &text,
+            japanese,
+            chinese.as_deref(),
+            english.as_deref(),
+        ))
+    })
+    .await
+    .map_err(|err| format!("transcribe pronunciation task failed: {err}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEXICON: &str = "中 zh ong1\n中国 zh ong1 g uo2\n国 g uo2\n人 r en2\n女 n v3\n\
+        有 y iou3\n一 y i1\n为 w uei4\n长 ch ang2\n长大 zh ang3 d a4\n\
+        hello HH AH0 L OW1\nworld w er l d 0 2 0 0\ndon't D OW1 N T\n";
+
+    fn readings(tokens: &[PronunciationToken]) -> Vec<(&str, Option<&str>)> {
+        tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.reading.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn writes_pinyin_with_tone_marks() {
+        assert_eq!(pinyin_syllable("j", "v", Some(3)), "jǔ");
+        assert_eq!(pinyin_syllable("n", "v", Some(3)), "nǚ");
+        assert_eq!(pinyin_syllable("l", "iou", Some(2)), "liú");
+        assert_eq!(pinyin_syllable("y", "ia", Some(1)), "yā");
+        assert_eq!(pinyin_syllable("y", "ing", Some(2)), "yíng");
+        assert_eq!(pinyin_syllable("w", "uei", Some(4)), "wèi");
+        assert_eq!(pinyin_syllable("sh", "ir", Some(4)), "shì");
+        assert_eq!(pinyin_syllable("g", "ou", Some(3)), "gǒu");
+        assert_eq!(pinyin_syllable("", "er", Some(2)), "ér");
+        assert_eq!(pinyin_syllable("m", "a", Some(5)), "ma");
+        assert_eq!(pinyin(&[("AA", Some(4)), ("ai", Some(4))]), "ài");
+    }
+
+    #[test]
+    fn reads_chinese_by_longest_phrase_and_english_as_ipa() {
+        let lexicon = Lexicon::parse(LEXICON);
+        let tokens = transcribe(
+            "中国人长大了, Hello world! Don’t",
+            false,
+            Some(&lexicon),
+            Some(&lexicon),
+        );
+        assert_eq!(
+            readings(&tokens),
+            vec![
+                ("中国", Some("zhōng guó")),
+                ("人", Some("rén")),
+                ("长大", Some("zhǎng dà")),
+                ("了", None),
+                (", ", None),
+                ("Hello", Some("həlˈoʊ")),
+                (" ", None),
+                ("world", Some("wˈɝld")),
+                ("! ", None),
+                ("Don’t", Some("dˈoʊnt")),
+            ]
+        );
+        assert_eq!(tokens[1].start, 2);
+        assert_eq!(tokens[1].end, 3);
+        assert_eq!(tokens[0].system, Some(PronunciationSystem::Pinyin));
+        assert_eq!(tokens[3].system, None);
+        let emoji_tokens = transcribe("🙂中", false, Some(&lexicon), None);
+        assert_eq!((emoji_tokens[1].start, emoji_tokens[1].end), (2, 3));
+    }
+
+    #[test]
+    fn reads_japanese_with_okurigana() {
+        let tokens = transcribe("東京へ行った。カメラ", true, None, None);
+        assert_eq!(
+            readings(&tokens),
+            vec![
+                ("東京へ", Some("とうきょうへ")),
+                ("行った", Some("いった")),
+                ("。", None),
+                ("カメラ", Some("かめら")),
+            ]
+        );
+        assert_eq!(tokens[0].romanization.as_deref(), Some("toukyou he"));
+        assert_eq!(tokens[3].end, 10);
+    }
+}
@@ -1,85 +1,90 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use bzip2::read::BzDecoder;
-use futures_util::StreamExt;
+use debug_print::debug_println;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use sherpa_onnx::{
     GenerationConfig, OfflineTts, OfflineTtsConfig, OfflineTtsKokoroModelConfig,
-    OfflineTtsModelConfig, OfflineTtsVitsModelConfig,
+    OfflineTtsMatchaModelConfig, OfflineTtsModelConfig, OfflineTtsVitsModelConfig,
 };
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{DiskExt, System, SystemExt};
 use tar::Archive;
-use tauri::{AppHandle, Emitter, Manager};
-use tokio::io::AsyncWriteExt;
+use tauri::{AppHandle, Emitter, Listener};
 
-const MELO_DIR: &str = "vits-melo-tts-zh_en";
-const MELO_SHA256: &str = "e58351ed7149f290a54534538badd4077cdbe6fddc964b24d0bee870415d1514";
-const MELO_URLS: &[&str] = &[
-    "https://release.yansu.app/sherpa/tts/vits-melo-tts-zh_en.tar.bz2",
-    "https://model-assets.yansu.app/sherpa/tts/vits-melo-tts-zh_en.tar.bz2",
-    "https://github.com/k2-fsa/sherpa-onnx/releases/download/tts-models/vits-melo-tts-zh_en.tar.bz2",
-];
+use crate::tts_audio::{encode_wav, AudioFileFormat};
+use crate::tts_catalog::{ModelFamily, ModelSpec};
+use crate::tts_markup::Span;
 
-const KOKORO_DIR: &str = "kokoro-en-v0_19";
-const KOKORO_SHA256: &str = "912804855a04745fa77a30be545b3f9a5d15c4d66db00b88cbcd4921df605ac7";
-const KOKORO_URLS: &[&str] = &[
-    "https://release.yansu.app/sherpa/tts/kokoro-en-v0_19.tar.bz2",
-    "https://model-assets.yansu.app/sherpa/tts/kokoro-en-v0_19.tar.bz2",
-    "https://github.com/k2-fsa/sherpa-onnx/releases/download/tts-models/kokoro-en-v0_19.tar.bz2",
-];
+/// Written next to the extracted files with their SHA-256, in `sha256sum`
+/// format, so installed models can be verified after the archive is gone.
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+/// Below the app cache directory.
+const CACHE_DIRECTORY: &str = "tts";
+/// In MiB, when `localTtsCacheSize` is not set.
+const DEFAULT_CACHE_SIZE: u64 = 100;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-enum ModelKind {
-    Chinese,
-    English,
-}
+/// In seconds, when `localTtsIdleUnload` is not set.
+const DEFAULT_IDLE_UNLOAD: u64 = 600;
+/// When `localTtsMaxEngines` is not set; enough for mixed Chinese–English.
+const DEFAULT_MAX_ENGINES: usize = 2;
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Beyond this many threads ONNX Runtime gains little on these small models
+/// while the rest of the machine loses a lot.
+const MAX_AUTO_THREADS: usize = 4;
+/// How long after startup the warm-up waits, so it doesn't compete with
+/// opening the windows.
+const WARM_UP_DELAY: Duration = Duration::from_secs(5);
 
-impl ModelKind {
-    fn id(self) -> &'static str {
-        match self {
-            Self::Chinese => "zh",
-            Self::English => "en",
-        }
-    }
+type SharedEngine = Arc<Mutex<OfflineTts>>;
+/// Samples and their rate.
+type Clip = (Vec<f32>, i32);
 
-    fn directory(self) -> &'static str {
-        match self {
-            Self::Chinese => MELO_DIR,
-            Self::English => KOKORO_DIR,
-        }
-    }
+/// How an engine runs, from the settings or detected from the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EngineSettings {
+    threads: usize,
+    max_sentences: usize,
+}
 
-    fn sha256(self) -> &'static str {
-        match self {
-            Self::Chinese => MELO_SHA256,
-            Self::English => KOKORO_SHA256,
-        }
-    }
+struct LoadedEngine {
+    engine: SharedEngine,
+    name: String,
+    settings: EngineSettings,
+    last_used: Instant,
+    /// Estimated from the model files, see `engine_memory`.
+    memory: u64,
+}
 
-    fn urls(self) -> &'static [&'static str] {
-        match self {
-            Self::Chinese => MELO_URLS,
-            Self::English => KOKORO_URLS,
-        }
+impl LoadedEngine {
+    /// Whether synthesis holds the engine right now.
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.engine) > 1
     }
 }
 
-type SharedEngine = Arc<Mutex<OfflineTts>>;
-
-static ENGINES: Lazy<Mutex<HashMap<ModelKind, SharedEngine>>> =
+/// Loaded engines by model id.
+static ENGINES: Lazy<Mutex<HashMap<String, LoadedEngine>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
+/// Whether the task unloading idle engines runs.
+static IDLE_WATCHER: AtomicBool = AtomicBool::new(false);
 static LOAD_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
 
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct TtsDownloadProgress {
-    lang: &'static str,
+    /// The model id; `zh` and `en` for the original two models.
+    lang: String,
+    name: String,
     downloaded: u64,
     total: u64,
     bytes_per_second: f64,
@@ -87,9 +92,184 @@ struct TtsDownloadProgress {
     message: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsModelInfo {
+    pub id: String,
+    pub name: String,
+    pub languages: Vec<String>,
+    pub installed: bool,
+    /// Whether the catalog pins a checksum, so the model can be downloaded.
+    pub downloadable: bool,
+    pub loaded: bool,
+    /// Bytes on disk, 0 when not installed.
+    pub size: f64,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsVoice {
+    /// `<model id>:<speaker id>`, as synthesis and the `localTtsVoices`
+    /// setting take it.
+    pub id: String,
+    pub model: String,
+    pub speaker: u32,
+    pub name: String,
+    pub gender: Option<String>,
+    pub accent: Option<String>,
+    /// The languages of the model.
+    pub languages: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsModelVerification {
+    /// Whether every file is present and, with checksums, unchanged.
+    pub ok: bool,
+    /// `false` for models downloaded before checksums were recorded; only the
+    /// required files are checked for those.
+    pub has_checksums: bool,
+    pub missing: Vec<String>,
+    pub mismatched: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsStorage {
+    pub directory: String,
+    /// Free bytes on the disk holding `directory`, `None` if unknown.
+    pub available: Option<f64>,
+    /// Bytes used by downloaded models.
+    pub used: f64,
+    /// Bytes used by cached speech.
+    pub cache: f64,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsLoadedEngine {
+    /// The model id.
+    pub id: String,
+    pub name: String,
+    /// Approximate bytes held, from the size of the model weights.
+    pub memory: f64,
+    /// Seconds since the engine was last used.
+    pub idle: f64,
+    /// Whether it is synthesizing right now.
+    pub in_use: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TtsExportOptions {
+    /// Taken from the extension of the path when not given.
+    pub format: Option<AudioFileFormat>,
+    /// The rate of the model when not given, or 48 kHz for Opus.
+    pub sample_rate: Option<u32>,
+    /// Writes every sentence to its own file, numbered after the path, and
+    /// lists them in a JSON manifest at the path with the extension `.json`.
+    pub split_sentences: bool,
+    pub speech: TtsSpeechOptions,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsExportFile {
+    /// Absolute in the command result, relative in the manifest.
+    pub path: String,
+    pub text: String,
+    pub duration_ms: u32,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsExport {
+    pub format: AudioFileFormat,
+    pub sample_rate: u32,
+    pub files: Vec<TtsExportFile>,
+    /// Path of the manifest when sentences were split.
+    pub manifest: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsSpeech {
+    /// Base64 WAV.
+    pub audio: String,
+    /// The sentences in the order they are read, for highlighting.
+    pub segments: Vec<TtsSpeechSegment>,
+}
+
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsSpeechSegment {
+    /// The sentence without markdown and markup.
+    pub text: String,
+    /// The stretches of the input the sentence is read from. Markdown and
+    /// markup inside the sentence fall between them.
+    pub ranges: Vec<TtsTextRange>,
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+/// Offsets in UTF-16 code units, as JavaScript indexes strings, `end`
+/// exclusive.
+#[derive(Clone, Debug, Serialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsTextRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TtsSpeechOptions {
+    /// Reads the text as SSML-lite: `<break>`, `<emphasis>` and `<say-as>`
+    /// for spelling; see `tts_markup`.
+    pub markup: bool,
+    /// From 0 to 1, 1 when not given.
+    pub volume: Option<f32>,
+    /// Shift in semitones, from -12 to 12.
+    pub pitch: Option<f32>,
+}
+
+impl TtsSpeechOptions {
+    fn spans(&self, text: &str) -> Vec<Span> {
+        if self.markup {
+            crate::tts_markup::parse(text)
+        } else {
+            vec![Span::plain(text)]
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        self.volume.unwrap_or(1.0).clamp(0.0, 1.0)
+    }
+
+    /// The pitch shift as a frequency factor.
+    fn pitch(&self) -> f32 {
+        2_f32.powf(self.pitch.unwrap_or(0.0).clamp(-12.0, 12.0) / 12.0)
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TtsStreamSegment {
+    id: String,
+    sequence: u32,
+    text: String,
+    audio: String,
+    sample_rate: i32,
+}
+
+#[derive(Deserialize)]
+struct TtsStreamAbort {
+    id: String,
+}
+
 fn emit_download_progress(
     app: &AppHandle,
-    kind: ModelKind,
+    spec: &ModelSpec,
     downloaded: u64,
     total: u64,
     bytes_per_second: f64,
@@ -99,7 +279,8 @@ fn emit_download_progress(
     let _ = app.emit(
         "tts-download-progress",
         TtsDownloadProgress {
-            lang: kind.id(),
+            lang: spec.id.clone(),
+            name: spec.name.clone(),
             downloaded,
             total,
             bytes_per_second,
@@ -109,20 +290,7 @@ fn emit_download_progress(
     );
 }
 
-fn model_for_text(text: &str, lang: &str) -> Result<ModelKind, String> {
-    if text.chars().any(is_han) {
-        return Ok(ModelKind::Chinese);
-    }
-    if lang == "en" {
-        return Ok(ModelKind::English);
-    }
-    if matches!(lang, "zh-Hans" | "zh-Hant" | "yue" | "lzh") {
-        return Ok(ModelKind::Chinese);
-    }
-    Err(format!("local TTS does not support language: {lang}"))
-}
-
-fn is_han(ch: char) -> bool {
+pub(crate) fn is_han(ch: char) -> bool {
     matches!(
         ch as u32,
         0x3400..=0x4DBF
@@ -133,234 +301,620 @@ fn is_han(ch: char) -> bool {
 }
 
 fn model_root(app: &AppHandle) -> Result<PathBuf, String> {
-    app.path()
-        .app_data_dir()
-        .map(|path| path.join("tts"))
-        .map_err(|err| format!("resolve app data directory: {err}"))
+    crate::paths::app_data_dir(app).map(|path| path.join("tts"))
+}
+
+fn catalog(app: &AppHandle) -> Result<Vec<ModelSpec>, String> {
+    crate::tts_catalog::load(&model_root(app)?)
+}
+
+fn find_model(app: &AppHandle, id: &str) -> Result<ModelSpec, String> {
+    catalog(app)?
+        .into_iter()
+        .find(|spec| spec.id == id)
+        .ok_or_else(|| format!("unknown TTS model: {id}"))
+}
+
+/// The lexicon of the model speaking Chinese, which for MeloTTS also covers
+/// English words; used by `pronunciation` for pinyin and IPA readings.
+pub(crate) fn chinese_lexicon_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let catalog = catalog(app)?;
+    let spec = crate::tts_catalog::route(&catalog, "", "zh-Hans")?;
+    let lexicon = spec
+        .files
+        .lexicon
+        .as_deref()
+        .and_then(|lexicon| lexicon.split(',').next())
+        .ok_or_else(|| format!("TTS model {} has no lexicon", spec.id))?;
+    Ok(model_root(app)?.join(&spec.directory).join(lexicon.trim()))
+}
+
+/// The English entry of the lexicon of the model that reads English, or
+/// `None` when that model has none, as Piper voices read through espeak-ng.
+pub(crate) fn english_lexicon_path(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    let catalog = catalog(app)?;
+    let Ok(spec) = crate::tts_catalog::route(&catalog, "", "en") else {
+        return Ok(None);
+    };
+    let lexicons = spec
+        .files
+        .lexicon
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    // Multilingual models keep one lexicon per language (`lexicon-us-en.txt`).
+    let lexicon = match lexicons.as_slice() {
+        [only] => Some(*only),
+        _ => lexicons.iter().copied().find(|name| name.contains("en")),
+    };
+    lexicon
+        .map(|lexicon| Ok(model_root(app)?.join(&spec.directory).join(lexicon)))
+        .transpose()
 }
 
-fn file_nonempty(path: impl AsRef<Path>) -> bool {
+pub(crate) fn file_nonempty(path: impl AsRef<Path>) -> bool {
     std::fs::metadata(path)
         .map(|metadata| metadata.is_file() && metadata.len() > 0)
         .unwrap_or(false)
 }
 
-fn model_present(kind: ModelKind, root: &Path) -> bool {
-    let directory = root.join(kind.directory());
-    match kind {
-        ModelKind::Chinese => {
-            file_nonempty(directory.join("model.onnx"))
-                && file_nonempty(directory.join("lexicon.txt"))
-                && file_nonempty(directory.join("tokens.txt"))
+fn model_present(spec: &ModelSpec, root: &Path) -> bool {
+    let directory = root.join(&spec.directory);
+    spec.required_files
+        .iter()
+        .all(|name| file_nonempty(directory.join(name)))
+}
+
+/// Files below `directory` as `/`-separated relative paths, sorted.
+pub(crate) fn model_files(directory: &Path) -> Vec<String> {
+    fn walk(directory: &Path, prefix: &str, files: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(directory) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => {
+                    walk(&entry.path(), &format!("{name}/"), files)
+                }
+                Ok(_) => files.push(name),
+                Err(_) => {}
+            }
         }
-        ModelKind::English => {
-            file_nonempty(directory.join("model.onnx"))
-                && file_nonempty(directory.join("voices.bin"))
-                && file_nonempty(directory.join("tokens.txt"))
+    }
+    let mut files = Vec::new();
+    walk(directory, "", &mut files);
+    files.sort();
+    files
+}
+
+pub(crate) fn directory_size(directory: &Path) -> u64 {
+    model_files(directory)
+        .iter()
+        .filter_map(|name| std::fs::metadata(directory.join(name)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|err| format!("open {}: {err}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|err| format!("read {}: {err}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub(crate) fn write_checksums(directory: &Path) -> Result<(), String> {
+    let mut checksums = String::new();
+    for name in model_files(directory) {
+        if name != CHECKSUMS_FILE {
+            checksums.push_str(&format!("{}  {name}\n", hash_file(&directory.join(&name))?));
+        }
+    }
+    std::fs::write(directory.join(CHECKSUMS_FILE), checksums)
+        .map_err(|err| format!("write model checksums: {err}"))
+}
+
+fn verify_files(directory: &Path, required_files: &[impl AsRef<str>]) -> TtsModelVerification {
+    let mut verification = TtsModelVerification::default();
+    if let Ok(file) = File::open(directory.join(CHECKSUMS_FILE)) {
+        verification.has_checksums = true;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Some((expected, name)) = line.split_once("  ") else {
+                continue;
+            };
+            let path = directory.join(name);
+            if !path.is_file() {
+                verification.missing.push(name.to_string());
+            } else if hash_file(&path).ok().as_deref() != Some(expected) {
+                verification.mismatched.push(name.to_string());
+            }
+        }
+    }
+    for name in required_files.iter().map(AsRef::as_ref) {
+        if !file_nonempty(directory.join(name)) && !verification.missing.iter().any(|m| m == name) {
+            verification.missing.push(name.to_string());
         }
     }
+    verification.ok = verification.missing.is_empty() && verification.mismatched.is_empty();
+    verification
 }
 
-async fn download_model(app: &AppHandle, kind: ModelKind, root: &Path) -> Result<(), String> {
+/// Free space on the disk with the longest mount point containing `path`.
+fn available_space(path: &Path) -> Option<u64> {
+    let mut system = System::new();
+    system.refresh_disks_list();
+    system
+        .disks()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+pub(crate) fn extract_archive(archive: &Path, destination: &Path) -> Result<(), String> {
+    let file = File::open(archive).map_err(|err| format!("open model archive: {err}"))?;
+    let mut tar = Archive::new(BzDecoder::new(file));
+    for entry in tar
+        .entries()
+        .map_err(|err| format!("read model archive: {err}"))?
+    {
+        entry
+            .map_err(|err| format!("read model archive entry: {err}"))?
+            .unpack_in(destination)
+            .map_err(|err| format!("extract model archive: {err}"))?;
+    }
+    Ok(())
+}
+
+fn copy_directory(from: &Path, to: &Path) -> Result<(), String> {
+    for name in model_files(from) {
+        let target = to.join(&name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("create {}: {err}", parent.display()))?;
+        }
+        std::fs::copy(from.join(&name), &target).map_err(|err| format!("copy {name}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Unpacks or copies `source` into `staging` and returns the model directory
+/// inside it with its name: the single top-level directory of an archive, or
+/// the files themselves when an archive has none.
+pub(crate) fn stage_import(source: &Path, staging: &Path) -> Result<(PathBuf, String), String> {
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("invalid model path: {}", source.display()))?;
+    if source.is_dir() {
+        let directory = staging.join(&file_name);
+        copy_directory(source, &directory)?;
+        return Ok((directory, file_name));
+    }
+    let Some(stem) = file_name
+        .strip_suffix(".tar.bz2")
+        .or_else(|| file_name.strip_suffix(".tbz2"))
+    else {
+        return Err("expected a .tar.bz2 archive or a model directory".to_string());
+    };
+    extract_archive(source, staging)?;
+    let entries = std::fs::read_dir(staging)
+        .map_err(|err| format!("read extracted model: {err}"))?
+        .flatten()
+        .collect::<Vec<_>>();
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok((
+            entry.path(),
+            entry.file_name().to_string_lossy().into_owned(),
+        )),
+        _ => Ok((staging.to_path_buf(), stem.to_string())),
+    }
+}
+
+fn download_key(id: &str) -> String {
+    format!("tts:{id}")
+}
+
+async fn download_model(app: &AppHandle, spec: &ModelSpec, root: &Path) -> Result<(), String> {
     tokio::fs::create_dir_all(root)
         .await
         .map_err(|err| format!("create TTS model directory: {err}"))?;
 
-    let archive_path = root.join(format!("{}.tar.bz2.part", kind.directory()));
-    let client = reqwest::Client::builder()
-        .build()
-        .map_err(|err| format!("create TTS download client: {err}"))?;
-    let mut last_error = String::from("no model mirror was available");
-
-    for url in kind.urls() {
-        let result = async {
-            let response = client
-                .get(*url)
-                .send()
-                .await
-                .map_err(|err| format!("request failed: {err}"))?
-                .error_for_status()
-                .map_err(|err| format!("server returned an error: {err}"))?;
-            let total = response.content_length().unwrap_or(0);
-            let mut downloaded = 0_u64;
-            let mut last_reported = 0_u64;
-            let mut last_report_at = Instant::now();
-            let mut hasher = Sha256::new();
-            let mut output = tokio::fs::File::create(&archive_path)
-                .await
-                .map_err(|err| format!("create model archive: {err}"))?;
-            let mut stream = response.bytes_stream();
-
-            emit_download_progress(app, kind, 0, total, 0.0, "downloading", None);
-
-            while let Some(chunk) = stream.next().await {
-                let chunk = chunk.map_err(|err| format!("download model archive: {err}"))?;
-                output
-                    .write_all(&chunk)
-                    .await
-                    .map_err(|err| format!("write model archive: {err}"))?;
-                hasher.update(&chunk);
-                downloaded += chunk.len() as u64;
-                let elapsed = last_report_at.elapsed();
-                if elapsed.as_millis() >= 200 || (total > 0 && downloaded >= total) {
-                    let speed =
-                        (downloaded - last_reported) as f64 / elapsed.as_secs_f64().max(0.001);
-                    emit_download_progress(
-                        app,
-                        kind,
-                        downloaded,
-                        total,
-                        speed,
-                        "downloading",
-                        None,
-                    );
-                    last_reported = downloaded;
-                    last_report_at = Instant::now();
-                }
-            }
-            if downloaded != last_reported {
-                let elapsed = last_report_at.elapsed();
-                let speed = (downloaded - last_reported) as f64 / elapsed.as_secs_f64().max(0.001);
-                emit_download_progress(app, kind, downloaded, total, speed, "downloading", None);
-            }
-            output
-                .flush()
-                .await
-                .map_err(|err| format!("flush model archive: {err}"))?;
-
-            let actual_sha = format!("{:x}", hasher.finalize());
-            if actual_sha != kind.sha256() {
-                return Err(format!(
-                    "model archive checksum mismatch: expected {}, got {actual_sha}",
-                    kind.sha256()
-                ));
-            }
-
-            emit_download_progress(app, kind, downloaded, total, 0.0, "extracting", None);
+    let archive_path = root.join(format!("{}.tar.bz2.part", spec.directory));
+    let mut downloaded = 0_u64;
+    let mut total = 0_u64;
+    let result = async {
+        if !spec.downloadable() {
+            return Err(
+                "no archive checksum is pinned in the catalog; install it by hand".to_string(),
+            );
+        }
+        let urls = spec.urls.iter().map(String::as_str).collect::<Vec<_>>();
+        crate::download::download_verified(
+            &download_key(&spec.id),
+            &urls,
+            &spec.sha256,
+            &archive_path,
+            |done, size, speed, phase| {
+                downloaded = done;
+                total = size;
+                emit_download_progress(app, spec, done, size, speed, phase, None);
+            },
+        )
+        .await?;
 
-            let archive = archive_path.clone();
-            let destination = root.to_path_buf();
-            let model_directory = root.join(kind.directory());
-            tokio::task::spawn_blocking(move || {
-                if model_directory.exists() {
-                    std::fs::remove_dir_all(&model_directory)
-                        .map_err(|err| format!("replace old TTS model: {err}"))?;
-                }
-                let file =
-                    File::open(&archive).map_err(|err| format!("open model archive: {err}"))?;
-                let mut tar = Archive::new(BzDecoder::new(file));
-                for entry in tar
-                    .entries()
-                    .map_err(|err| format!("read model archive: {err}"))?
-                {
-                    entry
-                        .map_err(|err| format!("read model archive entry: {err}"))?
-                        .unpack_in(&destination)
-                        .map_err(|err| format!("extract model archive: {err}"))?;
-                }
-                Ok::<(), String>(())
-            })
-            .await
-            .map_err(|err| format!("extract model task failed: {err}"))??;
+        emit_download_progress(app, spec, downloaded, total, 0.0, "extracting", None);
 
-            if !model_present(kind, root) {
-                return Err("model files are missing after extraction".to_string());
+        let archive = archive_path.clone();
+        let destination = root.to_path_buf();
+        let model_directory = root.join(&spec.directory);
+        let extracted = tokio::task::spawn_blocking(move || {
+            if model_directory.exists() {
+                std::fs::remove_dir_all(&model_directory)
+                    .map_err(|err| format!("replace old TTS model: {err}"))?;
             }
-            emit_download_progress(app, kind, downloaded, total, 0.0, "ready", None);
-            Ok::<(), String>(())
-        }
-        .await;
+            extract_archive(&archive, &destination)?;
+            write_checksums(&model_directory)
+        })
+        .await
+        .map_err(|err| format!("extract model task failed: {err}"))
+        .and_then(|result| result);
+        // Only a partial download is worth keeping, to resume it.
+        let _ = tokio::fs::remove_file(&archive_path).await;
+        extracted?;
 
-        if result.is_ok() {
-            let _ = tokio::fs::remove_file(&archive_path).await;
-            return Ok(());
+        if !model_present(spec, root) {
+            return Err("model files are missing after extraction".to_string());
         }
-        last_error = format!("{url}: {}", result.unwrap_err());
-        let _ = tokio::fs::remove_file(&archive_path).await;
+        Ok::<(), String>(())
     }
+    .await;
 
-    let error = format!("download {} TTS model: {last_error}", kind.id());
-    emit_download_progress(app, kind, 0, 0, 0.0, "error", Some(error.clone()));
-    Err(error)
+    match result {
+        Ok(()) => {
+            emit_download_progress(app, spec, downloaded, total, 0.0, "ready", None);
+            Ok(())
+        }
+        Err(err) if err == crate::download::CANCELLED => {
+            emit_download_progress(app, spec, 0, 0, 0.0, "cancelled", None);
+            Err(err)
+        }
+        Err(err) => {
+            let error = format!("download {} TTS model: {err}", spec.id);
+            emit_download_progress(app, spec, 0, 0, 0.0, "error", Some(error.clone()));
+            Err(error)
+        }
+    }
 }
 
-fn path_string(path: PathBuf) -> String {
+pub(crate) fn path_string(path: PathBuf) -> String {
     path.to_string_lossy().into_owned()
 }
 
-fn create_engine(kind: ModelKind, root: &Path) -> Result<OfflineTts, String> {
-    let directory = root.join(kind.directory());
-    let model = match kind {
-        ModelKind::Chinese => {
-            let rule_fsts = ["phone.fst", "date.fst", "number.fst"]
-                .iter()
-                .map(|name| directory.join(name))
-                .filter(|path| file_nonempty(path))
-                .map(path_string)
+/// Physical cores, falling back to logical ones where sysinfo can't tell.
+static CORES: Lazy<usize> = Lazy::new(|| {
+    System::new()
+        .physical_core_count()
+        .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+        .max(1)
+});
+
+/// Threads and sentence batching for `cores` physical cores: half of them,
+/// between 2 and `MAX_AUTO_THREADS`, leaving the rest to the app; on one or
+/// two cores, all of them. Batching sentences only pays off with threads to
+/// spread them over.
+fn auto_engine_settings(cores: usize) -> EngineSettings {
+    let threads = if cores <= 2 {
+        cores.max(1)
+    } else {
+        (cores / 2).clamp(2, MAX_AUTO_THREADS)
+    };
+    EngineSettings {
+        threads,
+        max_sentences: if threads > 1 { 2 } else { 1 },
+    }
+}
+
+/// The engine settings, where 0 or unset means detected from the CPU.
+fn engine_settings() -> EngineSettings {
+    let auto = auto_engine_settings(*CORES);
+    let config = crate::config::get_config().ok();
+    let configured = |value: Option<u32>, auto: usize| {
+        value
+            .filter(|value| *value > 0)
+            .map_or(auto, |value| value as usize)
+    };
+    EngineSettings {
+        threads: configured(
+            config.as_ref().and_then(|config| config.local_tts_threads),
+            auto.threads,
+        ),
+        max_sentences: configured(
+            config
+                .as_ref()
+                .and_then(|config| config.local_tts_max_sentences),
+            auto.max_sentences,
+        ),
+    }
+}
+
+/// Threads for an ONNX Runtime session, as the TTS engines use; speech
+/// recognition follows the same setting.
+pub(crate) fn engine_threads() -> usize {
+    engine_settings().threads
+}
+
+fn create_engine(
+    spec: &ModelSpec,
+    root: &Path,
+    settings: EngineSettings,
+) -> Result<OfflineTts, String> {
+    let directory = root.join(&spec.directory);
+    let file = |name: &Option<String>| {
+        name.as_ref().map(|name| {
+            name.split(',')
+                .map(|name| path_string(directory.join(name.trim())))
                 .collect::<Vec<_>>()
-                .join(",");
-            OfflineTtsConfig {
-                model: OfflineTtsModelConfig {
-                    vits: OfflineTtsVitsModelConfig {
-                        model: Some(path_string(directory.join("model.onnx"))),
-                        lexicon: Some(path_string(directory.join("lexicon.txt"))),
-                        tokens: Some(path_string(directory.join("tokens.txt"))),
-                        noise_scale: 0.667,
-                        noise_scale_w: 0.8,
-                        length_scale: 1.0,
-                        ..Default::default()
-                    },
-                    num_threads: 2,
-                    ..Default::default()
-                },
-                rule_fsts: (!rule_fsts.is_empty()).then_some(rule_fsts),
-                max_num_sentences: 2,
+                .join(",")
+        })
+    };
+    let files = &spec.files;
+    let mut model = OfflineTtsModelConfig {
+        num_threads: settings.threads as i32,
+        ..Default::default()
+    };
+    match spec.family {
+        ModelFamily::Vits | ModelFamily::Piper => {
+            model.vits = OfflineTtsVitsModelConfig {
+                model: file(&files.model),
+                lexicon: file(&files.lexicon),
+                tokens: file(&files.tokens),
+                data_dir: file(&files.data_dir),
+                dict_dir: file(&files.dict_dir),
                 ..Default::default()
             }
         }
-        ModelKind::English => OfflineTtsConfig {
-            model: OfflineTtsModelConfig {
-                kokoro: OfflineTtsKokoroModelConfig {
-                    model: Some(path_string(directory.join("model.onnx"))),
-                    voices: Some(path_string(directory.join("voices.bin"))),
-                    tokens: Some(path_string(directory.join("tokens.txt"))),
-                    data_dir: Some(path_string(directory.join("espeak-ng-data"))),
-                    length_scale: 1.0,
-                    ..Default::default()
-                },
-                num_threads: 2,
+        ModelFamily::Kokoro => {
+            model.kokoro = OfflineTtsKokoroModelConfig {
+                model: file(&files.model),
+                voices: file(&files.voices),
+                tokens: file(&files.tokens),
+                data_dir: file(&files.data_dir),
+                dict_dir: file(&files.dict_dir),
+                lexicon: file(&files.lexicon),
+                lang: spec.lang.clone(),
                 ..Default::default()
-            },
-            max_num_sentences: 2,
-            ..Default::default()
-        },
+            }
+        }
+        ModelFamily::Matcha => {
+            model.matcha = OfflineTtsMatchaModelConfig {
+                acoustic_model: file(&files.model),
+                vocoder: file(&files.vocoder),
+                lexicon: file(&files.lexicon),
+                tokens: file(&files.tokens),
+                data_dir: file(&files.data_dir),
+                dict_dir: file(&files.dict_dir),
+                ..Default::default()
+            }
+        }
+    }
+    let rule_fsts = files
+        .rule_fsts
+        .iter()
+        .map(|name| directory.join(name))
+        .filter(|path| file_nonempty(path))
+        .map(path_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let config = OfflineTtsConfig {
+        model,
+        rule_fsts: (!rule_fsts.is_empty()).then_some(rule_fsts),
+        max_num_sentences: settings.max_sentences as i32,
+        ..Default::default()
     };
 
-    OfflineTts::create(&model).ok_or_else(|| format!("failed to load {} TTS model", kind.id()))
+    OfflineTts::create(&config).ok_or_else(|| format!("failed to load {} TTS model", spec.id))
 }
 
-async fn ensure_engine(app: &AppHandle, kind: ModelKind) -> Result<SharedEngine, String> {
-    if let Some(engine) = ENGINES.lock().get(&kind).cloned() {
-        return Ok(engine);
+/// The memory an engine holds, roughly: ONNX Runtime keeps the weights in
+/// memory, and they dwarf everything else.
+fn engine_memory(spec: &ModelSpec, root: &Path) -> u64 {
+    let directory = root.join(&spec.directory);
+    [&spec.files.model, &spec.files.vocoder, &spec.files.voices]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| std::fs::metadata(directory.join(name)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// The idle timeout and the number of engines to keep loaded, from the
+/// settings; a timeout of 0 keeps engines until the limit pushes them out.
+pub(crate) fn engine_limits() -> (Option<Duration>, usize) {
+    let config = crate::config::get_config().ok();
+    let idle = config
+        .as_ref()
+        .and_then(|config| config.local_tts_idle_unload)
+        .unwrap_or(DEFAULT_IDLE_UNLOAD);
+    let max_engines = config
+        .as_ref()
+        .and_then(|config| config.local_tts_max_engines)
+        .map_or(DEFAULT_MAX_ENGINES, |max| max.max(1) as usize);
+    ((idle > 0).then(|| Duration::from_secs(idle)), max_engines)
+}
+
+/// Of `engines` as `(id, idle time, in use)`, those idle for longer than
+/// the timeout and, over the limit, the least recently used ones. Engines in
+/// use are left for a later pass.
+fn engines_to_unload(
+    engines: &[(String, Duration, bool)],
+    idle_timeout: Option<Duration>,
+    max_engines: usize,
+) -> Vec<String> {
+    let mut unused = engines
+        .iter()
+        .filter(|(_, _, in_use)| !in_use)
+        .collect::<Vec<_>>();
+    unused.sort_by_key(|(_, idle, _)| std::cmp::Reverse(*idle));
+    let idle = unused
+        .iter()
+        .take_while(|(_, idle, _)| idle_timeout.is_some_and(|timeout| *idle >= timeout))
+        .count();
+    let excess = engines.len().saturating_sub(max_engines);
+    unused
+        .into_iter()
+        .take(idle.max(excess))
+        .map(|(id, ..)| id.clone())
+        .collect()
+}
+
+fn trim_engines() {
+    let (idle_timeout, max_engines) = engine_limits();
+    let mut engines = ENGINES.lock();
+    let state = engines
+        .iter()
+        .map(|(id, loaded)| (id.clone(), loaded.last_used.elapsed(), loaded.in_use()))
+        .collect::<Vec<_>>();
+    for id in engines_to_unload(&state, idle_timeout, max_engines) {
+        engines.remove(&id);
     }
+}
 
-    let _load_guard = LOAD_LOCK.lock().await;
-    if let Some(engine) = ENGINES.lock().get(&kind).cloned() {
+/// Starts the task that unloads idle engines; it stops once none are left.
+fn watch_idle_engines() {
+    if IDLE_WATCHER.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            trim_engines();
+            // Checked under the lock `ensure_engine` inserts with, so an
+            // engine loaded meanwhile starts a new watcher.
+            let engines = ENGINES.lock();
+            if engines.is_empty() {
+                IDLE_WATCHER.store(false, Ordering::SeqCst);
+                break;
+            }
+        }
+    });
+}
+
+/// The engine loaded for model `id`. One loaded with other settings is
+/// dropped so that it's reloaded with the current ones; synthesis already
+/// holding it finishes first.
+fn loaded_engine(id: &str, settings: EngineSettings) -> Option<SharedEngine> {
+    let mut engines = ENGINES.lock();
+    if engines
+        .get(id)
+        .is_some_and(|loaded| loaded.settings != settings)
+    {
+        engines.remove(id);
+        return None;
+    }
+    let loaded = engines.get_mut(id)?;
+    loaded.last_used = Instant::now();
+    Some(loaded.engine.clone())
+}
+
+async fn ensure_engine(app: &AppHandle, spec: &ModelSpec) -> Result<SharedEngine, String> {
+    let settings = engine_settings();
+    if let Some(engine) = loaded_engine(&spec.id, settings) {
         return Ok(engine);
     }
 
     let root = model_root(app)?;
-    if !model_present(kind, &root) {
-        download_model(app, kind, &root).await?;
+    if !model_present(spec, &root) {
+        let _download_guard = crate::download::lock(&download_key(&spec.id)).await;
+        if !model_present(spec, &root) {
+            download_model(app, spec, &root).await?;
+        }
+    }
+
+    let _load_guard = LOAD_LOCK.lock().await;
+    if let Some(engine) = loaded_engine(&spec.id, settings) {
+        return Ok(engine);
     }
-    let engine = tokio::task::spawn_blocking(move || create_engine(kind, &root))
+    // Deleted while it was downloading.
+    if !model_present(spec, &root) {
+        return Err(format!("TTS model {} is not installed", spec.id));
+    }
+    let memory = engine_memory(spec, &root);
+    let owned = spec.clone();
+    let engine = tokio::task::spawn_blocking(move || create_engine(&owned, &root, settings))
         .await
         .map_err(|err| format!("load TTS model task failed: {err}"))??;
     let engine = Arc::new(Mutex::new(engine));
-    ENGINES.lock().insert(kind, engine.clone());
+    ENGINES.lock().insert(
+        spec.id.clone(),
+        LoadedEngine {
+            engine: engine.clone(),
+            name: spec.name.clone(),
+            settings,
+            last_used: Instant::now(),
+            memory,
+        },
+    );
+    trim_engines();
+    watch_idle_engines();
     Ok(engine)
 }
 
+/// With `localTtsWarmUp` on, loads the installed model for the default
+/// target language in the background and has it speak a word, so the first
+/// request doesn't wait for the model or ONNX Runtime's first run. Nothing is
+/// downloaded, and the engine unloads when idle like any other.
+pub fn warm_up(app: &AppHandle) {
+    let Ok(config) = crate::config::get_config() else {
+        return;
+    };
+    if config.local_tts_warm_up != Some(true) {
+        return;
+    }
+    let lang = config
+        .default_target_language
+        .unwrap_or_else(|| "en".to_string());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WARM_UP_DELAY).await;
+        if let Err(err) = warm_up_model(&app, &lang).await {
+            debug_println!("TTS warm-up failed: {err}");
+        }
+    });
+}
+
+async fn warm_up_model(app: &AppHandle, lang: &str) -> Result<(), String> {
+    let catalog = catalog(app)?;
+    let spec = crate::tts_catalog::route(&catalog, "", lang)?;
+    if !model_present(spec, &model_root(app)?) {
+        return Ok(());
+    }
+    let engine = ensure_engine(app, spec).await?;
+    let speaker = speaker_for(spec, None, lang);
+    let text = if lang.starts_with("zh") {
+        "你好。"
+    } else {
+        "Hello."
+    };
+    tokio::task::spawn_blocking(move || {
+        synthesize_samples(
+            &engine.lock(),
+            text,
+            1.0,
+            speaker,
+            None::<fn(&[f32], f32) -> bool>,
+        )
+    })
+    .await
+    .map_err(|err| format!("TTS warm-up task failed: {err}"))??;
+    Ok(())
+}
+
 fn normalize_peak(samples: &mut [f32], target: f32) {
     let peak = samples
         .iter()
@@ -377,8 +931,24 @@ fn normalize_peak(samples: &mut [f32], target: f32) {
     }
 }
 
+/// Scales `samples` down when they peak above `limit`, so that louder
+/// sentences and crossfades don't clip. Quieter audio is left alone, which
+/// keeps emphasis and volume.
+fn limit_peak(samples: &mut [f32], limit: f32) {
+    let peak = samples
+        .iter()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    if peak > limit {
+        apply_gain(samples, limit / peak);
+    }
+}
+
+/// Length of the fades at both ends of every clip, which is also how much
+/// clips overlap when `stitch` joins them.
+const FADE_MS: usize = 12;
+
 fn apply_edge_fades(samples: &mut [f32], sample_rate: i32) {
-    let mut fade = (sample_rate.max(0) as usize * 12) / 1000;
+    let mut fade = (sample_rate.max(0) as usize * FADE_MS) / 1000;
     fade = fade.min(samples.len() / 2);
     for index in 0..fade {
         let gain = index as f32 / fade as f32;
@@ -387,28 +957,670 @@ fn apply_edge_fades(samples: &mut [f32], sample_rate: i32) {
     }
 }
 
-fn encode_wav(samples: &[f32], sample_rate: i32) -> Vec<u8> {
-    let data_len = samples.len() * 2;
-    let mut output = Vec::with_capacity(44 + data_len);
-    output.extend_from_slice(b"RIFF");
-    output.extend_from_slice(&(36_u32 + data_len as u32).to_le_bytes());
-    output.extend_from_slice(b"WAVEfmt ");
-    output.extend_from_slice(&16_u32.to_le_bytes());
-    output.extend_from_slice(&1_u16.to_le_bytes());
-    output.extend_from_slice(&1_u16.to_le_bytes());
-    output.extend_from_slice(&(sample_rate as u32).to_le_bytes());
-    output.extend_from_slice(&((sample_rate as u32) * 2).to_le_bytes());
-    output.extend_from_slice(&2_u16.to_le_bytes());
-    output.extend_from_slice(&16_u16.to_le_bytes());
-    output.extend_from_slice(b"data");
-    output.extend_from_slice(&(data_len as u32).to_le_bytes());
-    for sample in samples {
-        let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
-        output.extend_from_slice(&pcm.to_le_bytes());
+/// Joins clips, possibly from models with different sample rates, into one
+/// at the highest rate, and tells where each clip ended up in it. Neighbours
+/// overlap by their faded edges, which makes a short crossfade at every seam.
+/// Clips come normalized from the model, so the result is only limited.
+fn stitch(clips: Vec<Clip>) -> Result<(Clip, Vec<Range<usize>>), String> {
+    let sample_rate = clips
+        .iter()
+        .map(|(_, rate)| *rate)
+        .max()
+        .ok_or_else(|| "TTS produced no audio".to_string())?;
+    let overlap = (sample_rate.max(0) as usize * FADE_MS) / 1000;
+    let mut output: Vec<f32> = Vec::new();
+    let mut placed = Vec::new();
+    for (samples, rate) in clips {
+        let samples = crate::tts_audio::resample(&samples, rate, sample_rate)?;
+        let overlap = overlap.min(output.len()).min(samples.len() / 2);
+        let start = output.len() - overlap;
+        for (sample, next) in output[start..].iter_mut().zip(&samples) {
+            *sample += next;
+        }
+        output.extend_from_slice(&samples[overlap..]);
+        placed.push(start..output.len());
+    }
+    if output.is_empty() {
+        return Err("TTS produced no audio".to_string());
     }
-    output
+    limit_peak(&mut output, 0.95);
+    Ok(((output, sample_rate), placed))
+}
+
+fn apply_gain(samples: &mut [f32], gain: f32) {
+    if gain != 1.0 {
+        for sample in samples {
+            *sample *= gain;
+        }
+    }
+}
+
+/// Raises the pitch of a clip the model read `factor` times slower, by
+/// playing it back `factor` times faster, which also restores its length.
+/// Formants move along, which is fine for a few semitones.
+fn shift_pitch(samples: &[f32], sample_rate: i32, factor: f32) -> Result<Vec<f32>, String> {
+    if (factor - 1.0).abs() < 0.001 {
+        return Ok(samples.to_vec());
+    }
+    // Rounded to 100 Hz, which keeps the resampler's FFTs short.
+    let from = ((sample_rate as f32 * factor / 100.0).round() as i32).max(1) * 100;
+    crate::tts_audio::resample(samples, from, sample_rate)
+}
+
+/// `ms` of silence in front of `clip`.
+fn with_pause((samples, sample_rate): (Vec<f32>, i32), ms: u32) -> (Vec<f32>, i32) {
+    if ms == 0 {
+        return (samples, sample_rate);
+    }
+    let silence = (sample_rate.max(0) as u64 * u64::from(ms) / 1000) as usize;
+    let mut padded = vec![0.0; silence];
+    padded.extend(samples);
+    (padded, sample_rate)
+}
+
+/// Splits after sentence punctuation and line breaks, like the frontend's
+/// `splitSpeechText`, keeping decimal points such as `3.14` inside.
+/// Sentences longer than this are split further, by `split_long`.
+const MAX_SENTENCE_CHARS: usize = 200;
+const MAX_HAN_SENTENCE_CHARS: usize = 60;
+const SENTENCE_ENDS: [char; 8] = ['。', '！', '？', '.', '!', '?', '；', ';'];
+
+fn split_sentences(text: &str) -> Vec<&str> {
+    let chars = text.char_indices().collect::<Vec<_>>();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (index, (offset, ch)) in chars.iter().enumerate() {
+        let decimal_point = *ch == '.'
+            && index > 0
+            && chars[index - 1].1.is_ascii_digit()
+            && chars
+                .get(index + 1)
+                .is_some_and(|(_, next)| next.is_ascii_digit());
+        if !decimal_point && (SENTENCE_ENDS.contains(ch) || *ch == '\n') {
+            let end = offset + ch.len_utf8();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+        .into_iter()
+        .flat_map(|sentence| {
+            let max_chars = if sentence.chars().any(is_han) {
+                MAX_HAN_SENTENCE_CHARS
+            } else {
+                MAX_SENTENCE_CHARS
+            };
+            crate::tts_normalize::split_long(sentence, max_chars)
+        })
+        .collect()
+}
+
+/// Splits `spans` at sentence ends, as `split_sentences` splits plain text,
+/// for one file or highlight per sentence. Every sentence comes with its
+/// text; those of only symbols are dropped.
+fn sentence_spans(spans: Vec<Span>) -> Vec<(String, Vec<Span>)> {
+    let mut sentences = Vec::new();
+    let mut current = Vec::new();
+    let mut pause_ms = 0;
+    for span in spans {
+        pause_ms += span.pause_ms;
+        let text = crate::tts_normalize::strip_markdown(&span.text);
+        let pieces = split_sentences(&text);
+        let last = pieces.len().saturating_sub(1);
+        for (index, piece) in pieces.into_iter().enumerate() {
+            current.push(Span {
+                text: piece.to_string(),
+                emphasis: span.emphasis,
+                pause_ms: std::mem::take(&mut pause_ms),
+            });
+            if index < last || piece.ends_with(SENTENCE_ENDS) {
+                sentences.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    sentences.push(current);
+    sentences
+        .into_iter()
+        .filter(|spans| {
+            spans
+                .iter()
+                .any(|span| span.text.chars().any(char::is_alphanumeric))
+        })
+        .map(|spans| {
+            let mut text = String::new();
+            for span in &spans {
+                // Pieces come trimmed; put back the space between words.
+                let spaced = text.ends_with(|ch: char| !is_han(ch))
+                    && span
+                        .text
+                        .starts_with(|ch: char| !is_han(ch) && !ch.is_ascii_punctuation());
+                if spaced {
+                    text.push(' ');
+                }
+                text.push_str(&span.text);
+            }
+            (text, spans)
+        })
+        .collect()
 }
 
+/// Where each of `sentences`, cut from `input` by `sentence_spans`, is read
+/// from in `input`, in UTF-16 code units. Stripping markdown and markup only
+/// drops characters, so the words of the sentences are found in order,
+/// skipping the characters `markup_chars` flags. A sentence is cut into
+/// several ranges wherever something between two of its words is not read,
+/// e.g. `**` or the target of a link.
+fn source_ranges(input: &str, markup_chars: &[bool], sentences: &[&str]) -> Vec<Vec<Range<usize>>> {
+    let mut offset = 0;
+    let chars = input
+        .chars()
+        .enumerate()
+        .map(|(index, ch)| {
+            let start = offset;
+            offset += ch.len_utf16();
+            (start, ch, markup_chars.get(index).copied().unwrap_or(false))
+        })
+        .collect::<Vec<_>>();
+    let same = |a: char, b: char| a == b || Iterator::eq(a.to_lowercase(), b.to_lowercase());
+    let is_word_at = |at: usize, word: &[char]| {
+        at + word.len() <= chars.len()
+            && (at == 0 || !chars[at - 1].1.is_alphanumeric())
+            && chars
+                .get(at + word.len())
+                .map_or(true, |(_, ch, _)| !ch.is_alphanumeric())
+            && word
+                .iter()
+                .zip(&chars[at..])
+                .all(|(ch, (_, candidate, markup))| !markup && same(*candidate, *ch))
+    };
+    let mut cursor = 0;
+    let mut ranges = Vec::new();
+    for sentence in sentences {
+        let spoken = sentence.chars().collect::<Vec<_>>();
+        // Positions in `spoken` and in `chars` of the letters and digits.
+        let mut matched = Vec::<(usize, usize)>::new();
+        let mut position = 0;
+        while position < spoken.len() {
+            if !spoken[position].is_alphanumeric() {
+                position += 1;
+                continue;
+            }
+            let end = (position..spoken.len())
+                .find(|&index| !spoken[index].is_alphanumeric())
+                .unwrap_or(spoken.len());
+            let word = &spoken[position..end];
+            // Letter by letter, as markdown may split a word (`**你好**世界`).
+            // The whole word wins when it starts before that match ends,
+            // which keeps the letters of a link target out.
+            let mut letters = Vec::new();
+            let mut next = cursor;
+            for (index, ch) in word.iter().enumerate() {
+                let found = (next..chars.len()).find(|&candidate| {
+                    let (_, candidate, markup) = chars[candidate];
+                    !markup && same(candidate, *ch)
+                });
+                if let Some(found) = found {
+                    letters.push((position + index, found));
+                    next = found + 1;
+                }
+            }
+            let limit = letters.last().map_or(cursor, |(_, index)| *index);
+            match (cursor..=limit).find(|&at| is_word_at(at, word)) {
+                Some(at) => matched.extend((position..end).zip(at..at + word.len())),
+                None => matched.extend(letters),
+            }
+            if let Some((_, index)) = matched.last() {
+                cursor = index + 1;
+            }
+            position = end;
+        }
+        let Some(&(_, first)) = matched.first() else {
+            ranges.push(Vec::new());
+            continue;
+        };
+        let mut pieces = Vec::new();
+        let mut piece_start = first;
+        for pair in matched.windows(2) {
+            let ((spoken_from, from), (spoken_to, to)) = (pair[0], pair[1]);
+            let written = &chars[from + 1..to];
+            let read = spoken[spoken_from + 1..spoken_to]
+                .iter()
+                .filter(|ch| !ch.is_whitespace());
+            let contiguous = written.iter().all(|(_, _, markup)| !markup)
+                && read.eq(written
+                    .iter()
+                    .map(|(_, ch, _)| ch)
+                    .filter(|ch| !ch.is_whitespace()));
+            if !contiguous {
+                pieces.push(piece_start..from + 1);
+                piece_start = to;
+            }
+        }
+        // Take in the punctuation that ends the sentence, also past tags.
+        let mut last = matched[matched.len() - 1].1;
+        let mut next = last + 1;
+        let mut past_markup = false;
+        while let Some(&(_, ch, markup)) = chars.get(next) {
+            if markup {
+                past_markup = true;
+            } else {
+                if !(SENTENCE_ENDS.contains(&ch)
+                    || matches!(ch, '"' | '\'' | '”' | '’' | ')' | '）' | '」'))
+                {
+                    break;
+                }
+                if past_markup {
+                    pieces.push(piece_start..last + 1);
+                    piece_start = next;
+                    past_markup = false;
+                }
+                last = next;
+            }
+            next += 1;
+        }
+        cursor = last + 1;
+        pieces.push(piece_start..last + 1);
+        ranges.push(
+            pieces
+                .into_iter()
+                .map(|piece| {
+                    let (end, ch, _) = chars[piece.end - 1];
+                    chars[piece.start].0..end + ch.len_utf16()
+                })
+                .collect(),
+        );
+    }
+    ranges
+}
+
+fn voice_id(model: &str, speaker: u32) -> String {
+    format!("{model}:{speaker}")
+}
+
+/// The speaker of `spec` to use: `voice` when it belongs to that model, else
+/// the voice configured for `lang`, else the first.
+fn speaker_for(spec: &ModelSpec, voice: Option<&str>, lang: &str) -> u32 {
+    let configured = crate::config::get_config()
+        .ok()
+        .and_then(|config| config.local_tts_voices)
+        .and_then(|voices| voices.get(lang).cloned());
+    [voice.map(str::to_string), configured]
+        .into_iter()
+        .flatten()
+        .find_map(|voice| {
+            let (model, speaker) = voice.rsplit_once(':')?;
+            (model == spec.id).then(|| speaker.parse().ok())?
+        })
+        .unwrap_or(0)
+}
+
+/// Synthesized sentences on disk as WAV files named after the SHA-256 of
+/// model, speaker, speed and verbalized text. Reading an entry updates its
+/// modification time, and the least recently used entries are deleted once
+/// the cache is over `capacity` bytes.
+#[derive(Clone)]
+struct SpeechCache {
+    directory: PathBuf,
+    capacity: u64,
+}
+
+impl SpeechCache {
+    /// The cache from the settings; `None` when `localTtsCacheSize` is 0.
+    fn configured(app: &AppHandle) -> Option<Self> {
+        let size = crate::config::get_config()
+            .ok()
+            .and_then(|config| config.local_tts_cache_size)
+            .unwrap_or(DEFAULT_CACHE_SIZE);
+        if size == 0 {
+            return None;
+        }
+        Some(Self {
+            directory: speech_cache_directory(app).ok()?,
+            capacity: size * 1024 * 1024,
+        })
+    }
+
+    fn path(&self, model: &str, speaker: u32, speed: f32, text: &str) -> PathBuf {
+        let key = Sha256::digest(format!("{model}\n{speaker}\n{speed:.3}\n{text}"));
+        self.directory.join(format!("{key:x}.wav"))
+    }
+
+    fn get(&self, path: &Path) -> Option<(Vec<f32>, i32)> {
+        let clip = crate::tts_audio::decode_wav(&std::fs::read(path).ok()?)?;
+        let _ = File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some(clip)
+    }
+
+    /// Stores `clip`, best effort: a full disk only costs a later resynthesis.
+    fn put(&self, path: &Path, clip: &(Vec<f32>, i32)) {
+        let partial = path.with_extension("part");
+        let stored = std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(&partial, encode_wav(&clip.0, clip.1)))
+            .and_then(|_| std::fs::rename(&partial, path));
+        if stored.is_err() {
+            let _ = std::fs::remove_file(&partial);
+            return;
+        }
+        self.evict();
+    }
+
+    fn evict(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.directory) else {
+            return;
+        };
+        let mut files = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "wav"))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect::<Vec<_>>();
+        let mut total = files.iter().map(|(_, size, _)| size).sum::<u64>();
+        files.sort();
+        for (_, size, path) in files {
+            if total <= self.capacity {
+                break;
+            }
+            if std::fs::remove_file(path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+fn speech_cache_directory(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_cache_dir(app).map(|path| path.join(CACHE_DIRECTORY))
+}
+
+/// A language run of the text to synthesize. The engine is only loaded when
+/// some sentence of the run is not cached.
+struct SynthesisRun {
+    app: AppHandle,
+    spec: ModelSpec,
+    model: String,
+    /// `None` when every sentence was cached.
+    engine: Option<SharedEngine>,
+    speaker: u32,
+    text: String,
+    cache: Option<SpeechCache>,
+    /// The speed the model reads at, which makes up for `pitch`.
+    speed: f32,
+    /// Pitch shift as a frequency factor, see `shift_pitch`.
+    pitch: f32,
+    gain: f32,
+    /// Silence before the run.
+    pause_ms: u32,
+}
+
+/// Synthesizes one sentence of `run`, or reads it from the cache, and shifts
+/// its pitch. Setting `cancelled` stops synthesis midway; the partial audio
+/// is not cached.
+fn synthesize_sentence(
+    run: &SynthesisRun,
+    sentence: &str,
+    cancelled: Option<&Arc<AtomicBool>>,
+) -> Result<(Vec<f32>, i32), String> {
+    let (samples, sample_rate) = read_sentence(run, sentence, cancelled)?;
+    if samples.is_empty() {
+        return Ok((samples, sample_rate));
+    }
+    let mut samples = shift_pitch(&samples, sample_rate, run.pitch)?;
+    apply_gain(&mut samples, run.gain);
+    Ok((samples, sample_rate))
+}
+
+fn read_sentence(
+    run: &SynthesisRun,
+    sentence: &str,
+    cancelled: Option<&Arc<AtomicBool>>,
+) -> Result<(Vec<f32>, i32), String> {
+    let speed = run.speed;
+    let cached = run
+        .cache
+        .as_ref()
+        .map(|cache| (cache, cache.path(&run.model, run.speaker, speed, sentence)));
+    if let Some(clip) = cached.as_ref().and_then(|(cache, path)| cache.get(path)) {
+        return Ok(clip);
+    }
+    // Without an engine only when an entry was evicted after
+    // `synthesis_runs` checked it; load it now to read the sentence anyway.
+    let engine = match &run.engine {
+        Some(engine) => engine.clone(),
+        None => tauri::async_runtime::block_on(ensure_engine(&run.app, &run.spec))?,
+    };
+    let keep_going = cancelled
+        .cloned()
+        .map(|flag| move |_: &[f32], _: f32| !flag.load(Ordering::SeqCst));
+    let clip = synthesize_samples(&engine.lock(), sentence, speed, run.speaker, keep_going)?;
+    if let Some((cache, path)) = cached {
+        if !cancelled.is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+            cache.put(&path, &clip);
+        }
+    }
+    Ok(clip)
+}
+
+/// Strips every span, splits it into language runs with `route_runs` and
+/// loads the engine of each. Every run is verbalized in its own language, so
+/// numbers in an English run inside Chinese prose are read in English.
+async fn synthesis_runs(
+    app: &AppHandle,
+    spans: &[Span],
+    lang: &str,
+    voice: Option<&str>,
+    rate: f32,
+    pitch: f32,
+) -> Result<Vec<SynthesisRun>, String> {
+    let catalog = catalog(app)?;
+    let root = model_root(app)?;
+    let cache = SpeechCache::configured(app);
+    let mut loaded = Vec::new();
+    let mut pause_ms = 0;
+    for span in spans {
+        pause_ms += span.pause_ms;
+        let text = crate::tts_normalize::strip_markdown(&span.text);
+        let segments = crate::lang::segment(None, &text, None);
+        let runs = crate::tts_catalog::route_runs(&catalog, &segments, lang, |spec| {
+            model_present(spec, &root)
+        })?;
+        let speed = (rate * span.emphasis.speed() / pitch).clamp(0.1, 2.0);
+        for run in runs {
+            let text = crate::tts_normalize::verbalize(&run.text, &run.lang);
+            if text.is_empty() {
+                continue;
+            }
+            let speaker = speaker_for(run.spec, voice, &run.lang);
+            let cached = cache.as_ref().is_some_and(|cache| {
+                split_sentences(&text)
+                    .iter()
+                    .all(|sentence| cache.path(&run.spec.id, speaker, speed, sentence).is_file())
+            });
+            loaded.push(SynthesisRun {
+                app: app.clone(),
+                spec: run.spec.clone(),
+                model: run.spec.id.clone(),
+                engine: if cached {
+                    None
+                } else {
+                    Some(ensure_engine(app, run.spec).await?)
+                },
+                speaker,
+                text,
+                cache: cache.clone(),
+                speed,
+                pitch,
+                gain: span.emphasis.gain(),
+                pause_ms: std::mem::take(&mut pause_ms),
+            });
+        }
+    }
+    Ok(loaded)
+}
+
+/// Speaker names of a Piper voice, from the `speaker_id_map` in the JSON
+/// config next to the model.
+fn piper_speakers(directory: &Path, model: &str) -> Option<Vec<(u32, String)>> {
+    let config = std::fs::read_to_string(directory.join(format!("{model}.json"))).ok()?;
+    let config = serde_json::from_str::<serde_json::Value>(&config).ok()?;
+    let mut speakers = config
+        .get("speaker_id_map")?
+        .as_object()?
+        .iter()
+        .filter_map(|(name, id)| Some((u32::try_from(id.as_u64()?).ok()?, name.clone())))
+        .collect::<Vec<_>>();
+    speakers.sort();
+    Some(speakers)
+}
+
+/// The voices of an installed model. Speakers the catalog does not name are
+/// counted by loading the model.
+async fn model_voices(app: &AppHandle, spec: &ModelSpec) -> Result<Vec<TtsVoice>, String> {
+    let voice =
+        |speaker: u32, name: String, gender: Option<String>, accent: Option<String>| TtsVoice {
+            id: voice_id(&spec.id, speaker),
+            model: spec.id.clone(),
+            speaker,
+            name,
+            gender,
+            accent,
+            languages: spec.languages.clone(),
+        };
+    if !spec.voices.is_empty() {
+        return Ok(spec
+            .voices
+            .iter()
+            .zip(0..)
+            .map(|(entry, speaker)| {
+                voice(
+                    speaker,
+                    entry.name.clone(),
+                    entry.gender.clone(),
+                    entry.accent.clone(),
+                )
+            })
+            .collect());
+    }
+    let count = ensure_engine(app, spec).await?.lock().num_speakers().max(1) as u32;
+    if count == 1 {
+        return Ok(vec![voice(0, spec.name.clone(), None, None)]);
+    }
+    let directory = model_root(app)?.join(&spec.directory);
+    let named = spec
+        .files
+        .model
+        .as_deref()
+        .and_then(|model| piper_speakers(&directory, model))
+        .unwrap_or_default();
+    Ok((0..count)
+        .map(|speaker| {
+            let name = named
+                .iter()
+                .find(|(id, _)| *id == speaker)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| format!("{} #{speaker}", spec.name));
+            voice(speaker, name, None, None)
+        })
+        .collect())
+}
+
+/// Synthesizes `text` and prepares it for playback. `keep_going` is polled
+/// while sherpa-onnx generates; returning `false` stops it early.
+fn synthesize_samples(
+    engine: &OfflineTts,
+    text: &str,
+    speed: f32,
+    speaker: u32,
+    keep_going: Option<impl FnMut(&[f32], f32) -> bool + 'static>,
+) -> Result<(Vec<f32>, i32), String> {
+    // Out-of-range ids, e.g. from a setting made for another version of the
+    // model, fall back to the first speaker.
+    let sid = i32::try_from(speaker)
+        .ok()
+        .filter(|sid| *sid < engine.num_speakers().max(1))
+        .unwrap_or(0);
+    let audio = engine
+        .generate_with_config(
+            text,
+            &GenerationConfig {
+                sid,
+                speed,
+                ..Default::default()
+            },
+            keep_going,
+        )
+        .ok_or_else(|| "TTS failed".to_string())?;
+    let mut samples = audio.samples().to_vec();
+    normalize_peak(&mut samples, 0.95);
+    apply_edge_fades(&mut samples, audio.sample_rate());
+    Ok((samples, audio.sample_rate()))
+}
+
+/// Synthesizes `parts` sentence by sentence and stitches the result. Also
+/// returns where every part is heard in it, in samples; empty for parts
+/// that yielded no audio.
+async fn synthesize_stitched(
+    app: &AppHandle,
+    parts: &[Vec<Span>],
+    lang: &str,
+    rate: f32,
+    voice: Option<&str>,
+    speech: &TtsSpeechOptions,
+) -> Result<(Clip, Vec<Range<usize>>), String> {
+    let mut runs = Vec::new();
+    for (part, spans) in parts.iter().enumerate() {
+        for run in synthesis_runs(app, spans, lang, voice, rate, speech.pitch()).await? {
+            runs.push((part, run));
+        }
+    }
+    if runs.is_empty() {
+        return Err("no speakable text".to_string());
+    }
+    let volume = speech.volume();
+    let parts = parts.len();
+
+    tokio::task::spawn_blocking(move || {
+        let mut clips = Vec::new();
+        // The part and leading pause of every clip.
+        let mut owners = Vec::new();
+        let mut pause_ms = 0;
+        for (part, run) in runs {
+            pause_ms += run.pause_ms;
+            for sentence in split_sentences(&run.text) {
+                let clip = synthesize_sentence(&run, sentence, None)?;
+                // A sentence of only symbols yields no audio.
+                if !clip.0.is_empty() {
+                    owners.push((part, pause_ms));
+                    clips.push(with_pause(clip, std::mem::take(&mut pause_ms)));
+                }
+            }
+        }
+        let ((mut samples, sample_rate), placed) = stitch(clips)?;
+        apply_gain(&mut samples, volume);
+        let mut heard = vec![0..0; parts];
+        for ((part, pause_ms), clip) in owners.into_iter().zip(placed) {
+            let pause = (sample_rate as u64 * u64::from(pause_ms) / 1000) as usize;
+            let range = &mut heard[part];
+            if range.start == range.end {
+                *range = (clip.start + pause).min(clip.end)..clip.end;
+            } else {
+                range.end = clip.end;
+            }
+        }
+        Ok(((samples, sample_rate), heard))
+    })
+    .await
+    .map_err(|err| format!("synthesize TTS task failed: {err}"))?
+}
+
+/// Synthesizes `text` into a WAV, with the sentences it reads and when, for
+/// highlighting along. `speech` sets volume and pitch and turns on markup
+/// such as `<break time="2s"/>` for pacing.
 #[tauri::command]
 #[specta::specta]
 pub async fn synthesize_local_tts(
@@ -416,38 +1628,513 @@ pub async fn synthesize_local_tts(
     text: String,
     lang: String,
     rate: f32,
-) -> Result<String, String> {
-    let text = text.trim().to_string();
-    if text.is_empty() {
-        return Err("no speakable text".to_string());
+    voice: Option<String>,
+    speech: Option<TtsSpeechOptions>,
+) -> Result<TtsSpeech, String> {
+    let speech = speech.unwrap_or_default();
+    let (texts, parts): (Vec<_>, Vec<_>) = sentence_spans(speech.spans(&text)).into_iter().unzip();
+    let ((samples, sample_rate), heard) =
+        synthesize_stitched(&app, &parts, &lang, rate, voice.as_deref(), &speech).await?;
+    let markup_chars = if speech.markup {
+        crate::tts_markup::markup_chars(&text)
+    } else {
+        Vec::new()
+    };
+    let sentences = texts.iter().map(String::as_str).collect::<Vec<_>>();
+    let sources = source_ranges(&text, &markup_chars, &sentences);
+    let ms = |samples: usize| (samples as u64 * 1000 / sample_rate.max(1) as u64) as u32;
+    let segments = texts
+        .into_iter()
+        .zip(sources)
+        .zip(heard)
+        .filter(|(_, heard)| !heard.is_empty())
+        .map(|((text, source), heard)| TtsSpeechSegment {
+            text,
+            ranges: source
+                .into_iter()
+                .map(|range| TtsTextRange {
+                    start: range.start as u32,
+                    end: range.end as u32,
+                })
+                .collect(),
+            start_ms: ms(heard.start),
+            end_ms: ms(heard.end),
+        })
+        .collect();
+    Ok(TtsSpeech {
+        audio: BASE64.encode(encode_wav(&samples, sample_rate)),
+        segments,
+    })
+}
+
+/// Synthesizes `text` into an audio file at `path`, for study material. The
+/// format comes from `options` or the extension of `path`; see
+/// `TtsExportOptions` for splitting into one file per sentence.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_local_tts(
+    app: AppHandle,
+    text: String,
+    lang: String,
+    rate: f32,
+    voice: Option<String>,
+    path: String,
+    options: TtsExportOptions,
+) -> Result<TtsExport, String> {
+    let speech = options.speech;
+    let mut path = PathBuf::from(path);
+    let format = match options.format {
+        Some(format) => format,
+        None => path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(AudioFileFormat::from_extension)
+            .ok_or_else(|| "choose an audio format or a .wav, .flac or .opus path".to_string())?,
+    };
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    if let Some(sample_rate) = options.sample_rate {
+        format.check_sample_rate(sample_rate)?;
+    }
+
+    let split = options.split_sentences;
+    let spans = speech.spans(&text);
+    let texts = if split {
+        sentence_spans(spans)
+    } else {
+        vec![(text, spans)]
+    };
+    let mut clips = Vec::new();
+    for (text, spans) in texts {
+        let (clip, _) =
+            synthesize_stitched(&app, &[spans], &lang, rate, voice.as_deref(), &speech).await?;
+        clips.push((text, clip));
     }
-    let kind = model_for_text(&text, &lang)?;
-    let engine = ensure_engine(&app, kind).await?;
-    let speed = rate.clamp(0.1, 2.0);
+    let sample_rate = match (options.sample_rate, format) {
+        (Some(sample_rate), _) => sample_rate,
+        (None, AudioFileFormat::Opus) => 48_000,
+        (None, _) => clips
+            .iter()
+            .map(|(_, (_, rate))| *rate as u32)
+            .max()
+            .ok_or_else(|| "no speakable text".to_string())?,
+    };
+    format.check_sample_rate(sample_rate)?;
 
     tokio::task::spawn_blocking(move || {
-        let engine = engine.lock();
-        let audio = engine
-            .generate_with_config(
-                &text,
-                &GenerationConfig {
-                    sid: 0,
-                    speed,
-                    ..Default::default()
-                },
-                None::<fn(&[f32], f32) -> bool>,
-            )
-            .ok_or_else(|| "TTS produced no audio".to_string())?;
-        let mut samples = audio.samples().to_vec();
-        if samples.is_empty() {
-            return Err("TTS produced no audio".to_string());
-        }
-        normalize_peak(&mut samples, 0.95);
-        apply_edge_fades(&mut samples, audio.sample_rate());
-        Ok(BASE64.encode(encode_wav(&samples, audio.sample_rate())))
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut files = Vec::new();
+        for (index, (text, (samples, rate))) in clips.into_iter().enumerate() {
+            let file = if split {
+                directory.join(format!("{stem}-{:03}.{}", index + 1, format.extension()))
+            } else {
+                path.clone()
+            };
+            let samples = crate::tts_audio::resample(&samples, rate, sample_rate as i32)?;
+            let encoded = format.encode(&samples, sample_rate)?;
+            std::fs::write(&file, encoded)
+                .map_err(|err| format!("write {}: {err}", file.display()))?;
+            files.push(TtsExportFile {
+                path: path_string(file),
+                text,
+                duration_ms: (samples.len() as u64 * 1000 / u64::from(sample_rate)) as u32,
+            });
+        }
+        let manifest = if split {
+            let manifest_path = path.with_extension("json");
+            let listed = files
+                .iter()
+                .map(|file| TtsExportFile {
+                    path: Path::new(&file.path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    ..file.clone()
+                })
+                .collect();
+            let content = serde_json::to_string_pretty(&TtsExport {
+                format,
+                sample_rate,
+                files: listed,
+                manifest: None,
+            })
+            .map_err(|err| format!("serialize export manifest: {err}"))?;
+            std::fs::write(&manifest_path, content)
+                .map_err(|err| format!("write {}: {err}", manifest_path.display()))?;
+            Some(path_string(manifest_path))
+        } else {
+            None
+        };
+        Ok(TtsExport {
+            format,
+            sample_rate,
+            files,
+            manifest,
+        })
     })
     .await
-    .map_err(|err| format!("synthesize TTS task failed: {err}"))?
+    .map_err(|err| format!("export TTS task failed: {err}"))?
+}
+
+/// Like `synthesize_local_tts`, but emits every sentence as soon as it is
+/// synthesized, as a `tts-stream-segment` event with the stream `id`, a
+/// sequence number counting from 0 and a base64 WAV. Emitting
+/// `abort-local-tts-stream` with the same `id` stops synthesis, also in the
+/// middle of a sentence. Returns the number of segments emitted.
+#[tauri::command]
+#[specta::specta]
+pub async fn synthesize_local_tts_stream(
+    app: AppHandle,
+    id: String,
+    text: String,
+    lang: String,
+    rate: f32,
+    voice: Option<String>,
+    speech: Option<TtsSpeechOptions>,
+) -> Result<u32, String> {
+    let speech = speech.unwrap_or_default();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let listen_id = {
+        let cancelled = cancelled.clone();
+        let id = id.clone();
+        app.listen_any("abort-local-tts-stream", move |event| {
+            if serde_json::from_str::<TtsStreamAbort>(event.payload())
+                .is_ok_and(|abort| abort.id == id)
+            {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        })
+    };
+    let result = async {
+        let spans = speech.spans(&text);
+        let runs =
+            synthesis_runs(&app, &spans, &lang, voice.as_deref(), rate, speech.pitch()).await?;
+        if runs.is_empty() {
+            return Err("no speakable text".to_string());
+        }
+        let volume = speech.volume();
+        let app = app.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut sequence = 0;
+            // Pauses go at the start of the segment after them.
+            let mut pause_ms = 0;
+            // Each run's sentences go out as their own segments, at the
+            // sample rate of the model that read them.
+            'runs: for run in runs {
+                pause_ms += run.pause_ms;
+                for sentence in split_sentences(&run.text) {
+                    if cancelled.load(Ordering::SeqCst) {
+                        break 'runs;
+                    }
+                    let synthesized = synthesize_sentence(&run, sentence, Some(&cancelled));
+                    if cancelled.load(Ordering::SeqCst) {
+                        break 'runs;
+                    }
+                    let clip = synthesized?;
+                    // A sentence of only symbols yields no audio.
+                    if clip.0.is_empty() {
+                        continue;
+                    }
+                    let (mut samples, sample_rate) =
+                        with_pause(clip, std::mem::take(&mut pause_ms));
+                    apply_gain(&mut samples, volume);
+                    let _ = app.emit(
+                        "tts-stream-segment",
+                        TtsStreamSegment {
+                            id: id.clone(),
+                            sequence,
+                            text: sentence.to_string(),
+                            audio: BASE64.encode(encode_wav(&samples, sample_rate)),
+                            sample_rate,
+                        },
+                    );
+                    sequence += 1;
+                }
+            }
+            if sequence == 0 && !cancelled.load(Ordering::SeqCst) {
+                return Err("TTS produced no audio".to_string());
+            }
+            Ok(sequence)
+        })
+        .await
+        .map_err(|err| format!("synthesize TTS task failed: {err}"))?
+    }
+    .await;
+    app.unlisten(listen_id);
+    result
+}
+
+fn model_info(spec: ModelSpec, root: &Path) -> TtsModelInfo {
+    let installed = model_present(&spec, root);
+    TtsModelInfo {
+        downloadable: spec.downloadable(),
+        loaded: ENGINES.lock().contains_key(&spec.id),
+        size: if installed {
+            directory_size(&root.join(&spec.directory)) as f64
+        } else {
+            0.0
+        },
+        installed,
+        id: spec.id,
+        name: spec.name,
+        languages: spec.languages,
+    }
+}
+
+/// The local TTS models this build knows, with whether each is downloaded and
+/// loaded and its size on disk.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_tts_models(app: AppHandle) -> Result<Vec<TtsModelInfo>, String> {
+    let root = model_root(&app)?;
+    let catalog = catalog(&app)?;
+    tokio::task::spawn_blocking(move || {
+        catalog
+            .into_iter()
+            .map(|spec| model_info(spec, &root))
+            .collect()
+    })
+    .await
+    .map_err(|err| format!("list TTS models task failed: {err}"))
+}
+
+/// The voices of the installed models, or of the model `id`. Models whose
+/// speakers the catalog does not list are loaded to count them.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_tts_voices(app: AppHandle, id: Option<String>) -> Result<Vec<TtsVoice>, String> {
+    let root = model_root(&app)?;
+    let mut voices = Vec::new();
+    for spec in catalog(&app)? {
+        if id.as_ref().is_some_and(|id| *id != spec.id) || !model_present(&spec, &root) {
+            continue;
+        }
+        voices.extend(model_voices(&app, &spec).await?);
+    }
+    Ok(voices)
+}
+
+/// Downloads a model ahead of its first use, reporting progress through
+/// `tts-download-progress` events. Does nothing when it is already installed.
+#[tauri::command]
+#[specta::specta]
+pub async fn download_tts_model(app: AppHandle, id: String) -> Result<(), String> {
+    let spec = find_model(&app, &id)?;
+    let _download_guard = crate::download::lock(&download_key(&spec.id)).await;
+    let root = model_root(&app)?;
+    if model_present(&spec, &root) {
+        return Ok(());
+    }
+    download_model(&app, &spec, &root).await
+}
+
+/// Pauses a running model download; it reports the `paused` phase and keeps
+/// what it has so far.
+#[tauri::command]
+#[specta::specta]
+pub fn pause_tts_download(id: String) -> Result<(), String> {
+    crate::download::pause(&download_key(&id))
+}
+
+/// Continues a paused model download with an HTTP Range request.
+#[tauri::command]
+#[specta::specta]
+pub fn resume_tts_download(id: String) -> Result<(), String> {
+    crate::download::resume(&download_key(&id))
+}
+
+/// Stops a running or paused model download and deletes the partial file;
+/// it reports the `cancelled` phase.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_tts_download(id: String) -> Result<(), String> {
+    crate::download::cancel(&download_key(&id))
+}
+
+/// Checks an installed model's files against the checksums recorded when it
+/// was downloaded.
+#[tauri::command]
+#[specta::specta]
+pub async fn verify_tts_model(app: AppHandle, id: String) -> Result<TtsModelVerification, String> {
+    let spec = find_model(&app, &id)?;
+    let directory = model_root(&app)?.join(&spec.directory);
+    if !directory.is_dir() {
+        return Err(format!("TTS model {id} is not installed"));
+    }
+    tokio::task::spawn_blocking(move || verify_files(&directory, &spec.required_files))
+        .await
+        .map_err(|err| format!("verify TTS model task failed: {err}"))
+}
+
+/// Unloads a model and deletes its files, cancelling a download of it.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_tts_model(app: AppHandle, id: String) -> Result<(), String> {
+    let spec = find_model(&app, &id)?;
+    let key = download_key(&spec.id);
+    // Fails when nothing is downloading, which is fine.
+    let _ = crate::download::cancel(&key);
+    let _download_guard = crate::download::lock(&key).await;
+    let _load_guard = LOAD_LOCK.lock().await;
+    ENGINES.lock().remove(&spec.id);
+    let directory = model_root(&app)?.join(&spec.directory);
+    if directory.exists() {
+        tokio::fs::remove_dir_all(&directory)
+            .await
+            .map_err(|err| format!("delete TTS model {id}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Installs a model from a local `.tar.bz2` or directory, for machines that
+/// cannot download. An archive of a catalog model, e.g. the release tarball of
+/// the bundled Chinese voice, replaces that model; anything else is added to
+/// `catalog.json` under its directory name after its files are checked
+/// against what its family needs. Either way it speaks `languages`.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_tts_model(
+    app: AppHandle,
+    path: String,
+    languages: Vec<String>,
+    name: Option<String>,
+    family: Option<ModelFamily>,
+) -> Result<TtsModelInfo, String> {
+    let languages = languages
+        .into_iter()
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+        .collect::<Vec<_>>();
+    if languages.is_empty() {
+        return Err("assign at least one language to the imported model".to_string());
+    }
+    let root = model_root(&app)?;
+    let catalog = catalog(&app)?;
+    let _load_guard = LOAD_LOCK.lock().await;
+
+    let import_root = root.clone();
+    let spec = tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&import_root)
+            .map_err(|err| format!("create TTS model directory: {err}"))?;
+        let staging = import_root.join(".import");
+        let _ = std::fs::remove_dir_all(&staging);
+        std::fs::create_dir_all(&staging)
+            .map_err(|err| format!("create import directory: {err}"))?;
+        let result = (|| {
+            let (staged, directory) = stage_import(Path::new(&path), &staging)?;
+            let files = model_files(&staged);
+            let spec = match catalog.iter().find(|spec| spec.directory == directory) {
+                Some(existing) => {
+                    if let Some(missing) = existing
+                        .required_files
+                        .iter()
+                        .find(|name| !files.contains(name))
+                    {
+                        return Err(format!("{missing} is missing"));
+                    }
+                    ModelSpec {
+                        name: name.clone().unwrap_or_else(|| existing.name.clone()),
+                        languages: languages.clone(),
+                        ..existing.clone()
+                    }
+                }
+                None => {
+                    let (family, files, required_files) =
+                        crate::tts_catalog::detect_layout(&files, family)?;
+                    ModelSpec {
+                        id: directory.clone(),
+                        name: name.clone().unwrap_or_else(|| directory.clone()),
+                        languages: languages.clone(),
+                        family,
+                        directory: directory.clone(),
+                        urls: Vec::new(),
+                        sha256: String::new(),
+                        files,
+                        required_files,
+                        lang: None,
+                        voices: Vec::new(),
+                    }
+                }
+            };
+            let destination = import_root.join(&spec.directory);
+            if destination.exists() {
+                std::fs::remove_dir_all(&destination)
+                    .map_err(|err| format!("replace old TTS model: {err}"))?;
+            }
+            std::fs::rename(&staged, &destination)
+                .map_err(|err| format!("move imported model: {err}"))?;
+            write_checksums(&destination)?;
+            crate::tts_catalog::save_user_entry(&import_root, spec.clone())?;
+            Ok(spec)
+        })();
+        let _ = std::fs::remove_dir_all(&staging);
+        result
+    })
+    .await
+    .map_err(|err| format!("import TTS model task failed: {err}"))?
+    .map_err(|err| format!("import TTS model: {err}"))?;
+
+    ENGINES.lock().remove(&spec.id);
+    Ok(model_info(spec, &root))
+}
+
+/// Where the models live, the free space there and the space they use, so
+/// the UI can warn before a download.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tts_storage(app: AppHandle) -> Result<TtsStorage, String> {
+    let root = model_root(&app)?;
+    let cache = speech_cache_directory(&app)?;
+    tokio::task::spawn_blocking(move || TtsStorage {
+        directory: root.to_string_lossy().into_owned(),
+        available: available_space(&root).map(|bytes| bytes as f64),
+        used: directory_size(&root) as f64,
+        cache: directory_size(&cache) as f64,
+    })
+    .await
+    .map_err(|err| format!("read TTS storage task failed: {err}"))
+}
+
+/// The loaded TTS engines with the memory they hold. Engines unload after
+/// `localTtsIdleUnload` seconds unused, and at most `localTtsMaxEngines`
+/// stay loaded.
+#[tauri::command]
+#[specta::specta]
+pub fn list_loaded_tts_engines() -> Vec<TtsLoadedEngine> {
+    let mut engines = ENGINES
+        .lock()
+        .iter()
+        .map(|(id, loaded)| TtsLoadedEngine {
+            id: id.clone(),
+            name: loaded.name.clone(),
+            memory: loaded.memory as f64,
+            idle: loaded.last_used.elapsed().as_secs_f64(),
+            in_use: loaded.in_use(),
+        })
+        .collect::<Vec<_>>();
+    engines.sort_by(|a, b| a.id.cmp(&b.id));
+    engines
+}
+
+/// Deletes all cached speech, e.g. after replacing a model with another
+/// version under the same id.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_tts_cache(app: AppHandle) -> Result<(), String> {
+    let directory = speech_cache_directory(&app)?;
+    tokio::task::spawn_blocking(move || match std::fs::remove_dir_all(&directory) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("clear TTS cache: {err}"))
+        }
+        _ => Ok(()),
+    })
+    .await
+    .map_err(|err| format!("clear TTS cache task failed: {err}"))?
 }
 
 #[cfg(test)]
@@ -455,22 +2142,209 @@ mod tests {
     use super::*;
 
     #[test]
-    fn routes_han_text_to_chinese_model() {
-        assert_eq!(model_for_text("Hello，世界", "en"), Ok(ModelKind::Chinese));
+    fn splits_sentences_but_not_decimals() {
+        assert_eq!(
+            split_sentences("你好。Pi is 3.14! Done\n\nnext"),
+            vec!["你好。", "Pi is 3.14!", "Done", "next"]
+        );
+        assert!(split_sentences(" \n ").is_empty());
+        assert_eq!(split_sentences(&"字".repeat(100)).len(), 2);
+    }
+
+    #[test]
+    fn verifies_files_against_recorded_checksums() {
+        let directory = std::env::temp_dir().join(format!("tts-verify-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("espeak-ng-data")).unwrap();
+        std::fs::write(directory.join("model.onnx"), b"model").unwrap();
+        std::fs::write(directory.join("espeak-ng-data/phontab"), b"phones").unwrap();
+        write_checksums(&directory).unwrap();
+        let checksums_size = std::fs::metadata(directory.join(CHECKSUMS_FILE))
+            .unwrap()
+            .len();
+        assert_eq!(directory_size(&directory), 11 + checksums_size);
+
+        let verification = verify_files(&directory, &["model.onnx"]);
+        assert!(verification.ok && verification.has_checksums);
+
+        std::fs::write(directory.join("model.onnx"), b"changed").unwrap();
+        std::fs::remove_file(directory.join("espeak-ng-data/phontab")).unwrap();
+        let verification = verify_files(&directory, &["model.onnx", "tokens.txt"]);
+        assert!(!verification.ok);
+        assert_eq!(verification.mismatched, vec!["model.onnx"]);
+        assert_eq!(
+            verification.missing,
+            vec!["espeak-ng-data/phontab", "tokens.txt"]
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn reads_piper_speaker_names() {
+        let directory = std::env::temp_dir().join(format!("tts-piper-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("en_US-libritts-high.onnx.json"),
+            r#"{"num_speakers": 3, "speaker_id_map": {"p3922": 1, "p14": 0, "p6590": 2}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            piper_speakers(&directory, "en_US-libritts-high.onnx").unwrap(),
+            vec![
+                (0, "p14".to_string()),
+                (1, "p3922".to_string()),
+                (2, "p6590".to_string())
+            ]
+        );
+        assert!(piper_speakers(&directory, "missing.onnx").is_none());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn speech_cache_evicts_least_recently_used() {
+        let cache = SpeechCache {
+            directory: std::env::temp_dir().join(format!("tts-cache-{}", std::process::id())),
+            // Two clips of 100 samples, 244 bytes each.
+            capacity: 500,
+        };
+        let clip = (vec![0.5; 100], 16_000);
+        let paths = ["a", "b", "c"].map(|text| cache.path("en", 0, 1.0, text));
+        assert_ne!(paths[0], cache.path("en", 1, 1.0, "a"));
+        assert_ne!(paths[0], cache.path("en", 0, 1.2, "a"));
+
+        let pause = || std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.put(&paths[0], &clip);
+        pause();
+        cache.put(&paths[1], &clip);
+        pause();
+        let (samples, sample_rate) = cache.get(&paths[0]).unwrap();
+        assert_eq!((samples.len(), sample_rate), (100, 16_000));
+        pause();
+        cache.put(&paths[2], &clip);
+        assert!(paths[0].is_file() && !paths[1].is_file() && paths[2].is_file());
+        std::fs::remove_dir_all(&cache.directory).unwrap();
+    }
+
+    #[test]
+    fn splits_markup_spans_into_sentences() {
+        let sentences = sentence_spans(crate::tts_markup::parse(
+            "Listen. <break time=\"2s\"/>Write <emphasis>this</emphasis> word! \
+             ... 你好。<break/>世界",
+        ));
+        let texts = sentences
+            .iter()
+            .map(|(text, spans)| (text.as_str(), spans.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("Listen.", 1),
+                ("Write this word!", 3),
+                ("你好。", 1),
+                ("世界", 1)
+            ]
+        );
+        assert_eq!(sentences[1].1[0].pause_ms, 2000);
+        assert_eq!(
+            sentences[1].1[1].emphasis,
+            crate::tts_markup::Emphasis::Moderate
+        );
+        assert_eq!(sentences[3].1[0].pause_ms, 500);
+    }
+
+    #[test]
+    fn maps_sentences_back_to_the_input() {
+        let sources = |input: &str, markup: bool| {
+            let spans = if markup {
+                crate::tts_markup::parse(input)
+            } else {
+                vec![Span::plain(input)]
+            };
+            let markup_chars = if markup {
+                crate::tts_markup::markup_chars(input)
+            } else {
+                Vec::new()
+            };
+            let texts = sentence_spans(spans)
+                .into_iter()
+                .map(|(text, _)| text)
+                .collect::<Vec<_>>();
+            let sentences = texts.iter().map(String::as_str).collect::<Vec<_>>();
+            let utf16 = input.encode_utf16().collect::<Vec<_>>();
+            source_ranges(input, &markup_chars, &sentences)
+                .into_iter()
+                .map(|ranges| {
+                    ranges
+                        .into_iter()
+                        .map(|range| String::from_utf16(&utf16[range]).unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sources(
+                "**Hello** world. Read [this](https://e.com/this) twice!\n🙂 你好。",
+                false
+            ),
+            [
+                vec!["Hello", "world."],
+                vec!["Read", "this", "twice!"],
+                vec!["你好。"],
+            ]
+        );
+        assert_eq!(
+            sources(
+                "<speak>Spell <say-as interpret-as=\"characters\">cat</say-as>.</speak> \
+                 Done &amp; dusted.",
+                true
+            ),
+            [vec!["Spell", "cat", "."], vec!["Done", "dusted."]]
+        );
+    }
+
+    #[test]
+    fn picks_engine_threads_by_core_count() {
+        let settings = |cores| {
+            let settings = auto_engine_settings(cores);
+            (settings.threads, settings.max_sentences)
+        };
+        assert_eq!(settings(0), (1, 1));
+        assert_eq!(settings(1), (1, 1));
+        assert_eq!(settings(2), (2, 2));
+        assert_eq!(settings(4), (2, 2));
+        assert_eq!(settings(6), (3, 2));
+        assert_eq!(settings(32), (4, 2));
     }
 
     #[test]
-    fn routes_supported_languages_and_rejects_others() {
-        assert_eq!(model_for_text("Hello", "en"), Ok(ModelKind::English));
-        assert_eq!(model_for_text("123", "zh-Hant"), Ok(ModelKind::Chinese));
-        assert!(model_for_text("Bonjour", "fr").is_err());
+    fn unloads_idle_and_least_recently_used_engines() {
+        let minutes = |n: u64| Duration::from_secs(60 * n);
+        let engines = [
+            ("zh".to_string(), minutes(20), false),
+            ("en".to_string(), minutes(5), false),
+            ("de".to_string(), minutes(30), true),
+            ("fr".to_string(), minutes(1), false),
+        ];
+        assert_eq!(engines_to_unload(&engines, Some(minutes(10)), 4), ["zh"]);
+        assert_eq!(engines_to_unload(&engines, None, 2), ["zh", "en"]);
+        assert_eq!(
+            engines_to_unload(&engines, Some(minutes(3)), 1),
+            ["zh", "en", "fr"]
+        );
+        assert!(engines_to_unload(&engines, None, 4).is_empty());
     }
 
     #[test]
-    fn wav_encoder_writes_pcm_header() {
-        let wav = encode_wav(&[0.0, 1.0, -1.0], 24_000);
-        assert_eq!(&wav[0..4], b"RIFF");
-        assert_eq!(&wav[8..12], b"WAVE");
-        assert_eq!(wav.len(), 50);
+    fn stitches_clips_at_the_highest_rate() {
+        let ((samples, sample_rate), placed) =
+            stitch(vec![(vec![0.5; 1_600], 16_000), (vec![0.5; 800], 8_000)]).unwrap();
+        assert_eq!(sample_rate, 16_000);
+        // 12 ms of overlap at 16 kHz, summed and limited to the peak.
+        assert_eq!(samples.len(), 1_600 + 1_600 - 192);
+        assert_eq!(placed, vec![0..1_600, 1_408..3_008]);
+        assert!(samples.iter().all(|sample| sample.abs() <= 0.95 + 1e-6));
+        assert!(stitch(Vec::new()).is_err());
+        // Quiet clips, e.g. after a lower emphasis gain, stay quiet.
+        let ((samples, _), _) = stitch(vec![(vec![0.25; 800], 8_000)]).unwrap();
+        assert!(samples.iter().all(|sample| *sample == 0.25));
     }
 }
@@ -0,0 +1,464 @@
+// Encoders for synthesized speech, which is mono f32 as sherpa-onnx returns
+// it: 16-bit WAV for playback in the webview and for export, plus FLAC and
+// Ogg/Opus for exported study material. FLAC has no encoder crate we can
+// build everywhere, and speech only needs its simplest parts: fixed
+// predictors with Rice-coded residuals, one partition per frame.
+
+use audiopus::coder::Encoder as OpusEncoder;
+use audiopus::{Application, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use rubato::{FftFixedIn, Resampler};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioFileFormat {
+    Wav,
+    Flac,
+    /// Ogg/Opus, written with the `.opus` extension.
+    Opus,
+}
+
+impl AudioFileFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "wav" => Some(Self::Wav),
+            "flac" => Some(Self::Flac),
+            "opus" | "ogg" => Some(Self::Opus),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
+        }
+    }
+
+    /// Rejects rates the format cannot store: Opus only runs at 8, 12, 16,
+    /// 24 and 48 kHz.
+    pub fn check_sample_rate(self, sample_rate: u32) -> Result<(), String> {
+        let supported = match self {
+            Self::Wav | Self::Flac => (8_000..=192_000).contains(&sample_rate),
+            Self::Opus => SampleRate::try_from(sample_rate as i32).is_ok(),
+        };
+        if supported {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} does not support a sample rate of {sample_rate} Hz",
+                self.extension()
+            ))
+        }
+    }
+
+    pub fn encode(self, samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Wav => Ok(encode_wav(samples, sample_rate as i32)),
+            Self::Flac => Ok(encode_flac(samples, sample_rate)),
+            Self::Opus => encode_opus(samples, sample_rate),
+        }
+    }
+}
+
+/// Converts between sample rates with rubato's FFT resampler, which filters
+/// out what the target rate cannot hold instead of letting it alias.
+pub fn resample(samples: &[f32], from: i32, to: i32) -> Result<Vec<f32>, String> {
+    if from == to || samples.is_empty() {
+        return Ok(samples.to_vec());
+    }
+    if from <= 0 || to <= 0 {
+        return Err(format!("cannot resample from {from} Hz to {to} Hz"));
+    }
+    let mut resampler = FftFixedIn::<f32>::new(from as usize, to as usize, 1024, 2, 1)
+        .map_err(|err| format!("create resampler: {err}"))?;
+    let delay = resampler.output_delay();
+    let length = (samples.len() as f64 * to as f64 / from as f64).round() as usize;
+    let mut output = Vec::with_capacity(length + delay);
+    let mut rest = samples;
+    while rest.len() >= resampler.input_frames_next() {
+        let (chunk, tail) = rest.split_at(resampler.input_frames_next());
+        let resampled = resampler
+            .process(&[chunk], None)
+            .map_err(|err| format!("resample: {err}"))?;
+        output.extend_from_slice(&resampled[0]);
+        rest = tail;
+    }
+    let mut remaining = (!rest.is_empty()).then_some(rest);
+    // The resampler holds back `delay` frames; flush them with silence.
+    while output.len() < length + delay {
+        let resampled = resampler
+            .process_partial(
+                remaining
+                    .take()
+                    .map(|rest| [rest])
+                    .as_ref()
+                    .map(|rest| rest.as_slice()),
+                None,
+            )
+            .map_err(|err| format!("resample: {err}"))?;
+        output.extend_from_slice(&resampled[0]);
+    }
+    output.drain(..delay);
+    output.truncate(length);
+    Ok(output)
+}
+
+fn to_pcm16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+pub fn encode_wav(samples: &[f32], sample_rate: i32) -> Vec<u8> {
+    let data_len = samples.len() * 2;
+    let mut output = Vec::with_capacity(44 + data_len);
+    output.extend_from_slice(b"RIFF");
+    output.extend_from_slice(&(36_u32 + data_len as u32).to_le_bytes());
+    output.extend_from_slice(b"WAVEfmt ");
+    output.extend_from_slice(&16_u32.to_le_bytes());
+    output.extend_from_slice(&1_u16.to_le_bytes());
+    output.extend_from_slice(&1_u16.to_le_bytes());
+    output.extend_from_slice(&(sample_rate as u32).to_le_bytes());
+    output.extend_from_slice(&((sample_rate as u32) * 2).to_le_bytes());
+    output.extend_from_slice(&2_u16.to_le_bytes());
+    output.extend_from_slice(&16_u16.to_le_bytes());
+    output.extend_from_slice(b"data");
+    output.extend_from_slice(&(data_len as u32).to_le_bytes());
+    for sample in samples {
+        output.extend_from_slice(&to_pcm16(*sample).to_le_bytes());
+    }
+    output
+}
+
+/// Reads back what `encode_wav` wrote; other WAV layouts are not supported.
+pub fn decode_wav(bytes: &[u8]) -> Option<(Vec<f32>, i32)> {
+    let field = |offset: usize| bytes.get(offset..offset + 4);
+    if field(0)? != b"RIFF" || field(8)? != b"WAVE" || field(36)? != b"data" {
+        return None;
+    }
+    // PCM, mono, 16-bit.
+    if bytes.get(20..24)? != [1, 0, 1, 0] || bytes.get(34..36)? != [16, 0] {
+        return None;
+    }
+    let sample_rate = i32::from_le_bytes(field(24)?.try_into().ok()?);
+    let samples = bytes[44..]
+        .chunks_exact(2)
+        .map(|pcm| f32::from(i16::from_le_bytes([pcm[0], pcm[1]])) / f32::from(i16::MAX))
+        .collect();
+    Some((samples, sample_rate))
+}
+
+/// Samples per FLAC frame, the size the reference encoder uses.
+const FLAC_BLOCK_SIZE: usize = 4096;
+/// Rice parameters above this need the escape code, which is never worth it
+/// for 16-bit audio.
+const MAX_RICE_PARAMETER: u32 = 14;
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    /// Appends the low `bits` of `value`, most significant first; at most 32
+    /// bits at a time.
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        if bits > 32 {
+            self.write(value >> 32, bits - 32);
+            self.write(value & u64::from(u32::MAX), 32);
+            return;
+        }
+        self.pending = (self.pending << bits) | (value & ((1 << bits) - 1));
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes.push((self.pending >> self.pending_bits) as u8);
+        }
+        self.pending &= (1 << self.pending_bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u32 as u64, bits);
+    }
+
+    fn write_rice(&mut self, residual: i32, parameter: u32) {
+        let folded = ((residual << 1) ^ (residual >> 31)) as u32;
+        let mut quotient = folded >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        self.write(u64::from(folded), parameter);
+    }
+
+    /// Frame numbers are coded like UTF-8, extended to 31 bits.
+    fn write_utf8(&mut self, value: u32) {
+        if value < 0x80 {
+            self.write(u64::from(value), 8);
+            return;
+        }
+        let mut length = 2;
+        while value >= 1 << (5 * length + 1) {
+            length += 1;
+        }
+        let lead = (0xFF00_u32 >> length) & 0xFF;
+        self.write(u64::from(lead | (value >> (6 * (length - 1)))), 8);
+        for index in (0..length - 1).rev() {
+            self.write(u64::from(0x80 | ((value >> (6 * index)) & 0x3F)), 8);
+        }
+    }
+
+    fn align(&mut self) {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |mut crc, byte| {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// The best Rice parameter for `residuals` and the bits they then take.
+fn rice_cost(residuals: &[i32]) -> (u32, u64) {
+    let folded = residuals
+        .iter()
+        .map(|residual| ((residual << 1) ^ (residual >> 31)) as u32)
+        .collect::<Vec<_>>();
+    (0..=MAX_RICE_PARAMETER)
+        .map(|parameter| {
+            let bits = folded
+                .iter()
+                .map(|value| u64::from(value >> parameter) + 1 + u64::from(parameter))
+                .sum();
+            (parameter, bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, 0))
+}
+
+fn write_subframe(writer: &mut BitWriter, block: &[i32]) {
+    if block.iter().all(|sample| *sample == block[0]) {
+        writer.write(0, 8);
+        writer.write_signed(block[0], 16);
+        return;
+    }
+    // Fixed predictor of order n: the n-th difference of the signal.
+    let mut residuals = block.to_vec();
+    let mut best: Option<(u64, usize, u32, Vec<i32>)> = None;
+    for order in 0..=4.min(block.len() - 1) {
+        if order > 0 {
+            residuals = residuals.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        }
+        let (parameter, bits) = rice_cost(&residuals);
+        let cost = 16 * order as u64 + 10 + bits;
+        if best
+            .as_ref()
+            .map_or(true, |(best_cost, ..)| cost < *best_cost)
+        {
+            best = Some((cost, order, parameter, residuals.clone()));
+        }
+    }
+    match best {
+        Some((cost, order, parameter, residuals)) if cost < 16 * block.len() as u64 => {
+            writer.write(0b0001_0000 | ((order as u64) << 1), 8);
+            for sample in &block[..order] {
+                writer.write_signed(*sample, 16);
+            }
+            // Rice coding with 4-bit parameters, a single partition.
+            writer.write(0, 6);
+            writer.write(u64::from(parameter), 4);
+            for residual in residuals {
+                writer.write_rice(residual, parameter);
+            }
+        }
+        _ => {
+            writer.write(0b0000_0010, 8);
+            for sample in block {
+                writer.write_signed(*sample, 16);
+            }
+        }
+    }
+}
+
+pub fn encode_flac(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let pcm = samples
+        .iter()
+        .map(|sample| i32::from(to_pcm16(*sample)))
+        .collect::<Vec<_>>();
+    let mut output = b"fLaC".to_vec();
+
+    // STREAMINFO, the only metadata block; the MD5 of the audio is optional.
+    let mut info = BitWriter::default();
+    info.write(0x80, 8);
+    info.write(34, 24);
+    info.write(FLAC_BLOCK_SIZE as u64, 16);
+    info.write(FLAC_BLOCK_SIZE as u64, 16);
+    info.write(0, 48);
+    info.write(u64::from(sample_rate), 20);
+    info.write(0, 3);
+    info.write(15, 5);
+    info.write(pcm.len() as u64, 36);
+    info.write(0, 64);
+    info.write(0, 64);
+    output.extend(info.bytes);
+
+    for (number, block) in pcm.chunks(FLAC_BLOCK_SIZE).enumerate() {
+        let mut frame = BitWriter::default();
+        frame.write(0b11_1111_1111_1110, 14);
+        frame.write(0, 2);
+        // 4096 samples has its own code; other sizes follow the header.
+        let full = block.len() == FLAC_BLOCK_SIZE;
+        frame.write(if full { 0b1100 } else { 0b0111 }, 4);
+        // Sample rate from STREAMINFO, mono, 16 bits.
+        frame.write(0b0000_0000_1000, 12);
+        frame.write_utf8(number as u32);
+        if !full {
+            frame.write(block.len() as u64 - 1, 16);
+        }
+        let header_crc = crc8(&frame.bytes);
+        frame.write(u64::from(header_crc), 8);
+        write_subframe(&mut frame, block);
+        frame.align();
+        let frame_crc = crc16(&frame.bytes);
+        frame.write(u64::from(frame_crc), 16);
+        output.extend(frame.bytes);
+    }
+    output
+}
+
+/// Any number works for a file with a single logical stream.
+const OPUS_SERIAL: u32 = 1;
+
+pub fn encode_opus(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    AudioFileFormat::Opus.check_sample_rate(sample_rate)?;
+    let rate = SampleRate::try_from(sample_rate as i32)
+        .map_err(|err| format!("invalid Opus sample rate: {err}"))?;
+    let encoder = OpusEncoder::new(rate, Channels::Mono, Application::Voip)
+        .map_err(|err| format!("create Opus encoder: {err}"))?;
+    let lookahead = encoder
+        .lookahead()
+        .map_err(|err| format!("read Opus lookahead: {err}"))? as usize;
+    // Ogg/Opus counts granules at 48 kHz whatever the encoder runs at.
+    let scale = u64::from(48_000 / sample_rate);
+    let pre_skip = lookahead as u64 * scale;
+    let end_granule = pre_skip + samples.len() as u64 * scale;
+    let frame_size = sample_rate as usize / 50;
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let write = |writer: &mut PacketWriter<Vec<u8>>, packet: &[u8], end, granule| {
+        writer
+            .write_packet(packet.into(), OPUS_SERIAL, end, granule)
+            .map_err(|err| format!("write Ogg page: {err}"))
+    };
+    let mut head = b"OpusHead".to_vec();
+    head.extend_from_slice(&[1, 1]);
+    head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    head.extend_from_slice(&sample_rate.to_le_bytes());
+    head.extend_from_slice(&0_i16.to_le_bytes());
+    head.push(0);
+    write(&mut writer, &head, PacketWriteEndInfo::EndPage, 0)?;
+    let vendor = audiopus::version();
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0_u32.to_le_bytes());
+    write(&mut writer, &tags, PacketWriteEndInfo::EndPage, 0)?;
+
+    // Pad so the samples held back by the lookahead are flushed too.
+    let mut input = samples.to_vec();
+    input.resize(
+        (samples.len() + lookahead).div_ceil(frame_size) * frame_size,
+        0.0,
+    );
+    let frames = input.len() / frame_size;
+    let mut packet = vec![0_u8; 4000];
+    for (index, frame) in input.chunks(frame_size).enumerate() {
+        let length = encoder
+            .encode_float(frame, &mut packet)
+            .map_err(|err| format!("encode Opus: {err}"))?;
+        let granule = (((index + 1) * frame_size) as u64 * scale).min(end_granule);
+        let end = if index + 1 == frames {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        write(&mut writer, &packet[..length], end, granule)?;
+    }
+    Ok(writer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_encoder_writes_pcm_header() {
+        let wav = encode_wav(&[0.0, 1.0, -1.0], 24_000);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(wav.len(), 50);
+        assert_eq!(decode_wav(&wav), Some((vec![0.0, 1.0, -1.0], 24_000)));
+        assert_eq!(decode_wav(&wav[..40]), None);
+    }
+
+    #[test]
+    fn flac_encoder_compresses_speech_like_signals() {
+        let tone = (0..10_000)
+            .map(|index| (index as f32 * 0.05).sin() * 0.5)
+            .collect::<Vec<_>>();
+        let flac = encode_flac(&tone, 16_000);
+        assert_eq!(&flac[0..4], b"fLaC");
+        // STREAMINFO: last block, 34 bytes, then 16 kHz mono 16-bit.
+        assert_eq!(&flac[4..8], [0x80, 0, 0, 34]);
+        assert_eq!(&flac[18..21], [0x03, 0xE8, 0x00]);
+        // Frames start right after it with the sync code.
+        assert_eq!(&flac[42..44], [0xFF, 0xF8]);
+        assert!(flac.len() < tone.len());
+        // Silence is a constant subframe: header, CRC-8, 3 bytes, CRC-16.
+        assert_eq!(encode_flac(&[0.0; 100], 16_000).len(), 42 + 7 + 1 + 3 + 2);
+    }
+
+    #[test]
+    fn resamples_to_the_expected_length() {
+        let tone = (0..24_000)
+            .map(|index| (index as f32 * 0.05).sin())
+            .collect::<Vec<_>>();
+        assert_eq!(resample(&tone, 24_000, 16_000).unwrap().len(), 16_000);
+        assert_eq!(resample(&tone[..100], 22_050, 44_100).unwrap().len(), 200);
+        assert_eq!(resample(&tone[..3], 16_000, 16_000).unwrap(), &tone[..3]);
+        assert!(AudioFileFormat::Opus.check_sample_rate(22_050).is_err());
+        assert!(AudioFileFormat::Flac.check_sample_rate(22_050).is_ok());
+    }
+}
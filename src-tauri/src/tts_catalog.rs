@@ -0,0 +1,519 @@
+// The local TTS models, described by `tts-models.json` bundled with the app
+// and by an optional `catalog.json` in the model directory, whose entries
+// replace bundled ones with the same id or add new ones. Supporting another
+// model or language only takes an entry in either file; imported models get
+// theirs from `detect_layout`.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::lang::LangSegment;
+
+const BUNDLED_CATALOG: &str = include_str!("../tts-models.json");
+/// User additions, next to the downloaded models.
+pub const USER_CATALOG_FILE: &str = "catalog.json";
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelFamily {
+    Vits,
+    /// Piper voices are VITS models phonemized with espeak-ng.
+    Piper,
+    Kokoro,
+    Matcha,
+}
+
+/// Paths relative to the model directory. `lexicon` may list several files
+/// separated by commas, as sherpa-onnx accepts.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelFiles {
+    /// The ONNX model, or the acoustic model for Matcha.
+    pub model: Option<String>,
+    pub vocoder: Option<String>,
+    pub lexicon: Option<String>,
+    pub tokens: Option<String>,
+    pub voices: Option<String>,
+    pub data_dir: Option<String>,
+    pub dict_dir: Option<String>,
+    /// Text normalization rules, used when present.
+    pub rule_fsts: Vec<String>,
+}
+
+/// A speaker of a multi-speaker model; its position is the speaker id.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceSpec {
+    pub name: String,
+    #[serde(default)]
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub accent: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSpec {
+    pub id: String,
+    pub name: String,
+    /// Frontend language codes. A language is spoken by the first model in the
+    /// catalog that lists it.
+    pub languages: Vec<String>,
+    pub family: ModelFamily,
+    /// The directory the archive extracts to, directly below the model root.
+    pub directory: String,
+    #[serde(default)]
+    pub urls: Vec<String>,
+    /// SHA-256 of the archive. Without one the model is never downloaded and
+    /// has to be installed by hand.
+    #[serde(default)]
+    pub sha256: String,
+    pub files: ModelFiles,
+    /// Files that must exist for the model to count as installed.
+    pub required_files: Vec<String>,
+    /// espeak-ng language for multilingual Kokoro models.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Speakers by id. When empty they are read from the model itself.
+    #[serde(default)]
+    pub voices: Vec<VoiceSpec>,
+}
+
+impl ModelSpec {
+    pub fn downloadable(&self) -> bool {
+        !self.urls.is_empty() && !self.sha256.is_empty()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let directory = Path::new(&self.directory);
+        let single_component = directory.components().count() == 1
+            && matches!(
+                directory.components().next(),
+                Some(std::path::Component::Normal(_))
+            );
+        if self.id.is_empty() || !single_component {
+            return Err(format!(
+                "TTS model {:?} needs an id and a plain directory name",
+                self.id
+            ));
+        }
+        if self.required_files.is_empty() {
+            return Err(format!("TTS model {} lists no required files", self.id));
+        }
+        Ok(())
+    }
+}
+
+pub fn parse(json: &str) -> Result<Vec<ModelSpec>, String> {
+    let specs = serde_json::from_str::<Vec<ModelSpec>>(json)
+        .map_err(|err| format!("invalid TTS model catalog: {err}"))?;
+    for spec in &specs {
+        spec.validate()?;
+    }
+    Ok(specs)
+}
+
+/// `extra` entries replace those with the same id in place; new ones go after
+/// the bundled models so they do not take over languages those already speak.
+fn merge(mut catalog: Vec<ModelSpec>, extra: Vec<ModelSpec>) -> Vec<ModelSpec> {
+    for spec in extra {
+        match catalog.iter_mut().find(|existing| existing.id == spec.id) {
+            Some(existing) => *existing = spec,
+            None => catalog.push(spec),
+        }
+    }
+    catalog
+}
+
+/// The bundled catalog merged with `catalog.json` below `root`, if present.
+pub fn load(root: &Path) -> Result<Vec<ModelSpec>, String> {
+    let bundled = parse(BUNDLED_CATALOG)?;
+    let path = root.join(USER_CATALOG_FILE);
+    if !path.is_file() {
+        return Ok(bundled);
+    }
+    let json =
+        std::fs::read_to_string(&path).map_err(|err| format!("read {}: {err}", path.display()))?;
+    let extra = parse(&json).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(merge(bundled, extra))
+}
+
+/// Adds or replaces `spec` in `catalog.json` below `root`.
+pub fn save_user_entry(root: &Path, spec: ModelSpec) -> Result<(), String> {
+    spec.validate()?;
+    let path = root.join(USER_CATALOG_FILE);
+    let existing = if path.is_file() {
+        let json = std::fs::read_to_string(&path)
+            .map_err(|err| format!("read {}: {err}", path.display()))?;
+        parse(&json).map_err(|err| format!("{}: {err}", path.display()))?
+    } else {
+        Vec::new()
+    };
+    let json = serde_json::to_string_pretty(&merge(existing, vec![spec]))
+        .map_err(|err| format!("serialize TTS model catalog: {err}"))?;
+    std::fs::write(&path, json).map_err(|err| format!("write {}: {err}", path.display()))
+}
+
+/// Works out the model files and family of a model directory from its file
+/// list, `/`-separated and relative to it, as sherpa-onnx releases lay them
+/// out. `family` overrides the guess, which goes by the distinctive files:
+/// `voices.bin` for Kokoro, a vocoder for Matcha and espeak-ng data without a
+/// lexicon for Piper.
+pub fn detect_layout(
+    files: &[String],
+    family: Option<ModelFamily>,
+) -> Result<(ModelFamily, ModelFiles, Vec<String>), String> {
+    let top_level = |suffix: &str| {
+        files
+            .iter()
+            .filter(|name| !name.contains('/') && name.ends_with(suffix))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let has = |name: &str| files.iter().any(|file| file == name);
+    let has_dir = |name: &str| {
+        files
+            .iter()
+            .any(|file| file.starts_with(&format!("{name}/")))
+    };
+
+    let onnx = top_level(".onnx");
+    let (vocoders, models): (Vec<_>, Vec<_>) = onnx
+        .into_iter()
+        .partition(|name| name.contains("vocos") || name.contains("hifigan"));
+    let model = models
+        .iter()
+        .find(|name| *name == "model.onnx")
+        .or_else(|| models.iter().find(|name| !name.contains(".int8.")))
+        .or(models.first())
+        .cloned();
+    let lexicons = top_level(".txt")
+        .into_iter()
+        .filter(|name| name.starts_with("lexicon"))
+        .collect::<Vec<_>>();
+    let detected = ModelFiles {
+        model,
+        vocoder: vocoders.first().cloned(),
+        lexicon: (!lexicons.is_empty()).then(|| lexicons.join(",")),
+        tokens: has("tokens.txt").then(|| "tokens.txt".to_string()),
+        voices: has("voices.bin").then(|| "voices.bin".to_string()),
+        data_dir: has_dir("espeak-ng-data").then(|| "espeak-ng-data".to_string()),
+        dict_dir: has_dir("dict").then(|| "dict".to_string()),
+        rule_fsts: top_level(".fst"),
+    };
+
+    let family = family.unwrap_or(if detected.voices.is_some() {
+        ModelFamily::Kokoro
+    } else if detected.vocoder.is_some() {
+        ModelFamily::Matcha
+    } else if detected.data_dir.is_some() && detected.lexicon.is_none() {
+        ModelFamily::Piper
+    } else {
+        ModelFamily::Vits
+    });
+    let Some(model) = detected.model.clone() else {
+        return Err("no .onnx model found".to_string());
+    };
+    let Some(tokens) = detected.tokens.clone() else {
+        return Err("tokens.txt is missing".to_string());
+    };
+    let mut required = vec![model, tokens];
+    let missing = match family {
+        ModelFamily::Kokoro if detected.voices.is_none() => Some("voices.bin"),
+        ModelFamily::Kokoro | ModelFamily::Piper if detected.data_dir.is_none() => {
+            Some("espeak-ng-data")
+        }
+        ModelFamily::Matcha if detected.vocoder.is_none() => Some("a vocos or hifigan vocoder"),
+        ModelFamily::Vits | ModelFamily::Matcha
+            if detected.lexicon.is_none() && detected.data_dir.is_none() =>
+        {
+            Some("lexicon.txt or espeak-ng-data")
+        }
+        _ => None,
+    };
+    if let Some(missing) = missing {
+        return Err(format!("{family:?} model needs {missing}"));
+    }
+    required.extend(detected.voices.clone());
+    required.extend(detected.vocoder.clone());
+    required.extend(lexicons);
+    if detected.data_dir.is_some() {
+        required.push("espeak-ng-data/phontab".to_string());
+    }
+    Ok((family, detected, required))
+}
+
+fn primary_subtag(lang: &str) -> &str {
+    lang.split(['-', '_']).next().unwrap_or(lang)
+}
+
+/// The model to speak `text` in `lang`. Han characters go to a Chinese model
+/// unless the text is Japanese; a region or script the catalog does not list,
+/// e.g. `de-AT`, falls back to the bare language.
+pub fn route<'a>(
+    catalog: &'a [ModelSpec],
+    text: &str,
+    lang: &str,
+) -> Result<&'a ModelSpec, String> {
+    let lang = if text.chars().any(crate::tts::is_han) && primary_subtag(lang) != "ja" {
+        "zh-Hans"
+    } else {
+        lang
+    };
+    catalog
+        .iter()
+        .find(|spec| spec.languages.iter().any(|candidate| candidate == lang))
+        .or_else(|| {
+            catalog.iter().find(|spec| {
+                spec.languages
+                    .iter()
+                    .any(|candidate| primary_subtag(candidate) == primary_subtag(lang))
+            })
+        })
+        .ok_or_else(|| format!("local TTS does not support language: {lang}"))
+}
+
+/// A stretch of mixed-language text and the model that reads it.
+#[derive(Debug)]
+pub struct Run<'a> {
+    pub spec: &'a ModelSpec,
+    pub lang: String,
+    pub text: String,
+}
+
+/// Splits text already cut into language `segments` into runs for different
+/// models, e.g. the English terms in Chinese prose for an English voice. A
+/// run only moves to another model when `usable` accepts it, so without the
+/// English model installed the bilingual Chinese one keeps reading both.
+/// Runs without letters stay with their neighbours, and neighbours read by
+/// the same model are merged.
+pub fn route_runs<'a>(
+    catalog: &'a [ModelSpec],
+    segments: &[LangSegment],
+    lang: &str,
+    usable: impl Fn(&ModelSpec) -> bool,
+) -> Result<Vec<Run<'a>>, String> {
+    let text = segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<String>();
+    let fallback = route(catalog, &text, lang)?;
+    let mut runs: Vec<Run<'a>> = Vec::new();
+    let mut leading = String::new();
+    for segment in segments {
+        if segment.lang.is_empty() {
+            match runs.last_mut() {
+                Some(run) => run.text.push_str(&segment.text),
+                None => leading.push_str(&segment.text),
+            }
+            continue;
+        }
+        // Kanji in Japanese text look like Chinese to the detectors.
+        let own_lang =
+            !(primary_subtag(lang) == "ja" && segment.text.chars().any(crate::tts::is_han));
+        let (spec, run_lang) = [own_lang.then_some(segment.lang.as_str()), Some(lang)]
+            .into_iter()
+            .flatten()
+            .find_map(|candidate| {
+                route(catalog, &segment.text, candidate)
+                    .ok()
+                    .filter(|spec| usable(spec))
+                    .map(|spec| (spec, candidate))
+            })
+            .unwrap_or((fallback, lang));
+        match runs.last_mut() {
+            Some(run) if run.spec.id == spec.id => run.text.push_str(&segment.text),
+            _ => runs.push(Run {
+                spec,
+                lang: run_lang.to_string(),
+                text: std::mem::take(&mut leading) + &segment.text,
+            }),
+        }
+    }
+    if runs.is_empty() {
+        runs.push(Run {
+            spec: fallback,
+            lang: lang.to_string(),
+            text: leading,
+        });
+    }
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_languages_through_bundled_catalog() {
+        let catalog = parse(BUNDLED_CATALOG).unwrap();
+        assert_eq!(route(&catalog, "Hello，世界", "en").unwrap().id, "zh");
+        assert_eq!(route(&catalog, "Hello", "en").unwrap().id, "en");
+        assert_eq!(route(&catalog, "123", "zh-Hant").unwrap().id, "zh");
+        assert_eq!(
+            route(&catalog, "Guten Tag", "de-AT").unwrap().id,
+            "de-thorsten"
+        );
+        assert!(route(&catalog, "こんにちは", "ja").is_err());
+        assert!(route(&catalog, "漢字", "ja").is_err());
+        assert!(catalog.iter().all(|spec| spec.validate().is_ok()));
+        assert!(catalog[0].downloadable() && catalog[1].downloadable());
+    }
+
+    #[test]
+    fn routes_language_runs_to_installed_models() {
+        let catalog = parse(BUNDLED_CATALOG).unwrap();
+        let segments = |runs: &[(&str, &str)]| {
+            runs.iter()
+                .map(|(text, lang)| LangSegment {
+                    text: text.to_string(),
+                    start: 0,
+                    end: 0,
+                    lang: lang.to_string(),
+                    script: String::new(),
+                    is_reliable: true,
+                })
+                .collect::<Vec<_>>()
+        };
+        let summary = |runs: Vec<Run>| {
+            runs.into_iter()
+                .map(|run| (run.spec.id.clone(), run.lang, run.text))
+                .collect::<Vec<_>>()
+        };
+        let run =
+            |id: &str, lang: &str, text: &str| (id.to_string(), lang.to_string(), text.to_string());
+
+        let mixed = segments(&[
+            ("我喜欢用 ", "zh-Hans"),
+            ("Rust", "en"),
+            (" 写代码", "zh-Hans"),
+            ("。", ""),
+        ]);
+        assert_eq!(
+            summary(route_runs(&catalog, &mixed, "zh-Hans", |_| true).unwrap()),
+            [
+                run("zh", "zh-Hans", "我喜欢用 "),
+                run("en", "en", "Rust"),
+                run("zh", "zh-Hans", " 写代码。"),
+            ]
+        );
+        assert_eq!(
+            summary(route_runs(&catalog, &mixed, "zh-Hans", |spec| spec.id == "zh").unwrap()),
+            [run("zh", "zh-Hans", "我喜欢用 Rust 写代码。")]
+        );
+        assert_eq!(
+            summary(
+                route_runs(
+                    &catalog,
+                    &segments(&[("2024 ", ""), ("Hello", "en")]),
+                    "en",
+                    |_| true
+                )
+                .unwrap()
+            ),
+            [run("en", "en", "2024 Hello")]
+        );
+        assert_eq!(
+            summary(
+                route_runs(&catalog, &segments(&[("Guten Tag", "nl")]), "de", |_| true).unwrap()
+            ),
+            [run("de-thorsten", "de", "Guten Tag")]
+        );
+        assert_eq!(
+            summary(route_runs(&catalog, &segments(&[("42", "")]), "en", |_| true).unwrap()),
+            [run("en", "en", "42")]
+        );
+    }
+
+    #[test]
+    fn user_entries_replace_or_extend_bundled_models() {
+        let extra = parse(
+            r#"[
+                {"id": "en", "name": "Custom", "languages": ["en"], "family": "piper",
+                 "directory": "custom", "files": {"model": "a.onnx"}, "requiredFiles": ["a.onnx"]},
+                {"id": "ja", "name": "Japanese", "languages": ["ja"], "family": "vits",
+                 "directory": "ja", "files": {"model": "m.onnx"}, "requiredFiles": ["m.onnx"]}
+            ]"#,
+        )
+        .unwrap();
+        let catalog = merge(parse(BUNDLED_CATALOG).unwrap(), extra);
+        assert_eq!(catalog[1].name, "Custom");
+        assert_eq!(route(&catalog, "漢字", "ja").unwrap().id, "ja");
+        assert!(parse(
+            r#"[{"id": "x", "name": "X", "languages": [], "family": "vits",
+                 "directory": "../x", "files": {}, "requiredFiles": ["m"]}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn detects_model_layouts() {
+        let files = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let (family, detected, required) = detect_layout(
+            &files(&[
+                "en_US-amy-low.onnx",
+                "en_US-amy-low.onnx.json",
+                "espeak-ng-data/phontab",
+                "tokens.txt",
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(family, ModelFamily::Piper);
+        assert_eq!(detected.model.as_deref(), Some("en_US-amy-low.onnx"));
+        assert_eq!(
+            required,
+            ["en_US-amy-low.onnx", "tokens.txt", "espeak-ng-data/phontab"]
+        );
+
+        let (family, detected, _) = detect_layout(
+            &files(&[
+                "dict/jieba.dict.utf8",
+                "lexicon.txt",
+                "model-steps-3.onnx",
+                "tokens.txt",
+                "vocos-22khz-univ.onnx",
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(family, ModelFamily::Matcha);
+        assert_eq!(detected.model.as_deref(), Some("model-steps-3.onnx"));
+        assert_eq!(detected.vocoder.as_deref(), Some("vocos-22khz-univ.onnx"));
+        assert_eq!(detected.dict_dir.as_deref(), Some("dict"));
+
+        let (family, detected, _) = detect_layout(
+            &files(&[
+                "model.int8.onnx",
+                "model.onnx",
+                "lexicon-us-en.txt",
+                "lexicon-zh.txt",
+                "tokens.txt",
+                "voices.bin",
+                "espeak-ng-data/phontab",
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(family, ModelFamily::Kokoro);
+        assert_eq!(detected.model.as_deref(), Some("model.onnx"));
+        assert_eq!(
+            detected.lexicon.as_deref(),
+            Some("lexicon-us-en.txt,lexicon-zh.txt")
+        );
+
+        assert!(detect_layout(&files(&["model.onnx", "lexicon.txt"]), None).is_err());
+        assert!(detect_layout(&files(&["model.onnx", "tokens.txt"]), None).is_err());
+        assert!(detect_layout(
+            &files(&["model.onnx", "tokens.txt", "lexicon.txt"]),
+            Some(ModelFamily::Kokoro)
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,375 @@
+// A small subset of SSML for local TTS, enough to pace text for dictation:
+//
+//   <break time="1.5s"/>          a pause, in s or ms, or <break strength="strong"/>
+//   <emphasis level="strong">     read slower; "reduced" reads faster and quieter
+//   <say-as interpret-as="characters">  spell out letters and digits one by one
+//
+// Other tags, `<speak>` included, are dropped and their text kept. The models
+// have no stress control, so emphasis only changes speed and level.
+
+/// Longest pause a `<break>` can ask for.
+const MAX_BREAK_MS: u32 = 10_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Emphasis {
+    #[default]
+    None,
+    Reduced,
+    Moderate,
+    Strong,
+}
+
+impl Emphasis {
+    fn from_level(level: Option<&str>) -> Self {
+        match level {
+            Some("none") => Self::None,
+            Some("reduced") => Self::Reduced,
+            Some("strong") => Self::Strong,
+            _ => Self::Moderate,
+        }
+    }
+
+    /// Factor on the speech rate.
+    pub fn speed(self) -> f32 {
+        match self {
+            Self::None => 1.0,
+            Self::Reduced => 1.1,
+            Self::Moderate => 0.85,
+            Self::Strong => 0.7,
+        }
+    }
+
+    /// Factor on the level.
+    pub fn gain(self) -> f32 {
+        match self {
+            Self::Reduced => 0.7,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Text read the same way, after `pause_ms` of silence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub emphasis: Emphasis,
+    pub pause_ms: u32,
+}
+
+impl Span {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            emphasis: Emphasis::None,
+            pause_ms: 0,
+        }
+    }
+}
+
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    attributes: Vec<(String, String)>,
+    source: &'a str,
+}
+
+impl Tag<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The tag at the start of `text`, if it looks like one, and its length.
+fn parse_tag(text: &str) -> Option<(Tag<'_>, usize)> {
+    let end = text.find('>')? + 1;
+    let source = &text[..end];
+    // As in XML, the name follows `<` or `</` directly, which keeps
+    // comparisons like `1 < 2` as text.
+    let inner = source[1..end - 1]
+        .trim_end()
+        .trim_end_matches('/')
+        .trim_end();
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let name_end = inner
+        .find(|ch: char| ch.is_whitespace())
+        .unwrap_or(inner.len());
+    let name = &inner[..name_end];
+    if !name.starts_with(|ch: char| ch.is_ascii_alphabetic())
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | ':'))
+    {
+        return None;
+    }
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while let Some(equals) = rest.find('=') {
+        let key = rest[..equals].trim().to_ascii_lowercase();
+        let value = rest[equals + 1..].trim_start();
+        let quote = value.chars().next().filter(|ch| matches!(ch, '"' | '\''));
+        let (value, next) = match quote {
+            Some(quote) => {
+                let close = value[1..].find(quote)? + 1;
+                (&value[1..close], &value[close + 1..])
+            }
+            None => {
+                let close = value
+                    .find(|ch: char| ch.is_whitespace())
+                    .unwrap_or(value.len());
+                (&value[..close], &value[close..])
+            }
+        };
+        attributes.push((key, decode_entities(value)));
+        rest = next.trim_start();
+    }
+    Some((
+        Tag {
+            name: name.to_ascii_lowercase(),
+            closing,
+            attributes,
+            source,
+        },
+        end,
+    ))
+}
+
+/// `&amp;` last, so that `&amp;lt;` stays `&lt;`.
+const ENTITIES: [(&str, &str); 5] = [
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&quot;", "\""),
+    ("&apos;", "'"),
+    ("&amp;", "&"),
+];
+
+fn decode_entities(text: &str) -> String {
+    ENTITIES
+        .iter()
+        .fold(text.to_string(), |text, (entity, ch)| {
+            text.replace(entity, ch)
+        })
+}
+
+/// For every character of `markup`, whether it belongs to a tag or an
+/// entity rather than to the text read.
+pub fn markup_chars(markup: &str) -> Vec<bool> {
+    let mut flags = Vec::new();
+    let mut rest = markup;
+    while let Some(ch) = rest.chars().next() {
+        let length = match ch {
+            '<' => parse_tag(rest).map(|(_, length)| length),
+            '&' => ENTITIES
+                .iter()
+                .find(|(entity, _)| rest.starts_with(entity))
+                .map(|(entity, _)| entity.len()),
+            _ => None,
+        };
+        match length {
+            Some(length) => {
+                flags.extend(std::iter::repeat(true).take(rest[..length].chars().count()));
+                rest = &rest[length..];
+            }
+            None => {
+                flags.push(false);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+    flags
+}
+
+/// `500ms`, `1.5s` or a bare number of milliseconds.
+fn parse_duration(time: &str) -> Option<u32> {
+    let time = time.trim();
+    let (number, scale) = if let Some(number) = time.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = time.strip_suffix('s') {
+        (number, 1000.0)
+    } else {
+        (time, 1.0)
+    };
+    let ms = number.trim().parse::<f64>().ok()? * scale;
+    (ms.is_finite() && ms >= 0.0).then(|| (ms.round() as u32).min(MAX_BREAK_MS))
+}
+
+fn break_ms(tag: &Tag) -> u32 {
+    if let Some(ms) = tag.attribute("time").and_then(parse_duration) {
+        return ms;
+    }
+    match tag.attribute("strength") {
+        Some("none") => 0,
+        Some("x-weak") => 100,
+        Some("weak") => 250,
+        Some("strong") => 1000,
+        Some("x-strong") => 2000,
+        _ => 500,
+    }
+}
+
+/// Letters and digits of `text` one by one, letters in capitals so that
+/// they are read as letter names rather than words like "a".
+pub fn spell(text: &str) -> String {
+    text.chars()
+        .filter(|ch| ch.is_alphanumeric())
+        .map(|ch| ch.to_uppercase().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn push_text(spans: &mut Vec<Span>, text: &str, emphasis: Emphasis, pause_ms: &mut u32) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.emphasis == emphasis && *pause_ms == 0 => last.text.push_str(text),
+        _ => spans.push(Span {
+            text: text.to_string(),
+            emphasis,
+            pause_ms: std::mem::take(pause_ms),
+        }),
+    }
+}
+
+/// Splits `markup` into spans; see the module comment. A `<` that does not
+/// start a tag is read as text, and a pause at the very end is dropped.
+pub fn parse(markup: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut emphasis = vec![Emphasis::None];
+    // The interpretation of the open `<say-as>` and the text it collected.
+    let mut say_as: Option<(String, String)> = None;
+    let mut pause_ms = 0;
+    let mut rest = markup;
+    while !rest.is_empty() {
+        let text_end = rest.find('<').unwrap_or(rest.len());
+        let (text, tag) = match rest[text_end..].starts_with('<') {
+            true => match parse_tag(&rest[text_end..]) {
+                Some((tag, length)) => {
+                    let text = &rest[..text_end];
+                    rest = &rest[text_end + length..];
+                    (text, Some(tag))
+                }
+                None => {
+                    let (text, next) = rest.split_at(text_end + 1);
+                    rest = next;
+                    (text, None)
+                }
+            },
+            false => {
+                let text = rest;
+                rest = "";
+                (text, None)
+            }
+        };
+        let current = *emphasis.last().unwrap_or(&Emphasis::None);
+        match say_as.as_mut() {
+            Some((_, collected)) => collected.push_str(text),
+            None => push_text(&mut spans, &decode_entities(text), current, &mut pause_ms),
+        }
+        let Some(tag) = tag else {
+            continue;
+        };
+        match (tag.name.as_str(), tag.closing) {
+            ("break", false) => pause_ms += break_ms(&tag),
+            ("emphasis", false) if !tag.source.ends_with("/>") => {
+                emphasis.push(Emphasis::from_level(tag.attribute("level")))
+            }
+            ("emphasis", true) if emphasis.len() > 1 => {
+                emphasis.pop();
+            }
+            ("say-as", false) if say_as.is_none() => {
+                let interpret_as = tag.attribute("interpret-as").unwrap_or_default();
+                say_as = Some((interpret_as.to_ascii_lowercase(), String::new()));
+            }
+            ("say-as", true) => {
+                if let Some((interpret_as, collected)) = say_as.take() {
+                    let collected = decode_entities(&collected);
+                    let text = match interpret_as.as_str() {
+                        "characters" | "spell-out" | "letters" | "digits" | "verbatim" => {
+                            // Spaces keep the spelling apart from its neighbours.
+                            format!(" {} ", spell(&collected))
+                        }
+                        _ => collected,
+                    };
+                    push_text(&mut spans, &text, current, &mut pause_ms);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((_, collected)) = say_as {
+        let current = *emphasis.last().unwrap_or(&Emphasis::None);
+        push_text(
+            &mut spans,
+            &decode_entities(&collected),
+            current,
+            &mut pause_ms,
+        );
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str, emphasis: Emphasis, pause_ms: u32) -> Span {
+        Span {
+            text: text.to_string(),
+            emphasis,
+            pause_ms,
+        }
+    }
+
+    #[test]
+    fn parses_breaks_emphasis_and_spelling() {
+        assert_eq!(
+            parse(
+                "<speak>Write <break time=\"1.5s\"/>the word \
+                 <emphasis level='strong'>necessary</emphasis>, spelled \
+                 <say-as interpret-as=\"characters\">nec</say-as>.<break/></speak>"
+            ),
+            vec![
+                span("Write ", Emphasis::None, 0),
+                span("the word ", Emphasis::None, 1500),
+                span("necessary", Emphasis::Strong, 0),
+                span(", spelled  N, E, C .", Emphasis::None, 0),
+            ]
+        );
+        assert_eq!(
+            parse("a<break strength=\"weak\"/><break time=\"300ms\"/>b"),
+            vec![span("a", Emphasis::None, 0), span("b", Emphasis::None, 550)]
+        );
+        assert_eq!(
+            parse("<break time=\"1h\"/>x<break time=\"60s\"/>y"),
+            vec![
+                span("x", Emphasis::None, 500),
+                span("y", Emphasis::None, MAX_BREAK_MS),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_text_that_is_not_markup() {
+        assert_eq!(
+            parse("1 < 2 &amp; 3 > 2 <b>bold</b> <emphasis>"),
+            vec![span("1 < 2 & 3 > 2 bold ", Emphasis::None, 0)]
+        );
+        assert_eq!(
+            parse("<say-as interpret-as=\"date\">2024-05-01</say-as> ok"),
+            vec![span("2024-05-01 ok", Emphasis::None, 0)]
+        );
+        assert_eq!(
+            parse("<emphasis level=\"reduced\">aside</emphasis> main"),
+            vec![
+                span("aside", Emphasis::Reduced, 0),
+                span(" main", Emphasis::None, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,2070 @@
+// Text normalization in front of local TTS. The models read what they are
+// given, so LLM output has to be turned into speakable text first: markdown
+// syntax and emoji are dropped, links and e-mail addresses are reduced to
+// their readable parts, and numbers, dates, times, currency and units are
+// spelled out in English, Chinese, German, French or Spanish. Other languages
+// only get the cleanup and leave numbers to the model.
+
+/// How numbers and symbols are read aloud.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reading {
+    English,
+    Chinese,
+    German,
+    French,
+    Spanish,
+    /// Only the cleanup; numbers are left as they are.
+    Other,
+}
+
+impl Reading {
+    /// Han text is read as Chinese unless it is Japanese, as `route` does.
+    pub fn for_text(text: &str, lang: &str) -> Self {
+        let primary = lang.split(['-', '_']).next().unwrap_or(lang);
+        if matches!(primary, "zh" | "yue" | "lzh")
+            || (primary != "ja" && text.chars().any(crate::tts::is_han))
+        {
+            Self::Chinese
+        } else {
+            match primary {
+                "en" => Self::English,
+                "de" => Self::German,
+                "fr" => Self::French,
+                "es" => Self::Spanish,
+                _ => Self::Other,
+            }
+        }
+    }
+
+    fn words(self) -> Option<&'static Words> {
+        match self {
+            Self::German => Some(&GERMAN),
+            Self::French => Some(&FRENCH),
+            Self::Spanish => Some(&SPANISH),
+            _ => None,
+        }
+    }
+}
+
+/// Makes markdown-free `text` in `lang` speakable; see the module comment.
+/// Mixed-language text is stripped once with `strip_markdown` and then
+/// verbalized run by run, so each run is read in its own language.
+pub fn verbalize(text: &str, lang: &str) -> String {
+    let reading = Reading::for_text(text, lang);
+    let text = verbalize_links(text, reading);
+    let text = match reading {
+        Reading::English => expand_abbreviations(&text),
+        Reading::Chinese => text.replace('&', "和"),
+        _ => match reading.words() {
+            Some(words) => text.replace('&', words.ampersand),
+            None => text,
+        },
+    };
+    let text = match reading {
+        Reading::Other => text,
+        _ => expand_numbers(&text, reading),
+    };
+    collapse_spaces(&text)
+}
+
+fn is_emoji(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x2190..=0x21FF
+            | 0x2500..=0x257F
+            | 0xFE0E..=0xFE0F
+            | 0x200D
+            | 0x20E3
+            | 0xE0020..=0xE007F
+    )
+}
+
+/// The text of a markdown line without its block syntax, or `None` for lines
+/// with nothing to read, like rules and table separators.
+fn strip_block_syntax(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let is_rule = trimmed.len() >= 3
+        && ['-', '*', '_']
+            .into_iter()
+            .any(|marker| trimmed.chars().all(|ch| ch == marker || ch == ' '));
+    let is_table_separator = trimmed.starts_with('|')
+        && trimmed
+            .chars()
+            .all(|ch| matches!(ch, '|' | '-' | ':' | ' '));
+    if is_rule || is_table_separator {
+        return None;
+    }
+    let mut rest = trimmed;
+    loop {
+        let stripped = rest
+            .strip_prefix("> ")
+            .or_else(|| rest.strip_prefix('>'))
+            .map(str::trim_start);
+        match stripped {
+            Some(stripped) => rest = stripped,
+            None => break,
+        }
+    }
+    let heading = rest.trim_start_matches('#');
+    if heading.len() < rest.len() && rest.len() - heading.len() <= 6 && heading.starts_with(' ') {
+        rest = heading.trim_start();
+    }
+    for marker in ["- [ ] ", "- [x] ", "- ", "* ", "+ "] {
+        if let Some(stripped) = rest.strip_prefix(marker) {
+            rest = stripped;
+            break;
+        }
+    }
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && rest[digits..].starts_with([')', '.']) && rest[digits + 1..].starts_with(' ') {
+        rest = rest[digits + 1..].trim_start();
+    }
+    if rest.starts_with('|') {
+        let cells = rest
+            .trim_matches('|')
+            .split('|')
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .collect::<Vec<_>>();
+        return Some(cells.join(", "));
+    }
+    Some(rest.to_string())
+}
+
+/// Drops inline markdown: emphasis markers, backticks, link targets, images
+/// and HTML tags, keeping the text a reader would see.
+fn strip_inline_syntax(line: &str) -> String {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(line.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let rest = &chars[index..];
+        // [text](target) and ![alt](target) keep only the text.
+        if ch == '[' || (ch == '!' && rest.get(1) == Some(&'[')) {
+            let open = if ch == '!' { index + 1 } else { index };
+            if let Some(close) = chars[open..].iter().position(|&c| c == ']') {
+                let close = open + close;
+                if chars.get(close + 1) == Some(&'(') {
+                    if let Some(end) = chars[close..].iter().position(|&c| c == ')') {
+                        output.extend(&chars[open + 1..close]);
+                        index = close + end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        // HTML tags such as <br> or </b>; autolinks keep their address.
+        if ch == '<' {
+            if let Some(end) = rest.iter().position(|&c| c == '>') {
+                let inner = rest[1..end].iter().collect::<String>();
+                if inner.contains("://") || inner.contains('@') {
+                    output.push_str(&inner);
+                    index += end + 1;
+                    continue;
+                }
+                let name = inner.trim_start_matches('/');
+                if name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '=' | '"' | '/'))
+                {
+                    output.push(' ');
+                    index += end + 1;
+                    continue;
+                }
+            }
+        }
+        match ch {
+            '`' => {}
+            '*' | '~' if rest.get(1) == Some(&ch) => index += 1,
+            '*' => {}
+            '_' if rest.get(1) == Some(&'_') => index += 1,
+            _ if is_emoji(ch) => {}
+            _ => output.push(ch),
+        }
+        index += 1;
+    }
+    output
+}
+
+/// Drops markdown syntax, HTML tags and emoji, keeping the text they mark up.
+pub fn strip_markdown(text: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some(line) = strip_block_syntax(line) {
+            lines.push(strip_inline_syntax(&line));
+        }
+    }
+    lines.join("\n")
+}
+
+fn is_url_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, '<' | '>' | '"' | '\'' | ')' | ']') && (ch as u32) < 0x2E80
+}
+
+fn is_email_local_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '%' | '+' | '-')
+}
+
+fn is_domain_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-')
+}
+
+fn spoken_dot(reading: Reading) -> &'static str {
+    match reading {
+        Reading::Chinese => " 点 ",
+        _ => reading.words().map_or(" dot ", |words| words.dot),
+    }
+}
+
+/// `www.example.com` as "example dot com".
+fn speak_host(host: &str, reading: Reading) -> String {
+    host.trim_start_matches("www.")
+        .split('.')
+        .filter(|label| !label.is_empty())
+        .collect::<Vec<_>>()
+        .join(spoken_dot(reading))
+}
+
+fn speak_email(local: &str, domain: &str, reading: Reading) -> String {
+    let mut local_spoken = String::new();
+    for ch in local.chars() {
+        match (ch, reading) {
+            ('.', _) => local_spoken.push_str(spoken_dot(reading)),
+            ('_', Reading::Chinese) => local_spoken.push_str(" 下划线 "),
+            ('_', _) => local_spoken.push_str(
+                reading
+                    .words()
+                    .map_or(" underscore ", |words| words.underscore),
+            ),
+            ('-', Reading::Chinese) => local_spoken.push_str(" 横线 "),
+            ('-', _) => local_spoken.push_str(reading.words().map_or(" dash ", |words| words.dash)),
+            _ => local_spoken.push(ch),
+        }
+    }
+    let at = reading.words().map_or(" at ", |words| words.at);
+    format!("{local_spoken}{at}{}", speak_host(domain, reading))
+}
+
+/// Reads links as their host and e-mail addresses as `name at host`; a path
+/// or query read character by character helps no one.
+fn verbalize_links(text: &str, reading: Reading) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let at_boundary = index == 0 || !chars[index - 1].is_ascii_alphanumeric();
+        let rest = chars[index..].iter().take(8).collect::<String>();
+        let scheme = ["https://", "http://", "www."]
+            .into_iter()
+            .find(|prefix| rest.to_ascii_lowercase().starts_with(prefix));
+        if let (true, Some(scheme)) = (at_boundary, scheme) {
+            let mut end = index
+                + chars[index..]
+                    .iter()
+                    .take_while(|&&c| is_url_char(c))
+                    .count();
+            while end > index && matches!(chars[end - 1], '.' | ',' | ';' | ':' | '!' | '?') {
+                end -= 1;
+            }
+            let url = chars[index..end].iter().collect::<String>();
+            let after_scheme = if scheme == "www." {
+                &url[..]
+            } else {
+                &url[scheme.len()..]
+            };
+            let host = after_scheme
+                .split(['/', ':', '?', '#'])
+                .next()
+                .unwrap_or_default();
+            if host.contains('.') || scheme != "www." {
+                output.push_str(&speak_host(host, reading));
+                index = end;
+                continue;
+            }
+        }
+        if chars[index] == '@' {
+            let local_len = output
+                .chars()
+                .rev()
+                .take_while(|&c| is_email_local_char(c))
+                .count();
+            let mut domain_end = index
+                + 1
+                + chars[index + 1..]
+                    .iter()
+                    .take_while(|&&c| is_domain_char(c))
+                    .count();
+            while domain_end > index + 1 && matches!(chars[domain_end - 1], '.' | '-') {
+                domain_end -= 1;
+            }
+            let domain = chars[index + 1..domain_end].iter().collect::<String>();
+            let tld_is_alphabetic = domain
+                .rsplit('.')
+                .next()
+                .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+            if local_len > 0 && domain.contains('.') && tld_is_alphabetic {
+                let split = output
+                    .char_indices()
+                    .rev()
+                    .nth(local_len - 1)
+                    .map(|(offset, _)| offset)
+                    .unwrap_or(0);
+                let local = output.split_off(split);
+                output.push_str(&speak_email(&local, &domain, reading));
+                index = domain_end;
+                continue;
+            }
+        }
+        output.push(chars[index]);
+        index += 1;
+    }
+    output
+}
+
+const ENGLISH_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("e.g.", "for example"),
+    ("i.e.", "that is"),
+    ("etc.", "et cetera"),
+    ("vs.", "versus"),
+    ("approx.", "approximately"),
+    ("Dr.", "Doctor"),
+    ("Mr.", "Mister"),
+    ("Mrs.", "Missus"),
+    ("Ms.", "Miz"),
+    ("&", "and"),
+];
+
+fn expand_abbreviations(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        let at_boundary = !output
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let found = ENGLISH_ABBREVIATIONS
+            .iter()
+            .find(|(abbreviation, _)| {
+                rest.starts_with(abbreviation)
+                    && !rest[abbreviation.len()..]
+                        .chars()
+                        .next()
+                        .is_some_and(char::is_alphanumeric)
+            })
+            .filter(|_| at_boundary);
+        match found {
+            Some((abbreviation, expansion)) => {
+                if *abbreviation == "&" {
+                    output.push(' ');
+                }
+                output.push_str(expansion);
+                if *abbreviation == "&" {
+                    output.push(' ');
+                }
+                rest = &rest[abbreviation.len()..];
+            }
+            None => {
+                output.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+    output
+}
+
+const EN_ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const EN_SCALES: [&str; 5] = ["", "thousand", "million", "billion", "trillion"];
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn en_below_hundred(n: u64) -> String {
+    match (n / 10, n % 10) {
+        (0 | 1, _) => EN_ONES[n as usize].to_string(),
+        (tens, 0) => EN_TENS[tens as usize].to_string(),
+        (tens, ones) => format!("{}-{}", EN_TENS[tens as usize], EN_ONES[ones as usize]),
+    }
+}
+
+fn en_below_thousand(n: u64) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => en_below_hundred(rest),
+        (hundreds, 0) => format!("{} hundred", EN_ONES[hundreds as usize]),
+        (hundreds, rest) => format!(
+            "{} hundred {}",
+            EN_ONES[hundreds as usize],
+            en_below_hundred(rest)
+        ),
+    }
+}
+
+/// Below 10^15, which covers anything worth reading as a quantity.
+fn en_cardinal(n: u64) -> String {
+    if n == 0 {
+        return "zero".to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 1000);
+        rest /= 1000;
+    }
+    groups
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, group)| **group > 0)
+        .map(|(scale, group)| match EN_SCALES[scale] {
+            "" => en_below_thousand(*group),
+            name => format!("{} {name}", en_below_thousand(*group)),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn en_ordinal(n: u64) -> String {
+    let cardinal = en_cardinal(n);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |index| index + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        _ if last.ends_with('y') => format!("{}ieth", &last[..last.len() - 1]),
+        _ => format!("{last}th"),
+    };
+    format!("{head}{last}")
+}
+
+/// 1999 as "nineteen ninety-nine", 2005 as "two thousand five".
+fn en_year(year: u64) -> String {
+    let (century, rest) = (year / 100, year % 100);
+    match rest {
+        _ if (2000..2010).contains(&year) => en_cardinal(year),
+        0 => format!("{} hundred", en_below_hundred(century)),
+        1..=9 => format!(
+            "{} oh {}",
+            en_below_hundred(century),
+            EN_ONES[rest as usize]
+        ),
+        _ => format!("{} {}", en_below_hundred(century), en_below_hundred(rest)),
+    }
+}
+
+const ZH_DIGITS: [char; 10] = ['零', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+const ZH_GROUP_UNITS: [&str; 4] = ["", "万", "亿", "万亿"];
+
+fn zh_group(n: u64, output: &mut String) {
+    let digits = [n / 1000, n / 100 % 10, n / 10 % 10, n % 10];
+    let mut started = false;
+    let mut zero = false;
+    for (digit, unit) in digits.into_iter().zip(["千", "百", "十", ""]) {
+        if digit == 0 {
+            zero |= started;
+            continue;
+        }
+        if zero {
+            output.push('零');
+            zero = false;
+        }
+        output.push(ZH_DIGITS[digit as usize]);
+        output.push_str(unit);
+        started = true;
+    }
+}
+
+/// Below 10^16, e.g. 20305 as 二万零三百零五.
+fn zh_cardinal(n: u64) -> String {
+    if n == 0 {
+        return "零".to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 10_000);
+        rest /= 10_000;
+    }
+    let mut output = String::new();
+    let mut zero = false;
+    for (scale, group) in groups.iter().enumerate().rev() {
+        if *group == 0 {
+            zero |= !output.is_empty();
+            continue;
+        }
+        if !output.is_empty() && (zero || *group < 1000) {
+            output.push('零');
+        }
+        zero = false;
+        zh_group(*group, &mut output);
+        output.push_str(ZH_GROUP_UNITS[scale]);
+    }
+    match output.strip_prefix("一十") {
+        Some(rest) => format!("十{rest}"),
+        None => output,
+    }
+}
+
+/// Symbol, one and many of the currency, one and many of its subunit (empty
+/// without one).
+type Currency = (char, &'static str, &'static str, &'static str, &'static str);
+
+/// Words for reading numbers in German, French and Spanish, which share the
+/// English rules but not the words.
+struct Words {
+    cardinal: fn(u64) -> String,
+    /// Also given the word before the number, for German case endings.
+    ordinal: fn(u64, &str) -> String,
+    /// A count in front of a noun, e.g. "einundzwanzig" as "einundzwanzig"
+    /// but "hunderteins" as "hundertein".
+    before_noun: fn(String) -> String,
+    year: fn(u64) -> String,
+    /// Day, month name, year and the word before the date.
+    date: fn(u64, &str, u64, &str) -> String,
+    time: fn(u64, u64) -> String,
+    months: [&'static str; 12],
+    minus: &'static str,
+    point: &'static str,
+    percent: &'static str,
+    range: &'static str,
+    /// Joins an amount and its cents.
+    and: &'static str,
+    ampersand: &'static str,
+    dot: &'static str,
+    at: &'static str,
+    underscore: &'static str,
+    dash: &'static str,
+    /// Thousands separators; the decimal separator is a comma, or a point
+    /// not followed by three digits.
+    grouping: &'static [char],
+    currencies: &'static [Currency],
+    /// Abbreviation, one and many, longer abbreviations first as in `UNITS`.
+    units: &'static [(&'static str, &'static str, &'static str)],
+}
+
+const DE_ONES: [&str; 20] = [
+    "null",
+    "eins",
+    "zwei",
+    "drei",
+    "vier",
+    "fünf",
+    "sechs",
+    "sieben",
+    "acht",
+    "neun",
+    "zehn",
+    "elf",
+    "zwölf",
+    "dreizehn",
+    "vierzehn",
+    "fünfzehn",
+    "sechzehn",
+    "siebzehn",
+    "achtzehn",
+    "neunzehn",
+];
+const DE_TENS: [&str; 10] = [
+    "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig",
+];
+
+fn de_below_hundred(n: u64) -> String {
+    match (n / 10, n % 10) {
+        (0 | 1, _) => DE_ONES[n as usize].to_string(),
+        (tens, 0) => DE_TENS[tens as usize].to_string(),
+        (tens, 1) => format!("einund{}", DE_TENS[tens as usize]),
+        (tens, ones) => format!("{}und{}", DE_ONES[ones as usize], DE_TENS[tens as usize]),
+    }
+}
+
+fn de_below_thousand(n: u64) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => de_below_hundred(rest),
+        (hundreds, rest) => {
+            let head = if hundreds == 1 {
+                "ein"
+            } else {
+                DE_ONES[hundreds as usize]
+            };
+            let rest = if rest == 0 {
+                String::new()
+            } else {
+                de_below_hundred(rest)
+            };
+            format!("{head}hundert{rest}")
+        }
+    }
+}
+
+/// "eins" is "ein" in front of another word.
+fn de_before_noun(words: String) -> String {
+    match words.strip_suffix("eins") {
+        Some(head) => format!("{head}ein"),
+        None => words,
+    }
+}
+
+/// Below 10^15; everything under a million is one word.
+fn de_cardinal(n: u64) -> String {
+    if n == 0 {
+        return "null".to_string();
+    }
+    let mut parts = Vec::new();
+    for (scale, one, many) in [
+        (1_000_000_000_000, "eine Billion", "Billionen"),
+        (1_000_000_000, "eine Milliarde", "Milliarden"),
+        (1_000_000, "eine Million", "Millionen"),
+    ] {
+        match n / scale % 1000 {
+            0 => {}
+            1 => parts.push(one.to_string()),
+            count => parts.push(format!(
+                "{} {many}",
+                de_before_noun(de_below_thousand(count))
+            )),
+        }
+    }
+    let (thousands, rest) = (n / 1000 % 1000, n % 1000);
+    let mut word = String::new();
+    if thousands > 0 {
+        word.push_str(&de_before_noun(de_below_thousand(thousands)));
+        word.push_str("tausend");
+    }
+    if rest > 0 {
+        word.push_str(&de_below_thousand(rest));
+    }
+    if !word.is_empty() {
+        parts.push(word);
+    }
+    parts.join(" ")
+}
+
+/// "dritte" after "der", "dritten" after "am" and the like, else "dritter".
+fn de_ordinal(n: u64, previous: &str) -> String {
+    let cardinal = de_cardinal(n);
+    let last = n % 100;
+    let stem = if last == 0 || last >= 20 {
+        format!("{cardinal}ste")
+    } else {
+        let head = &cardinal[..cardinal.len() - DE_ONES[last as usize].len()];
+        match last {
+            1 => format!("{head}erste"),
+            3 => format!("{head}dritte"),
+            7 => format!("{head}siebte"),
+            8 => format!("{head}achte"),
+            _ => format!("{cardinal}te"),
+        }
+    };
+    match previous.to_lowercase().as_str() {
+        "der" | "die" | "das" => stem,
+        "am" | "vom" | "zum" | "im" | "beim" | "dem" | "den" | "bis" | "seit" | "ab" => {
+            format!("{stem}n")
+        }
+        _ => format!("{stem}r"),
+    }
+}
+
+/// 1999 as "neunzehnhundertneunundneunzig", 2024 as a plain number.
+fn de_year(year: u64) -> String {
+    match year {
+        1100..=1999 => format!(
+            "{}hundert{}",
+            de_below_hundred(year / 100),
+            if year % 100 == 0 {
+                String::new()
+            } else {
+                de_below_hundred(year % 100)
+            }
+        ),
+        _ => de_cardinal(year),
+    }
+}
+
+fn de_date(day: u64, month: &str, year: u64, previous: &str) -> String {
+    format!("{} {month} {}", de_ordinal(day, previous), de_year(year))
+}
+
+fn de_time(hour: u64, minute: u64) -> String {
+    let hour = de_before_noun(de_cardinal(hour));
+    match minute {
+        0 => format!("{hour} Uhr"),
+        _ => format!("{hour} Uhr {}", de_cardinal(minute)),
+    }
+}
+
+static GERMAN: Words = Words {
+    cardinal: de_cardinal,
+    ordinal: de_ordinal,
+    before_noun: de_before_noun,
+    year: de_year,
+    date: de_date,
+    time: de_time,
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    minus: "minus ",
+    point: " Komma ",
+    percent: "Prozent",
+    range: "bis",
+    and: "und",
+    ampersand: " und ",
+    dot: " Punkt ",
+    at: " at ",
+    underscore: " Unterstrich ",
+    dash: " Bindestrich ",
+    grouping: &['.', '\u{a0}', '\u{202f}'],
+    currencies: &[
+        ('$', "ein Dollar", "Dollar", "ein Cent", "Cent"),
+        ('€', "ein Euro", "Euro", "ein Cent", "Cent"),
+        ('£', "ein Pfund", "Pfund", "ein Penny", "Pence"),
+        ('¥', "ein Yen", "Yen", "", ""),
+        ('￥', "ein Yuan", "Yuan", "", ""),
+    ],
+    units: &[
+        ("km/h", "ein Kilometer pro Stunde", "Kilometer pro Stunde"),
+        ("mph", "eine Meile pro Stunde", "Meilen pro Stunde"),
+        ("km", "ein Kilometer", "Kilometer"),
+        ("cm", "ein Zentimeter", "Zentimeter"),
+        ("mm", "ein Millimeter", "Millimeter"),
+        ("kg", "ein Kilogramm", "Kilogramm"),
+        ("mg", "ein Milligramm", "Milligramm"),
+        ("ml", "ein Milliliter", "Milliliter"),
+        ("ms", "eine Millisekunde", "Millisekunden"),
+        ("°C", "ein Grad Celsius", "Grad Celsius"),
+        ("°F", "ein Grad Fahrenheit", "Grad Fahrenheit"),
+        ("TB", "ein Terabyte", "Terabyte"),
+        ("GB", "ein Gigabyte", "Gigabyte"),
+        ("MB", "ein Megabyte", "Megabyte"),
+        ("KB", "ein Kilobyte", "Kilobyte"),
+        ("GHz", "ein Gigahertz", "Gigahertz"),
+        ("MHz", "ein Megahertz", "Megahertz"),
+        ("kHz", "ein Kilohertz", "Kilohertz"),
+        ("Hz", "ein Hertz", "Hertz"),
+    ],
+};
+
+const FR_ONES: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix", "onze",
+    "douze", "treize", "quatorze", "quinze", "seize",
+];
+const FR_TENS: [&str; 7] = [
+    "",
+    "dix",
+    "vingt",
+    "trente",
+    "quarante",
+    "cinquante",
+    "soixante",
+];
+
+/// 70 and 90 count on from 60 and 80: soixante-douze, quatre-vingt-onze.
+fn fr_below_hundred(n: u64) -> String {
+    let (tens, ones) = (n / 10, n % 10);
+    match (tens, ones) {
+        _ if n <= 16 => FR_ONES[n as usize].to_string(),
+        (1, _) => format!("dix-{}", FR_ONES[ones as usize]),
+        (7, 1) => "soixante et onze".to_string(),
+        (7, _) => format!("soixante-{}", fr_below_hundred(10 + ones)),
+        (8, 0) => "quatre-vingts".to_string(),
+        (8, _) => format!("quatre-vingt-{}", FR_ONES[ones as usize]),
+        (9, _) => format!("quatre-vingt-{}", fr_below_hundred(10 + ones)),
+        (_, 0) => FR_TENS[tens as usize].to_string(),
+        (_, 1) => format!("{} et un", FR_TENS[tens as usize]),
+        _ => format!("{}-{}", FR_TENS[tens as usize], FR_ONES[ones as usize]),
+    }
+}
+
+fn fr_below_thousand(n: u64) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => fr_below_hundred(rest),
+        (1, 0) => "cent".to_string(),
+        (1, rest) => format!("cent {}", fr_below_hundred(rest)),
+        (hundreds, 0) => format!("{} cents", FR_ONES[hundreds as usize]),
+        (hundreds, rest) => format!(
+            "{} cent {}",
+            FR_ONES[hundreds as usize],
+            fr_below_hundred(rest)
+        ),
+    }
+}
+
+/// Below 10^15. "cents" and "quatre-vingts" lose their s before "mille".
+fn fr_cardinal(n: u64) -> String {
+    if n == 0 {
+        return "zéro".to_string();
+    }
+    let mut parts = Vec::new();
+    for (scale, name) in [
+        (1_000_000_000_000, "billion"),
+        (1_000_000_000, "milliard"),
+        (1_000_000, "million"),
+    ] {
+        match n / scale % 1000 {
+            0 => {}
+            1 => parts.push(format!("un {name}")),
+            count => parts.push(format!("{} {name}s", fr_below_thousand(count))),
+        }
+    }
+    match n / 1000 % 1000 {
+        0 => {}
+        1 => parts.push("mille".to_string()),
+        count => {
+            let count = fr_below_thousand(count);
+            let count = match count.ends_with("cents") || count.ends_with("vingts") {
+                true => &count[..count.len() - 1],
+                false => &count,
+            };
+            parts.push(format!("{count} mille"));
+        }
+    }
+    if n % 1000 > 0 {
+        parts.push(fr_below_thousand(n % 1000));
+    }
+    parts.join(" ")
+}
+
+fn fr_ordinal(n: u64, _previous: &str) -> String {
+    if n == 1 {
+        return "premier".to_string();
+    }
+    let cardinal = fr_cardinal(n);
+    let stem = if let Some(head) = cardinal.strip_suffix("cinq") {
+        format!("{head}cinqu")
+    } else if let Some(head) = cardinal.strip_suffix("neuf") {
+        format!("{head}neuv")
+    } else if cardinal.ends_with("cents") || cardinal.ends_with("vingts") || cardinal.ends_with('e')
+    {
+        cardinal[..cardinal.len() - 1].to_string()
+    } else {
+        cardinal
+    };
+    format!("{stem}ième")
+}
+
+/// Hours are feminine: "une heure", "vingt et une heures".
+fn fr_feminine(words: String) -> String {
+    match words.strip_suffix("un") {
+        Some(head) if head.is_empty() || head.ends_with(' ') => format!("{head}une"),
+        _ => words,
+    }
+}
+
+fn fr_date(day: u64, month: &str, year: u64, _previous: &str) -> String {
+    let day = if day == 1 {
+        "premier".to_string()
+    } else {
+        fr_cardinal(day)
+    };
+    format!("{day} {month} {}", fr_cardinal(year))
+}
+
+fn fr_time(hour: u64, minute: u64) -> String {
+    let unit = if hour <= 1 { "heure" } else { "heures" };
+    let hour = fr_feminine(fr_cardinal(hour));
+    match minute {
+        0 => format!("{hour} {unit}"),
+        _ => format!("{hour} {unit} {}", fr_feminine(fr_cardinal(minute))),
+    }
+}
+
+static FRENCH: Words = Words {
+    cardinal: fr_cardinal,
+    ordinal: fr_ordinal,
+    before_noun: |words| words,
+    year: fr_cardinal,
+    date: fr_date,
+    time: fr_time,
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    minus: "moins ",
+    point: " virgule ",
+    percent: "pour cent",
+    range: "à",
+    and: "et",
+    ampersand: " et ",
+    dot: " point ",
+    at: " arobase ",
+    underscore: " tiret bas ",
+    dash: " tiret ",
+    grouping: &[' ', '\u{a0}', '\u{202f}'],
+    currencies: &[
+        ('$', "un dollar", "dollars", "un cent", "cents"),
+        ('€', "un euro", "euros", "un centime", "centimes"),
+        ('£', "une livre", "livres", "un penny", "pence"),
+        ('¥', "un yen", "yens", "", ""),
+        ('￥', "un yuan", "yuans", "", ""),
+    ],
+    units: &[
+        ("km/h", "un kilomètre par heure", "kilomètres par heure"),
+        ("mph", "un mile par heure", "miles par heure"),
+        ("km", "un kilomètre", "kilomètres"),
+        ("cm", "un centimètre", "centimètres"),
+        ("mm", "un millimètre", "millimètres"),
+        ("kg", "un kilogramme", "kilogrammes"),
+        ("mg", "un milligramme", "milligrammes"),
+        ("ml", "un millilitre", "millilitres"),
+        ("ms", "une milliseconde", "millisecondes"),
+        ("°C", "un degré Celsius", "degrés Celsius"),
+        ("°F", "un degré Fahrenheit", "degrés Fahrenheit"),
+        ("TB", "un téraoctet", "téraoctets"),
+        ("GB", "un gigaoctet", "gigaoctets"),
+        ("MB", "un mégaoctet", "mégaoctets"),
+        ("KB", "un kilooctet", "kilooctets"),
+        ("GHz", "un gigahertz", "gigahertz"),
+        ("MHz", "un mégahertz", "mégahertz"),
+        ("kHz", "un kilohertz", "kilohertz"),
+        ("Hz", "un hertz", "hertz"),
+    ],
+};
+
+const ES_ONES: [&str; 30] = [
+    "cero",
+    "uno",
+    "dos",
+    "tres",
+    "cuatro",
+    "cinco",
+    "seis",
+    "siete",
+    "ocho",
+    "nueve",
+    "diez",
+    "once",
+    "doce",
+    "trece",
+    "catorce",
+    "quince",
+    "dieciséis",
+    "diecisiete",
+    "dieciocho",
+    "diecinueve",
+    "veinte",
+    "veintiuno",
+    "veintidós",
+    "veintitrés",
+    "veinticuatro",
+    "veinticinco",
+    "veintiséis",
+    "veintisiete",
+    "veintiocho",
+    "veintinueve",
+];
+const ES_TENS: [&str; 10] = [
+    "",
+    "",
+    "",
+    "treinta",
+    "cuarenta",
+    "cincuenta",
+    "sesenta",
+    "setenta",
+    "ochenta",
+    "noventa",
+];
+const ES_HUNDREDS: [&str; 10] = [
+    "",
+    "ciento",
+    "doscientos",
+    "trescientos",
+    "cuatrocientos",
+    "quinientos",
+    "seiscientos",
+    "setecientos",
+    "ochocientos",
+    "novecientos",
+];
+const ES_ORDINALS: [&str; 11] = [
+    "", "primero", "segundo", "tercero", "cuarto", "quinto", "sexto", "séptimo", "octavo",
+    "noveno", "décimo",
+];
+
+fn es_below_hundred(n: u64) -> String {
+    match n {
+        0..=29 => ES_ONES[n as usize].to_string(),
+        _ if n % 10 == 0 => ES_TENS[n as usize / 10].to_string(),
+        _ => format!(
+            "{} y {}",
+            ES_TENS[n as usize / 10],
+            ES_ONES[n as usize % 10]
+        ),
+    }
+}
+
+fn es_below_thousand(n: u64) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => es_below_hundred(rest),
+        (1, 0) => "cien".to_string(),
+        (hundreds, 0) => ES_HUNDREDS[hundreds as usize].to_string(),
+        (hundreds, rest) => format!(
+            "{} {}",
+            ES_HUNDREDS[hundreds as usize],
+            es_below_hundred(rest)
+        ),
+    }
+}
+
+/// "uno" is "un" in front of a noun: veintiún mil, un millón.
+fn es_before_noun(words: String) -> String {
+    if let Some(head) = words.strip_suffix("veintiuno") {
+        format!("{head}veintiún")
+    } else if let Some(head) = words.strip_suffix("uno") {
+        format!("{head}un")
+    } else {
+        words
+    }
+}
+
+fn es_below_million(n: u64) -> String {
+    let (thousands, rest) = (n / 1000, n % 1000);
+    let mut parts = Vec::new();
+    match thousands {
+        0 => {}
+        1 => parts.push("mil".to_string()),
+        _ => parts.push(format!(
+            "{} mil",
+            es_before_noun(es_below_thousand(thousands))
+        )),
+    }
+    if rest > 0 || thousands == 0 {
+        parts.push(es_below_thousand(rest));
+    }
+    parts.join(" ")
+}
+
+/// Below 10^15, on the long scale: a thousand millions, then "billón".
+fn es_cardinal(n: u64) -> String {
+    let mut parts = Vec::new();
+    for (scale, one, many) in [
+        (1_000_000_000_000, "un billón", "billones"),
+        (1_000_000, "un millón", "millones"),
+    ] {
+        match n / scale % 1_000_000 {
+            0 => {}
+            1 => parts.push(one.to_string()),
+            count => parts.push(format!(
+                "{} {many}",
+                es_before_noun(es_below_million(count))
+            )),
+        }
+    }
+    if n % 1_000_000 > 0 || parts.is_empty() {
+        parts.push(es_below_million(n % 1_000_000));
+    }
+    parts.join(" ")
+}
+
+fn es_ordinal(n: u64, _previous: &str) -> String {
+    match ES_ORDINALS.get(n as usize) {
+        Some(ordinal) if n > 0 => ordinal.to_string(),
+        _ => es_cardinal(n),
+    }
+}
+
+/// Hours are feminine: "la una", "las veintiuna".
+fn es_hour(hour: u64) -> String {
+    let hour = es_cardinal(hour);
+    match hour.strip_suffix("uno") {
+        Some(head) => format!("{head}una"),
+        None => hour,
+    }
+}
+
+fn es_date(day: u64, month: &str, year: u64, _previous: &str) -> String {
+    let day = if day == 1 {
+        "primero".to_string()
+    } else {
+        es_cardinal(day)
+    };
+    format!("{day} de {month} de {}", es_cardinal(year))
+}
+
+fn es_time(hour: u64, minute: u64) -> String {
+    match minute {
+        0 => format!("{} en punto", es_hour(hour)),
+        _ => format!("{} y {}", es_hour(hour), es_cardinal(minute)),
+    }
+}
+
+static SPANISH: Words = Words {
+    cardinal: es_cardinal,
+    ordinal: es_ordinal,
+    before_noun: es_before_noun,
+    year: es_cardinal,
+    date: es_date,
+    time: es_time,
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    minus: "menos ",
+    point: " coma ",
+    percent: "por ciento",
+    range: "a",
+    and: "con",
+    ampersand: " y ",
+    dot: " punto ",
+    at: " arroba ",
+    underscore: " guion bajo ",
+    dash: " guion ",
+    grouping: &['.', '\u{a0}', '\u{202f}'],
+    currencies: &[
+        ('$', "un dólar", "dólares", "un centavo", "centavos"),
+        ('€', "un euro", "euros", "un céntimo", "céntimos"),
+        ('£', "una libra", "libras", "un penique", "peniques"),
+        ('¥', "un yen", "yenes", "", ""),
+        ('￥', "un yuan", "yuanes", "", ""),
+    ],
+    units: &[
+        ("km/h", "un kilómetro por hora", "kilómetros por hora"),
+        ("mph", "una milla por hora", "millas por hora"),
+        ("km", "un kilómetro", "kilómetros"),
+        ("cm", "un centímetro", "centímetros"),
+        ("mm", "un milímetro", "milímetros"),
+        ("kg", "un kilogramo", "kilogramos"),
+        ("mg", "un miligramo", "miligramos"),
+        ("ml", "un mililitro", "mililitros"),
+        ("ms", "un milisegundo", "milisegundos"),
+        ("°C", "un grado Celsius", "grados Celsius"),
+        ("°F", "un grado Fahrenheit", "grados Fahrenheit"),
+        ("TB", "un terabyte", "terabytes"),
+        ("GB", "un gigabyte", "gigabytes"),
+        ("MB", "un megabyte", "megabytes"),
+        ("KB", "un kilobyte", "kilobytes"),
+        ("GHz", "un gigahercio", "gigahercios"),
+        ("MHz", "un megahercio", "megahercios"),
+        ("kHz", "un kilohercio", "kilohercios"),
+        ("Hz", "un hercio", "hercios"),
+    ],
+};
+
+fn digit_by_digit(digits: &str, reading: Reading) -> String {
+    let words = digits.chars().filter_map(|ch| ch.to_digit(10));
+    match reading {
+        Reading::Chinese => words.map(|digit| ZH_DIGITS[digit as usize]).collect(),
+        _ => words
+            .map(|digit| match reading.words() {
+                Some(words) => (words.cardinal)(digit as u64),
+                None => EN_ONES[digit as usize].to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// A number as written: digits without grouping commas, and the digits after
+/// the decimal point.
+#[derive(Debug)]
+struct Number {
+    negative: bool,
+    integer: String,
+    fraction: Option<String>,
+}
+
+impl Number {
+    fn value(&self) -> Option<u64> {
+        self.integer.parse().ok()
+    }
+
+    /// Leading zeros and very long digit runs, like phone numbers and codes,
+    /// are read digit by digit.
+    fn is_code(&self, reading: Reading) -> bool {
+        let long = match reading {
+            Reading::Chinese => 11,
+            _ => 16,
+        };
+        (self.integer.len() > 1 && self.integer.starts_with('0')) || self.integer.len() >= long
+    }
+
+    fn cardinal(&self, reading: Reading) -> String {
+        let integer = match self.value() {
+            Some(_) if self.is_code(reading) => digit_by_digit(&self.integer, reading),
+            Some(value) if reading == Reading::Chinese => zh_cardinal(value),
+            Some(value) => match reading.words() {
+                Some(words) => (words.cardinal)(value),
+                None => en_cardinal(value),
+            },
+            None => digit_by_digit(&self.integer, reading),
+        };
+        let (minus, point) = match reading {
+            Reading::Chinese => ("负", "点"),
+            _ => reading
+                .words()
+                .map_or(("minus ", " point "), |words| (words.minus, words.point)),
+        };
+        let mut spoken = String::new();
+        if self.negative {
+            spoken.push_str(minus);
+        }
+        spoken.push_str(&integer);
+        if let Some(fraction) = &self.fraction {
+            spoken.push_str(point);
+            spoken.push_str(&digit_by_digit(fraction, reading));
+        }
+        spoken
+    }
+
+    fn is_one(&self) -> bool {
+        self.integer == "1" && self.fraction.is_none()
+    }
+
+    /// `one` or the number and `many`, e.g. "ein Euro" or "hundertein Euro".
+    fn count(&self, words: &Words, one: &str, many: &str, reading: Reading) -> String {
+        match self.fraction {
+            _ if self.is_one() => one.to_string(),
+            Some(_) => format!("{} {many}", self.cardinal(reading)),
+            None => format!("{} {many}", (words.before_noun)(self.cardinal(reading))),
+        }
+    }
+}
+
+/// Parses `1,234.5` or `-3` at `start`, returning the number and its end.
+/// Commas only count as grouping when followed by exactly three digits; in
+/// languages with `Words`, their grouping characters do, and commas and other
+/// points start the decimals, as in `1.234,5`.
+fn parse_number(chars: &[char], start: usize, reading: Reading) -> Option<(Number, usize)> {
+    let (grouping, decimal): (&[char], &[char]) = match reading.words() {
+        Some(words) => (words.grouping, &[',', '.']),
+        None => (&[','], &['.']),
+    };
+    let negative = chars.get(start) == Some(&'-');
+    let mut index = start + usize::from(negative);
+    if !chars.get(index).is_some_and(char::is_ascii_digit) {
+        return None;
+    }
+    let mut integer = String::new();
+    loop {
+        while let Some(ch) = chars.get(index).filter(|ch| ch.is_ascii_digit()) {
+            integer.push(*ch);
+            index += 1;
+        }
+        let grouped = chars.get(index).is_some_and(|ch| grouping.contains(ch))
+            && (1..=3).all(|offset| chars.get(index + offset).is_some_and(char::is_ascii_digit))
+            && !chars.get(index + 4).is_some_and(char::is_ascii_digit);
+        if !grouped {
+            break;
+        }
+        index += 1;
+    }
+    let mut fraction = None;
+    if chars.get(index).is_some_and(|ch| decimal.contains(ch))
+        && chars.get(index + 1).is_some_and(char::is_ascii_digit)
+    {
+        let digits = chars[index + 1..]
+            .iter()
+            .take_while(|ch| ch.is_ascii_digit())
+            .collect::<String>();
+        index += 1 + digits.len();
+        fraction = Some(digits);
+    }
+    Some((
+        Number {
+            negative,
+            integer,
+            fraction,
+        },
+        index,
+    ))
+}
+
+/// Fixed-width digits at `start`, e.g. the parts of `2024-03-15`.
+fn fixed_digits(chars: &[char], start: usize, len: usize) -> Option<u64> {
+    let digits = chars.get(start..start + len)?;
+    if !digits.iter().all(char::is_ascii_digit)
+        || chars.get(start + len).is_some_and(char::is_ascii_digit)
+    {
+        return None;
+    }
+    digits.iter().collect::<String>().parse().ok()
+}
+
+/// The word in front of `start`, skipping spaces.
+fn previous_word(chars: &[char], start: usize) -> String {
+    let before = &chars[..start];
+    let end = before.len() - before.iter().rev().take_while(|ch| **ch == ' ').count();
+    let length = before[..end]
+        .iter()
+        .rev()
+        .take_while(|ch| ch.is_alphabetic())
+        .count();
+    before[end - length..end].iter().collect()
+}
+
+fn speak_iso_date(chars: &[char], start: usize, reading: Reading) -> Option<(String, usize)> {
+    let year = fixed_digits(chars, start, 4)?;
+    if chars.get(start + 4) != Some(&'-') || chars.get(start + 7) != Some(&'-') {
+        return None;
+    }
+    let month = fixed_digits(chars, start + 5, 2).filter(|month| (1..=12).contains(month))?;
+    let day = fixed_digits(chars, start + 8, 2).filter(|day| (1..=31).contains(day))?;
+    let spoken = match reading {
+        Reading::Chinese => format!(
+            "{}年{}月{}日",
+            digit_by_digit(&year.to_string(), reading),
+            zh_cardinal(month),
+            zh_cardinal(day)
+        ),
+        _ if reading.words().is_some() => {
+            let words = reading.words()?;
+            let previous = previous_word(chars, start);
+            (words.date)(day, words.months[month as usize - 1], year, &previous)
+        }
+        _ => format!(
+            "{} {}, {}",
+            EN_MONTHS[month as usize - 1],
+            en_ordinal(day),
+            en_year(year)
+        ),
+    };
+    Some((spoken, start + 10))
+}
+
+fn speak_time(chars: &[char], start: usize, reading: Reading) -> Option<(String, usize)> {
+    let hour_len = chars[start..]
+        .iter()
+        .take_while(|ch| ch.is_ascii_digit())
+        .count();
+    if !(1..=2).contains(&hour_len) || chars.get(start + hour_len) != Some(&':') {
+        return None;
+    }
+    let hour = fixed_digits(chars, start, hour_len).filter(|hour| *hour < 24)?;
+    let minute = fixed_digits(chars, start + hour_len + 1, 2).filter(|minute| *minute < 60)?;
+    let mut end = start + hour_len + 3;
+    if chars.get(end) == Some(&':') {
+        return None;
+    }
+    // `9:05 Uhr` would otherwise be read with two of them.
+    let space = usize::from(chars.get(end) == Some(&' '));
+    if reading == Reading::German && matches_at(chars, end + space, "Uhr") {
+        end += space + 3;
+    }
+    let spoken = match (reading, minute) {
+        (Reading::Chinese, 0) => format!("{}点", zh_cardinal(hour)),
+        (Reading::Chinese, _) if minute < 10 => {
+            format!("{}点零{}分", zh_cardinal(hour), zh_cardinal(minute))
+        }
+        (Reading::Chinese, _) => format!("{}点{}分", zh_cardinal(hour), zh_cardinal(minute)),
+        _ if reading.words().is_some() => (reading.words()?.time)(hour, minute),
+        (_, 0) => format!("{} o'clock", en_cardinal(hour)),
+        (_, 1..=9) => format!("{} oh {}", en_cardinal(hour), en_cardinal(minute)),
+        _ => format!("{} {}", en_cardinal(hour), en_cardinal(minute)),
+    };
+    Some((spoken, end))
+}
+
+/// Currency symbol, English singular and plural, subunit singular and
+/// plural (empty without one), and Chinese name.
+const CURRENCIES: &[(char, &str, &str, &str, &str, &str)] = &[
+    ('$', "dollar", "dollars", "cent", "cents", "美元"),
+    ('€', "euro", "euros", "cent", "cents", "欧元"),
+    ('£', "pound", "pounds", "penny", "pence", "英镑"),
+    ('¥', "yen", "yen", "", "", "元"),
+    ('￥', "yuan", "yuan", "", "", "元"),
+];
+
+/// Units read after a number: abbreviation, English singular and plural,
+/// Chinese. Longer abbreviations come first so `km/h` is not read as `km`.
+const UNITS: &[(&str, &str, &str, &str)] = &[
+    (
+        "km/h",
+        "kilometer per hour",
+        "kilometers per hour",
+        "公里每小时",
+    ),
+    ("mph", "mile per hour", "miles per hour", "英里每小时"),
+    ("km", "kilometer", "kilometers", "公里"),
+    ("cm", "centimeter", "centimeters", "厘米"),
+    ("mm", "millimeter", "millimeters", "毫米"),
+    ("kg", "kilogram", "kilograms", "公斤"),
+    ("mg", "milligram", "milligrams", "毫克"),
+    ("ml", "milliliter", "milliliters", "毫升"),
+    ("ms", "millisecond", "milliseconds", "毫秒"),
+    ("°C", "degree Celsius", "degrees Celsius", "摄氏度"),
+    ("°F", "degree Fahrenheit", "degrees Fahrenheit", "华氏度"),
+    ("TB", "terabyte", "terabytes", "TB"),
+    ("GB", "gigabyte", "gigabytes", "GB"),
+    ("MB", "megabyte", "megabytes", "MB"),
+    ("KB", "kilobyte", "kilobytes", "KB"),
+    ("GHz", "gigahertz", "gigahertz", "吉赫兹"),
+    ("MHz", "megahertz", "megahertz", "兆赫兹"),
+    ("kHz", "kilohertz", "kilohertz", "千赫兹"),
+    ("Hz", "hertz", "hertz", "赫兹"),
+];
+
+const EN_SCALE_WORDS: [&str; 4] = ["thousand", "million", "billion", "trillion"];
+const ZH_SCALE_WORDS: [char; 3] = ['万', '亿', '千'];
+
+fn matches_at(chars: &[char], start: usize, word: &str) -> bool {
+    let mut index = start;
+    for ch in word.chars() {
+        if chars.get(index) != Some(&ch) {
+            return false;
+        }
+        index += 1;
+    }
+    !chars.get(index).is_some_and(|ch| ch.is_ascii_alphabetic())
+}
+
+/// `$5.99` as "five dollars and ninety-nine cents", `$2 million` as "two
+/// million dollars", `￥30` as 三十元.
+fn speak_currency(chars: &[char], start: usize, reading: Reading) -> Option<(String, usize)> {
+    let &(_, singular, plural, sub_singular, sub_plural, chinese) = CURRENCIES
+        .iter()
+        .find(|currency| currency.0 == chars[start])?;
+    let (number, mut end) = parse_number(chars, start + 1, reading)?;
+    if let Some(words) = reading.words() {
+        let currency = words
+            .currencies
+            .iter()
+            .find(|currency| currency.0 == chars[start])?;
+        return Some((speak_amount(number, currency, words, reading), end));
+    }
+    if reading == Reading::Chinese {
+        let mut spoken = number.cardinal(reading);
+        if let Some(scale) = chars.get(end).filter(|ch| ZH_SCALE_WORDS.contains(ch)) {
+            spoken.push(*scale);
+            end += 1;
+        }
+        return Some((format!("{spoken}{chinese}"), end));
+    }
+    let space = usize::from(chars.get(end) == Some(&' '));
+    if let Some(scale) = EN_SCALE_WORDS
+        .iter()
+        .find(|scale| matches_at(chars, end + space, scale))
+    {
+        let spoken = format!("{} {scale} {plural}", number.cardinal(reading));
+        return Some((spoken, end + space + scale.len()));
+    }
+    let unit = if number.is_one() { singular } else { plural };
+    let cents = number
+        .fraction
+        .as_ref()
+        .filter(|fraction| fraction.len() == 2 && !sub_plural.is_empty())
+        .and_then(|fraction| fraction.parse::<u64>().ok());
+    let spoken = match cents {
+        Some(cents) => {
+            let whole = Number {
+                fraction: None,
+                ..number
+            };
+            let sub_unit = if cents == 1 { sub_singular } else { sub_plural };
+            let cents = format!("{} {sub_unit}", en_cardinal(cents));
+            match whole.value() {
+                Some(0) => cents,
+                _ if cents.starts_with("zero") => format!("{} {unit}", whole.cardinal(reading)),
+                _ => {
+                    let unit = if whole.is_one() { singular } else { plural };
+                    format!("{} {unit} and {cents}", whole.cardinal(reading))
+                }
+            }
+        }
+        None => format!("{} {unit}", number.cardinal(reading)),
+    };
+    Some((spoken, end))
+}
+
+/// An amount in a language with `Words`, e.g. "fünf Euro und
+/// neunundneunzig Cent"; the symbol may stand before or after the number.
+fn speak_amount(number: Number, currency: &Currency, words: &Words, reading: Reading) -> String {
+    let &(_, one, many, sub_one, sub_many) = currency;
+    let cents = number
+        .fraction
+        .as_ref()
+        .filter(|fraction| fraction.len() == 2 && !sub_many.is_empty())
+        .and_then(|fraction| fraction.parse::<u64>().ok());
+    let Some(cents) = cents else {
+        return number.count(words, one, many, reading);
+    };
+    let whole = Number {
+        fraction: None,
+        ..number
+    };
+    let spoken_cents = Number {
+        negative: false,
+        integer: cents.to_string(),
+        fraction: None,
+    }
+    .count(words, sub_one, sub_many, reading);
+    match whole.value() {
+        Some(0) => spoken_cents,
+        _ if cents == 0 => whole.count(words, one, many, reading),
+        _ => format!(
+            "{} {} {spoken_cents}",
+            whole.count(words, one, many, reading),
+            words.and
+        ),
+    }
+}
+
+/// Ordinals as German (`3. Mai`), French (`1er`, `2e`) and Spanish (`1º`,
+/// `1ª`) write them, with the length of the suffix after the digits.
+fn words_ordinal(
+    chars: &[char],
+    start: usize,
+    end: usize,
+    value: u64,
+    reading: Reading,
+) -> Option<(String, usize)> {
+    let words = reading.words()?;
+    let ends_at = |length: usize| {
+        !chars
+            .get(end + length)
+            .is_some_and(|ch| ch.is_alphanumeric())
+    };
+    match reading {
+        Reading::German => {
+            // Only in dates, as a point after a number usually ends a sentence.
+            let before_month = chars.get(end) == Some(&'.')
+                && chars.get(end + 1) == Some(&' ')
+                && words
+                    .months
+                    .iter()
+                    .any(|month| matches_at(chars, end + 2, month));
+            (before_month && (1..=31).contains(&value))
+                .then(|| ((words.ordinal)(value, &previous_word(chars, start)), 1))
+        }
+        Reading::French => {
+            let suffix = ["ère", "ème", "er", "re", "e"]
+                .into_iter()
+                .find(|suffix| matches_at(chars, end, suffix) && ends_at(suffix.chars().count()))?;
+            let spoken = match (value, suffix) {
+                (1, "re" | "ère") => "première".to_string(),
+                _ => (words.ordinal)(value, ""),
+            };
+            Some((spoken, suffix.chars().count()))
+        }
+        Reading::Spanish => {
+            let suffix = *chars.get(end).filter(|ch| matches!(ch, 'º' | 'ª'))?;
+            if !ends_at(1) {
+                return None;
+            }
+            let ordinal = (words.ordinal)(value, "");
+            let spoken = match ordinal.strip_suffix('o') {
+                Some(stem) if suffix == 'ª' => format!("{stem}a"),
+                _ => ordinal,
+            };
+            Some((spoken, 1))
+        }
+        _ => None,
+    }
+}
+
+/// The number at `start` with what follows it: a percent sign, an ordinal
+/// suffix, a currency symbol, a unit, or 年 after a year.
+fn speak_number(chars: &[char], start: usize, reading: Reading) -> Option<(String, usize)> {
+    if let Some(spoken) = speak_iso_date(chars, start, reading) {
+        return Some(spoken);
+    }
+    if let Some(spoken) = speak_time(chars, start, reading) {
+        return Some(spoken);
+    }
+    let (number, end) = parse_number(chars, start, reading)?;
+    // `50 %` and `5 €` are spaced in German, French and Spanish.
+    let gap = usize::from(
+        reading.words().is_some() && matches!(chars.get(end), Some(' ' | '\u{a0}' | '\u{202f}')),
+    );
+    if chars.get(end + gap) == Some(&'%') {
+        let spoken = match reading {
+            Reading::Chinese => format!("百分之{}", number.cardinal(reading)),
+            _ => format!(
+                "{} {}",
+                number.cardinal(reading),
+                reading.words().map_or("percent", |words| words.percent)
+            ),
+        };
+        return Some((spoken, end + gap + 1));
+    }
+    if let Some(words) = reading.words() {
+        let currency = words
+            .currencies
+            .iter()
+            .find(|currency| chars.get(end + gap) == Some(&currency.0));
+        if let Some(currency) = currency {
+            return Some((
+                speak_amount(number, currency, words, reading),
+                end + gap + 1,
+            ));
+        }
+    }
+    let plain = !number.negative && number.fraction.is_none();
+    let year = plain && number.integer.len() == 4 && end - start == 4 && !number.is_code(reading);
+    if reading == Reading::Chinese {
+        if year && chars.get(end) == Some(&'年') {
+            return Some((digit_by_digit(&number.integer, reading), end));
+        }
+    } else if let (true, Some(value)) = (plain && reading != Reading::English, number.value()) {
+        if let Some((spoken, length)) = words_ordinal(chars, start, end, value, reading) {
+            return Some((spoken, end + length));
+        }
+    } else if plain {
+        let suffix = chars
+            .get(end..end + 2)
+            .map(|suffix| suffix.iter().collect::<String>().to_ascii_lowercase());
+        let ordinal = matches!(suffix.as_deref(), Some("st" | "nd" | "rd" | "th"))
+            && !chars.get(end + 2).is_some_and(|ch| ch.is_alphanumeric());
+        if let (true, Some(value)) = (ordinal, number.value()) {
+            return Some((en_ordinal(value), end + 2));
+        }
+    }
+    let space = usize::from(chars.get(end) == Some(&' '));
+    let unit_at = |abbreviation: &str| {
+        matches_at(chars, end + space, abbreviation)
+            && !chars
+                .get(end + space + abbreviation.chars().count())
+                .is_some_and(|ch| ch.is_alphanumeric())
+    };
+    if let Some(words) = reading.words() {
+        if let Some(&(abbreviation, one, many)) = words.units.iter().find(|unit| unit_at(unit.0)) {
+            return Some((
+                number.count(words, one, many, reading),
+                end + space + abbreviation.chars().count(),
+            ));
+        }
+    }
+    let unit = UNITS.iter().find(|unit| unit_at(unit.0));
+    if let Some(&(abbreviation, singular, plural, chinese)) = unit {
+        let spoken = match reading {
+            Reading::Chinese => format!("{}{chinese}", number.cardinal(reading)),
+            _ if number.is_one() => format!("one {singular}"),
+            _ => format!("{} {plural}", number.cardinal(reading)),
+        };
+        return Some((spoken, end + space + abbreviation.chars().count()));
+    }
+    if reading == Reading::English && year {
+        if let Some(value) = number.value().filter(|value| (1900..2100).contains(value)) {
+            return Some((en_year(value), end));
+        }
+    }
+    if let (true, Some(words), Some(value)) = (year, reading.words(), number.value()) {
+        return Some(((words.year)(value), end));
+    }
+    Some((number.cardinal(reading), end))
+}
+
+/// Pushes spoken words, with spaces around them where English words would
+/// otherwise run into their neighbours.
+fn push_spoken(output: &mut String, spoken: &str, next: Option<char>, reading: Reading) {
+    let needs_space = |ch: char| match reading {
+        Reading::Chinese => ch.is_ascii_alphanumeric(),
+        _ => ch.is_alphanumeric(),
+    };
+    let first = spoken.chars().next().is_some_and(needs_space);
+    let last = spoken.chars().next_back().is_some_and(needs_space);
+    if first && output.chars().next_back().is_some_and(needs_space) {
+        output.push(' ');
+    }
+    output.push_str(spoken);
+    if last && next.is_some_and(needs_space) {
+        output.push(' ');
+    }
+}
+
+/// Whether the hyphen at `index` joins two short numbers, as in `2-3 days`;
+/// longer runs are more likely phone numbers or codes.
+fn is_range(chars: &[char], index: usize) -> bool {
+    let before = chars[..index]
+        .iter()
+        .rev()
+        .take_while(|ch| ch.is_ascii_digit())
+        .count();
+    let after = chars[index + 1..]
+        .iter()
+        .take_while(|ch| ch.is_ascii_digit())
+        .count();
+    (1..=3).contains(&before)
+        && (1..=3).contains(&after)
+        && !chars[..index - before]
+            .last()
+            .is_some_and(|ch| matches!(ch, '-' | '.'))
+        && !chars
+            .get(index + 1 + after)
+            .is_some_and(|ch| matches!(ch, '-' | '.'))
+}
+
+fn expand_numbers(text: &str, reading: Reading) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len() * 2);
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let previous = index.checked_sub(1).map(|previous| chars[previous]);
+        // Digits glued to a word, e.g. `mp3` or `H2O`, stay as they are.
+        let after_word = previous.is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '.');
+        let starts_number = ch.is_ascii_digit()
+            || (ch == '-'
+                && chars.get(index + 1).is_some_and(char::is_ascii_digit)
+                && !previous.is_some_and(|ch| ch.is_ascii_alphanumeric()));
+        let spoken = if ch == '-' && is_range(&chars, index) {
+            let word = match reading {
+                Reading::Chinese => "到",
+                _ => reading.words().map_or("to", |words| words.range),
+            };
+            Some((word.to_string(), index + 1))
+        } else if CURRENCIES.iter().any(|currency| currency.0 == ch) {
+            speak_currency(&chars, index, reading)
+        } else if starts_number && !after_word {
+            speak_number(&chars, index, reading)
+        } else {
+            None
+        };
+        match spoken {
+            Some((spoken, end)) => {
+                push_spoken(&mut output, &spoken, chars.get(end).copied(), reading);
+                index = end;
+            }
+            None => {
+                // Skip over the rest of a glued number so its digits are
+                // not expanded one run at a time.
+                let run = if after_word && ch.is_ascii_digit() {
+                    chars[index..]
+                        .iter()
+                        .take_while(|ch| ch.is_ascii_alphanumeric())
+                        .count()
+                } else {
+                    1
+                };
+                output.extend(&chars[index..index + run]);
+                index += run;
+            }
+        }
+    }
+    output
+}
+
+fn collapse_spaces(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a sentence longer than `max_chars` characters, preferably after a
+/// comma or similar pause, else at a space, else anywhere; models lose the
+/// prosody, or fail outright, on very long inputs.
+pub fn split_long(sentence: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = sentence.trim();
+    while rest.chars().count() > max_chars {
+        let window = rest.char_indices().take(max_chars + 1).collect::<Vec<_>>();
+        let break_after = |accept: &dyn Fn(char) -> bool| {
+            window[max_chars / 2..]
+                .iter()
+                .rev()
+                .find(|(_, ch)| accept(*ch))
+                .map(|(offset, ch)| offset + ch.len_utf8())
+        };
+        let end =
+            break_after(&|ch| matches!(ch, ',' | '，' | '、' | ':' | '：' | '—' | '）' | ')'))
+                .or_else(|| break_after(&char::is_whitespace))
+                .unwrap_or(window[max_chars].0);
+        pieces.push(rest[..end].trim());
+        rest = rest[end..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(cases: &[(&str, &str)], lang: &str) {
+        for (input, expected) in cases {
+            assert_eq!(
+                verbalize(&strip_markdown(input), lang),
+                *expected,
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn normalizes_english() {
+        check(
+            &[
+                ("I have 3 cats.", "I have three cats."),
+                (
+                    "It costs $5.99 today",
+                    "It costs five dollars and ninety-nine cents today",
+                ),
+                ("$1 and $0.50", "one dollar and fifty cents"),
+                (
+                    "A $2.5 billion deal",
+                    "A two point five billion dollars deal",
+                ),
+                ("€10, £1", "ten euros, one pound"),
+                (
+                    "1,234,567 people",
+                    "one million two hundred thirty-four thousand five hundred sixty-seven people",
+                ),
+                ("Pi is 3.14", "Pi is three point one four"),
+                (
+                    "Growth was 50% at -3",
+                    "Growth was fifty percent at minus three",
+                ),
+                ("the 21st and 2nd", "the twenty-first and second"),
+                (
+                    "On 2024-03-15 at 9:05",
+                    "On March fifteenth, twenty twenty-four at nine oh five",
+                ),
+                ("Meet at 10:00", "Meet at ten o'clock"),
+                (
+                    "In 1999 and 2005",
+                    "In nineteen ninety-nine and two thousand five",
+                ),
+                ("Ran 5 km in 1 hour", "Ran five kilometers in one hour"),
+                (
+                    "1 km, 30km/h, 20 °C",
+                    "one kilometer, thirty kilometers per hour, twenty degrees Celsius",
+                ),
+                ("Call 007 or mp3", "Call zero zero seven or mp3"),
+                ("2-3 days", "two to three days"),
+                (
+                    "Dr. Smith & co, e.g. this",
+                    "Doctor Smith and co, for example this",
+                ),
+            ],
+            "en",
+        );
+    }
+
+    #[test]
+    fn normalizes_chinese() {
+        check(
+            &[
+                ("我有3只猫", "我有三只猫"),
+                ("价格是￥30", "价格是三十元"),
+                ("花了$5亿", "花了五亿美元"),
+                ("共20305人", "共二万零三百零五人"),
+                ("10个和110个", "十个和一百一十个"),
+                ("增长了50%", "增长了百分之五十"),
+                ("圆周率3.14", "圆周率三点一四"),
+                ("2024年3月", "二零二四年三月"),
+                ("日期2024-03-15", "日期二零二四年三月十五日"),
+                ("9:05出发", "九点零五分出发"),
+                ("跑了5km", "跑了五公里"),
+                ("3-5天，温度-5°C", "三到五天，温度负五摄氏度"),
+                ("电话13800138000", "电话一三八零零一三八零零零"),
+                ("苹果&香蕉", "苹果和香蕉"),
+            ],
+            "zh-Hans",
+        );
+    }
+
+    #[test]
+    fn normalizes_german_french_and_spanish() {
+        check(
+            &[
+                ("Ich habe 3 Katzen.", "Ich habe drei Katzen."),
+                (
+                    "Es kostet 5,99 € oder 21 €",
+                    "Es kostet fünf Euro und neunundneunzig Cent oder einundzwanzig Euro",
+                ),
+                (
+                    "1.234.567 Menschen",
+                    "eine Million zweihundertvierunddreißigtausendfünfhundertsiebenundsechzig \
+                     Menschen",
+                ),
+                ("Pi ist 3,14", "Pi ist drei Komma eins vier"),
+                (
+                    "50 % bei -3 °C",
+                    "fünfzig Prozent bei minus drei Grad Celsius",
+                ),
+                (
+                    "Am 3. Mai 1999 um 9:05 Uhr",
+                    "Am dritten Mai neunzehnhundertneunundneunzig um neun Uhr fünf",
+                ),
+                (
+                    "Stand: 2024-03-15",
+                    "Stand: fünfzehnter März zweitausendvierundzwanzig",
+                ),
+                ("1 km, 101 km", "ein Kilometer, einhundertein Kilometer"),
+                ("2-3 Tage, Tom & Jerry", "zwei bis drei Tage, Tom und Jerry"),
+            ],
+            "de",
+        );
+        check(
+            &[
+                ("J'ai 3 chats.", "J'ai trois chats."),
+                (
+                    "Il coûte 5,99 €",
+                    "Il coûte cinq euros et quatre-vingt-dix-neuf centimes",
+                ),
+                (
+                    "71, 80, 91 et 200 000",
+                    "soixante et onze, quatre-vingts, quatre-vingt-onze et deux cent mille",
+                ),
+                (
+                    "le 1er mai 2024 à 21:30",
+                    "le premier mai deux mille vingt-quatre à vingt et une heures trente",
+                ),
+                ("la 2e fois, 50 %", "la deuxième fois, cinquante pour cent"),
+                ("2024-03-15", "quinze mars deux mille vingt-quatre"),
+            ],
+            "fr",
+        );
+        check(
+            &[
+                ("Tengo 3 gatos.", "Tengo tres gatos."),
+                (
+                    "Cuesta 5,99 €",
+                    "Cuesta cinco euros con noventa y nueve céntimos",
+                ),
+                (
+                    "21 km y 1.000.000 de personas",
+                    "veintiún kilómetros y un millón de personas",
+                ),
+                (
+                    "el 1º de mayo a las 10:00",
+                    "el primero de mayo a las diez en punto",
+                ),
+                ("2024-03-15", "quince de marzo de dos mil veinticuatro"),
+                ("1 €, 3,5 %", "un euro, tres coma cinco por ciento"),
+            ],
+            "es",
+        );
+    }
+
+    #[test]
+    fn strips_markdown_links_and_emoji() {
+        check(
+            &[
+                (
+                    "## Title\n\n- **Bold** and *italic* 🎉",
+                    "Title\nBold and italic",
+                ),
+                (
+                    "See [the docs](https://example.com/a?b=1).",
+                    "See the docs.",
+                ),
+                (
+                    "Visit https://www.example.com/path, now",
+                    "Visit example dot com, now",
+                ),
+                ("Open http://localhost:8080/app", "Open localhost"),
+                (
+                    "Mail john.doe@example.org!",
+                    "Mail john dot doe at example dot org!",
+                ),
+                (
+                    "Run `cargo build`:\n```\nfn main() {}\n```\nDone",
+                    "Run cargo build:\nDone",
+                ),
+                ("| a | b |\n|---|---|\n| 1 | 2 |", "a, b\none, two"),
+                ("> quoted<br>text", "quoted text"),
+            ],
+            "en",
+        );
+        check(
+            &[
+                ("访问 https://example.com 了解", "访问 example 点 com 了解"),
+                ("# 标题\n1. 第一项 ✅", "标题\n第一项"),
+            ],
+            "zh-Hans",
+        );
+        check(&[("Am 3. Mai 🎉 **fett**", "Am dritten Mai fett")], "de");
+    }
+
+    #[test]
+    fn splits_long_sentences_at_pauses() {
+        assert_eq!(
+            split_long("one two three, four five six seven", 20),
+            vec!["one two three,", "four five six seven"]
+        );
+        assert_eq!(
+            split_long("alpha beta gamma delta epsilon", 12),
+            vec!["alpha beta", "gamma delta", "epsilon"]
+        );
+        assert_eq!(
+            split_long("一二三四五六七八", 4),
+            vec!["一二三四", "五六七八"]
+        );
+        assert_eq!(split_long("short", 10), vec!["short"]);
+    }
+}
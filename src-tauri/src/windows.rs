@@ -124,6 +124,7 @@ pub fn get_translator_window_always_on_top() -> bool {
 #[tauri::command]
 #[specta::specta]
 pub async fn show_translator_window_with_selected_text_command() {
+    crate::app_rules::update_active_rule();
     remember_active_window();
     let config = config::get_config().ok();
     let restore_previous_position = config
@@ -403,6 +404,10 @@ fn handle_webview_build_failure(e: &tauri::Error) -> ! {
 pub fn checked_build<R: tauri::Runtime, M: tauri::Manager<R>>(
     builder: tauri::WebviewWindowBuilder<'_, R, M>,
 ) -> tauri::WebviewWindow<R> {
+    let builder = match crate::paths::webview_data_dir() {
+        Some(data_directory) => builder.data_directory(data_directory),
+        None => builder,
+    };
     match builder.build() {
         Ok(window) => window,
         Err(e) => handle_webview_build_failure(&e),
@@ -1019,6 +1024,7 @@ pub async fn hide_inline_lookup_window() {
 #[tauri::command]
 #[specta::specta]
 pub async fn show_inline_lookup_window_command() {
+    crate::app_rules::update_active_rule();
     show_inline_lookup_window(false, true, true);
 }
 
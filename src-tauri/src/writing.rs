@@ -26,6 +26,8 @@
 // already-translated text now performs a full select-all + retype, which is
 // the only way to guarantee zero over-deletion.
 
+use crate::app_rules::InsertionMode;
+use crate::insertion::paste_text;
 use crate::utils::{
     get_focused_text_via_ax, get_selected_text_by_clipboard, get_selected_text_via_ax,
     get_writing_anchor_rect, select_all, INPUT_LOCK,
@@ -40,6 +42,9 @@ use tauri_plugin_aptabase::EventTracker;
 
 static IS_WRITING: Mutex<bool> = Mutex::new(false);
 static IS_FIRST_CHUNK: Mutex<bool> = Mutex::new(true);
+/// Streamed text waiting to be pasted in one go by `finish_writing`, when the
+/// active rule inserts through the clipboard.
+static PENDING_PASTE: Mutex<String> = Mutex::new(String::new());
 
 /// How the first streamed chunk should erase whatever's currently in the input.
 #[derive(Clone, Copy)]
@@ -112,10 +117,14 @@ pub fn writing_command() {
         None => return,
     };
     let app_handle = APP_HANDLE.get().unwrap();
-    let _ = app_handle.track_event("writing", None);
+    if crate::config::is_telemetry_enabled() {
+        let _ = app_handle.track_event("writing", None);
+    }
 
     // Reset per-invocation state.
     *IS_FIRST_CHUNK.lock() = true;
+    PENDING_PASTE.lock().clear();
+    crate::app_rules::update_active_rule();
 
     // Snapshot the anchor rect (selection bounds, falling back to focused
     // element frame) BEFORE we touch the clipboard / fire ⌘C, so the indicator
@@ -179,63 +188,43 @@ pub fn writing_command() {
     crate::utils::writing_text(content);
 }
 
+/// Maps one `+`-separated token of `writingNewlineHotkey` to a key: a single
+/// character, or one of the named keys below.
+pub(crate) fn parse_newline_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Unicode(c));
+    }
+    match name {
+        "ctrl" => Some(Key::Control),
+        "alt" => Some(Key::Alt),
+        "shift" => Some(Key::Shift),
+        "meta" => Some(Key::Meta),
+        "caps_lock" => Some(Key::CapsLock),
+        "escape" => Some(Key::Escape),
+        "enter" => Some(Key::Return),
+        _ => None,
+    }
+}
+
 fn do_write_to_input(enigo: &mut Enigo, text: String, animation: bool) {
     let _guard = INPUT_LOCK.lock();
     if animation {
         for c in text.chars() {
             let char = c.to_string();
             if char == "\n" {
-                if let Ok(config) = crate::config::get_config() {
+                if let Ok(config) = crate::app_rules::get_app_config() {
                     if let Some(writing_newline_hotkey) = config.writing_newline_hotkey {
                         let keys = writing_newline_hotkey
                             .split('+')
                             .map(|c| c.trim())
-                            .collect::<Vec<&str>>();
+                            .filter_map(parse_newline_key)
+                            .collect::<Vec<Key>>();
                         for key in &keys {
-                            if key.len() == 1 {
-                                enigo
-                                    .key(
-                                        Key::Unicode(key.chars().next().unwrap()),
-                                        Direction::Press,
-                                    )
-                                    .unwrap_or_default();
-                            } else {
-                                match *key {
-                                    "ctrl" => enigo.key(Key::Control, Direction::Press).unwrap(),
-                                    "alt" => enigo.key(Key::Alt, Direction::Press).unwrap(),
-                                    "shift" => enigo.key(Key::Shift, Direction::Press).unwrap(),
-                                    "meta" => enigo.key(Key::Meta, Direction::Press).unwrap(),
-                                    "caps_lock" => {
-                                        enigo.key(Key::CapsLock, Direction::Press).unwrap()
-                                    }
-                                    "escape" => enigo.key(Key::Escape, Direction::Press).unwrap(),
-                                    "enter" => enigo.key(Key::Return, Direction::Press).unwrap(),
-                                    _ => {}
-                                }
-                            }
+                            enigo.key(*key, Direction::Press).unwrap_or_default();
                         }
                         for key in keys.iter().rev() {
-                            if key.len() == 1 {
-                                enigo
-                                    .key(
-                                        Key::Unicode(key.chars().next().unwrap()),
-                                        Direction::Release,
-                                    )
-                                    .unwrap_or_default();
-                            } else {
-                                match *key {
-                                    "ctrl" => enigo.key(Key::Control, Direction::Release).unwrap(),
-                                    "alt" => enigo.key(Key::Alt, Direction::Release).unwrap(),
-                                    "shift" => enigo.key(Key::Shift, Direction::Release).unwrap(),
-                                    "meta" => enigo.key(Key::Meta, Direction::Release).unwrap(),
-                                    "caps_lock" => {
-                                        enigo.key(Key::CapsLock, Direction::Release).unwrap()
-                                    }
-                                    "escape" => enigo.key(Key::Escape, Direction::Release).unwrap(),
-                                    "enter" => enigo.key(Key::Return, Direction::Release).unwrap(),
-                                    _ => {}
-                                }
-                            }
+                            enigo.key(*key, Direction::Release).unwrap_or_default();
                         }
                         continue;
                     }
@@ -252,8 +241,37 @@ fn do_write_to_input(enigo: &mut Enigo, text: String, animation: bool) {
 #[tauri::command]
 #[specta::specta]
 pub fn write_to_input(text: String) {
+    let insertion_mode = crate::app_rules::get_app_config()
+        .ok()
+        .and_then(|config| config.insertion_mode)
+        .unwrap_or_default();
+    if insertion_mode == InsertionMode::Paste {
+        // Pasting chunk by chunk would swap the user's clipboard out and back,
+        // with its sleeps, for every piece of the stream.
+        PENDING_PASTE.lock().push_str(&text);
+        return;
+    }
     let mut enigo = Enigo::new(&Settings::default()).unwrap();
+    prepare_first_chunk(&mut enigo);
+    do_write_to_input(&mut enigo, text, true);
+}
 
+/// Pastes the text collected by `write_to_input` in Paste mode, typing it
+/// instead if the clipboard is unavailable.
+fn paste_pending_text() {
+    let text = std::mem::take(&mut *PENDING_PASTE.lock());
+    if text.is_empty() {
+        return;
+    }
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+    prepare_first_chunk(&mut enigo);
+    if let Err(e) = paste_text(&mut enigo, &text) {
+        debug_println!("[writing] paste failed ({}), typing instead", e);
+        do_write_to_input(&mut enigo, text, true);
+    }
+}
+
+fn prepare_first_chunk(enigo: &mut Enigo) {
     let mut is_first = IS_FIRST_CHUNK.lock();
     if *is_first {
         *is_first = false;
@@ -261,7 +279,7 @@ pub fn write_to_input(text: String) {
             FirstChunkMode::SelectAllThenType => {
                 // Replace existing content by selecting all then typing the
                 // chunk over it. No Backspace involved.
-                select_all(&mut enigo);
+                select_all(enigo);
                 thread::sleep(Duration::from_millis(50));
             }
             FirstChunkMode::ReplaceSelection => {
@@ -271,15 +289,13 @@ pub fn write_to_input(text: String) {
             }
         }
     }
-    drop(is_first);
-
-    do_write_to_input(&mut enigo, text, true);
 }
 
 #[tauri::command]
 #[specta::specta]
 pub fn finish_writing() {
     debug_println!("[writing] finish_writing called");
+    paste_pending_text();
     *IS_WRITING.lock() = false;
     *IS_FIRST_CHUNK.lock() = true;
     // Tell the indicator panel to flash "done", THEN hide the window from Rust
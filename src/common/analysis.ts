@@ -1,6 +1,6 @@
 import * as Sentry from '@sentry/react'
 import ReactGA from 'react-ga4'
-import { getSettings, isDesktopApp, isUserscript } from './utils'
+import { getSettings, isDesktopApp, isTauri, isUserscript } from './utils'
 
 export async function setupAnalysis() {
     if (isUserscript()) {
@@ -9,6 +9,19 @@ export async function setupAnalysis() {
     doSetupAnalysis()
 }
 
+/** Sends a usage event from the desktop app, unless statistics are disabled by the user or by a policy. */
+export async function trackEvent(eventName: string, payload?: Record<string, string | number>) {
+    if (!isTauri()) {
+        return
+    }
+    const settings = await getSettings()
+    if (settings.disableCollectingStatistics) {
+        return
+    }
+    const { trackEvent: trackAptabaseEvent } = await import('@aptabase/tauri')
+    await trackAptabaseEvent(eventName, payload)
+}
+
 let isAnalysisSetupped = false
 
 export async function doSetupAnalysis() {
@@ -102,6 +102,16 @@ export function createShouldUpdate(
     }
 }
 
+/** Fields fixed elsewhere, e.g. by an administrator, which render read-only. */
+export interface LockedFields {
+    /** Top-level field names. */
+    names: ReadonlySet<string>
+    /** Shown under a locked field instead of its own caption. */
+    caption?: ReactNode
+}
+
+const LockedFieldsContext = React.createContext<LockedFields>({ names: new Set() })
+
 const defaultFormItemClassName: Required<FormItemClassName> = {
     item: 'rc-form-item',
     label: 'rc-form-item-label',
@@ -144,6 +154,9 @@ export function createForm<S extends {} = Store>({
             name: string | number
         }
 
+        const lockedFields = React.useContext(LockedFieldsContext)
+        const locked = name !== undefined && lockedFields.names.has(String(Array.isArray(name) ? name[0] : name))
+
         const rules: Rule[] =
             typeof validators === 'function'
                 ? [
@@ -171,12 +184,16 @@ export function createForm<S extends {} = Store>({
             ((control: any, { errors }: FieldData, form: FormInstance<S>) => {
                 const { getFieldsValue } = form
 
-                const childNode =
+                const fieldNode =
                     typeof children === 'function'
                         ? children(getFieldsValue(deps))
                         : React.cloneElement(children as React.ReactElement, {
                               ...control,
                           })
+                // A disabled fieldset disables every input inside, whatever the control.
+                const childNode = locked
+                    ? React.createElement('fieldset', { disabled: true, className: styles.locked }, fieldNode)
+                    : fieldNode
 
                 if (noStyle) {
                     return childNode
@@ -209,7 +226,7 @@ export function createForm<S extends {} = Store>({
                                   labelText,
                               ])
                             : labelText,
-                        caption,
+                        caption: locked ? lockedFields.caption ?? caption : caption,
                         children: childNode,
                     },
                     childNode
@@ -246,6 +263,7 @@ export function createForm<S extends {} = Store>({
         FormList: RcForm.List,
         FormProvider: RcForm.FormProvider,
         FormItemLabel,
+        LockedFields: LockedFieldsContext.Provider,
         useForm,
     }
 }
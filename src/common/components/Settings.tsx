@@ -7,6 +7,7 @@ import wechat from '../assets/images/wechat.png'
 import alipay from '../assets/images/alipay.png'
 import toast from 'react-hot-toast'
 import * as utils from '../utils'
+import { trackEvent } from '../analysis'
 import { Client as Styletron } from 'styletron-engine-atomic'
 import { Provider as StyletronProvider } from 'styletron-react'
 import { BaseProvider, LightTheme } from 'baseui-sd'
@@ -1548,7 +1549,7 @@ export function ProviderSelector({ value, onChange, hasPromotion }: IProviderSel
     )
 }
 
-const { Form, FormItem, useForm } = createForm<ISettings>()
+const { Form, FormItem, LockedFields, useForm } = createForm<ISettings>()
 
 interface IInnerSettingsProps {
     showFooter?: boolean
@@ -1896,7 +1897,6 @@ export function InnerSettings({
                 return
             }
             try {
-                const { trackEvent } = await import('@aptabase/tauri')
                 await trackEvent(eventName, payload)
             } catch (error) {
                 console.error(`Failed to track event ${eventName}`, error)
@@ -1943,6 +1943,21 @@ export function InnerSettings({
     const { refreshThemeType } = useThemeType()
 
     const { t } = useTranslation()
+    // Settings the administrator's policy fixes; the backend ignores them on save.
+    const [lockedKeys, setLockedKeys] = useState<ReadonlySet<string>>(new Set())
+    useEffect(() => {
+        if (!utils.isTauri()) {
+            return
+        }
+        import('@/tauri/bindings')
+            .then(({ commands }) => commands.getLockedConfigKeys())
+            .then((keys) => setLockedKeys(new Set(keys)))
+            .catch((error) => console.error('Failed to read locked settings', error))
+    }, [])
+    const lockedFields = useMemo(
+        () => ({ names: lockedKeys, caption: t('Set by your administrator') }),
+        [lockedKeys, t]
+    )
 
     const [loading, setLoading] = useState(false)
     const { settings, setSettings } = useSettings()
@@ -2022,6 +2037,34 @@ export function InnerSettings({
             setLoading(true)
             const oldSettings = await utils.getSettings()
             if (isTauri) {
+                const { commands } = await import('@/tauri/bindings')
+                const errors = await commands.validateConfig(JSON.stringify({ ...oldSettings, ...data }))
+                // Only errors the user can fix here block saving. The rest come
+                // from stored or locked values this form doesn't show, so they
+                // are only reported.
+                const renderedFields = form.getFieldsError().map(({ name }) => name.join('.'))
+                const isRendered = (field: string) =>
+                    field === '' ||
+                    renderedFields.some(
+                        (name) => name === field || field.startsWith(`${name}.`) || name.startsWith(`${field}.`)
+                    )
+                const formatErrors = (fieldErrors: typeof errors) =>
+                    fieldErrors.map((error) => `${error.field}: ${error.message}`).join('\n')
+                const blockingErrors = errors.filter((error) => isRendered(error.field))
+                if (blockingErrors.length > 0) {
+                    toast.error(formatErrors(blockingErrors), {
+                        duration: 8000,
+                    })
+                    setLoading(false)
+                    return
+                }
+                const warnings = errors.filter((error) => !isRendered(error.field))
+                if (warnings.length > 0) {
+                    toast(formatErrors(warnings), {
+                        icon: '⚠️',
+                        duration: 8000,
+                    })
+                }
                 try {
                     const {
                         enable: autostartEnable,
@@ -2054,7 +2097,7 @@ export function InnerSettings({
             setSettings(data)
             onSave?.(oldSettings)
         },
-        [isTauri, onSave, setSettings, refreshThemeType, t, trackTauriEvent]
+        [isTauri, form, onSave, setSettings, refreshThemeType, t, trackTauriEvent]
     )
 
     const onBlur = useCallback(async () => {
@@ -2534,1209 +2577,1211 @@ export function InnerSettings({
                     </a>
                 </div>
             )}
-            <Form
-                autoComplete='off'
-                autoCapitalize='off'
-                form={form}
-                style={{
-                    padding: '20px 25px',
-                    paddingBottom: utils.isBrowserExtensionOptions() ? 0 : undefined,
-                }}
-                onFinish={onSubmit}
-                initialValues={values}
-                onValuesChange={onChange}
-            >
-                <div>
-                    <div
-                        style={{
-                            display: activeTab === 'general' ? 'block' : 'none',
-                        }}
-                    >
-                        <FormItem name='i18n' label={t('i18n')}>
-                            <Ii18nSelector onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            name='provider'
-                            label={
-                                <div
-                                    style={{
-                                        display: 'flex',
-                                        flexDirection: 'row',
-                                        alignItems: 'center',
-                                        gap: 10,
-                                    }}
-                                >
-                                    {t('Default service provider')}
-                                    {openaiAPIKeyPromotion !== undefined && !openaiAPIKeyPromotionShowed && (
-                                        <div
-                                            style={{
-                                                width: '0.45rem',
-                                                height: '0.45rem',
-                                                borderRadius: '50%',
-                                                backgroundColor: theme.colors.warning300,
-                                            }}
-                                        />
-                                    )}
-                                </div>
-                            }
-                            required
-                            caption={
-                                values.provider === 'Ollama' ? (
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://github.com/ollama/ollama#ollama'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Ollama Homepage
-                                        </a>{' '}
-                                        {t('to learn how to install and setup.')}
-                                    </div>
-                                ) : undefined
-                            }
-                        >
-                            <ProviderSelector
-                                hasPromotion={openaiAPIKeyPromotion !== undefined && !openaiAPIKeyPromotionShowed}
-                            />
-                        </FormItem>
+            <LockedFields value={lockedFields}>
+                <Form
+                    autoComplete='off'
+                    autoCapitalize='off'
+                    form={form}
+                    style={{
+                        padding: '20px 25px',
+                        paddingBottom: utils.isBrowserExtensionOptions() ? 0 : undefined,
+                    }}
+                    onFinish={onSubmit}
+                    initialValues={values}
+                    onValuesChange={onChange}
+                >
+                    <div>
                         <div
                             style={{
-                                display: values.provider === 'Ollama' ? 'block' : 'none',
+                                display: activeTab === 'general' ? 'block' : 'none',
                             }}
                         >
-                            <FormItem
-                                name='ollamaAPIURL'
-                                label={t('API URL')}
-                                required={values.provider === 'Ollama'}
-                                caption={t('Generally, there is no need to modify this item.')}
-                            >
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='ollamaModelLifetimeInMemory'
-                                label={t('The survival time of the Ollama model in memory')}
-                                required={values.provider === 'Ollama'}
-                            >
-                                <DurationPicker size='compact' />
+                            <FormItem name='i18n' label={t('i18n')}>
+                                <Ii18nSelector onBlur={onBlur} />
                             </FormItem>
                             <FormItem
-                                name='ollamaAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Ollama'}
+                                name='provider'
+                                label={
+                                    <div
+                                        style={{
+                                            display: 'flex',
+                                            flexDirection: 'row',
+                                            alignItems: 'center',
+                                            gap: 10,
+                                        }}
+                                    >
+                                        {t('Default service provider')}
+                                        {openaiAPIKeyPromotion !== undefined && !openaiAPIKeyPromotionShowed && (
+                                            <div
+                                                style={{
+                                                    width: '0.45rem',
+                                                    height: '0.45rem',
+                                                    borderRadius: '50%',
+                                                    backgroundColor: theme.colors.warning300,
+                                                }}
+                                            />
+                                        )}
+                                    </div>
+                                }
+                                required
                                 caption={
-                                    <div>
+                                    values.provider === 'Ollama' ? (
                                         <div>
-                                            {t(
-                                                'Model needs to first use the `ollama pull` command to download locally, please view all models from this page:'
-                                            )}{' '}
+                                            {t('Go to the')}{' '}
                                             <a
                                                 target='_blank'
-                                                href='https://ollama.com/library'
+                                                href='https://github.com/ollama/ollama#ollama'
                                                 rel='noreferrer'
                                                 style={linkStyle}
                                             >
-                                                Models
-                                            </a>
+                                                Ollama Homepage
+                                            </a>{' '}
+                                            {t('to learn how to install and setup.')}
                                         </div>
-                                    </div>
+                                    ) : undefined
                                 }
                             >
-                                <APIModelSelector provider='Ollama' currentProvider={values.provider} onBlur={onBlur} />
+                                <ProviderSelector
+                                    hasPromotion={openaiAPIKeyPromotion !== undefined && !openaiAPIKeyPromotionShowed}
+                                />
                             </FormItem>
                             <div
                                 style={{
-                                    display: values.ollamaAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    display: values.provider === 'Ollama' ? 'block' : 'none',
                                 }}
                             >
                                 <FormItem
-                                    name='ollamaCustomModelName'
-                                    label={t('Custom Model Name')}
-                                    required={values.provider === 'Ollama' && values.ollamaAPIModel === CUSTOM_MODEL_ID}
+                                    name='ollamaAPIURL'
+                                    label={t('API URL')}
+                                    required={values.provider === 'Ollama'}
+                                    caption={t('Generally, there is no need to modify this item.')}
+                                >
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='ollamaModelLifetimeInMemory'
+                                    label={t('The survival time of the Ollama model in memory')}
+                                    required={values.provider === 'Ollama'}
                                 >
-                                    <Input autoComplete='off' size='compact' />
+                                    <DurationPicker size='compact' />
                                 </FormItem>
+                                <FormItem
+                                    name='ollamaAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Ollama'}
+                                    caption={
+                                        <div>
+                                            <div>
+                                                {t(
+                                                    'Model needs to first use the `ollama pull` command to download locally, please view all models from this page:'
+                                                )}{' '}
+                                                <a
+                                                    target='_blank'
+                                                    href='https://ollama.com/library'
+                                                    rel='noreferrer'
+                                                    style={linkStyle}
+                                                >
+                                                    Models
+                                                </a>
+                                            </div>
+                                        </div>
+                                    }
+                                >
+                                    <APIModelSelector provider='Ollama' currentProvider={values.provider} onBlur={onBlur} />
+                                </FormItem>
+                                <div
+                                    style={{
+                                        display: values.ollamaAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    }}
+                                >
+                                    <FormItem
+                                        name='ollamaCustomModelName'
+                                        label={t('Custom Model Name')}
+                                        required={values.provider === 'Ollama' && values.ollamaAPIModel === CUSTOM_MODEL_ID}
+                                    >
+                                        <Input autoComplete='off' size='compact' />
+                                    </FormItem>
+                                </div>
                             </div>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Groq' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'Groq'}
-                                name='groqAPIKey'
-                                label='Groq API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://console.groq.com/keys'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            GroqCloud
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
-                            >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem name='groqAPIModel' label={t('API Model')} required={values.provider === 'Groq'}>
-                                <APIModelSelector
-                                    provider='Groq'
-                                    currentProvider={values.provider}
-                                    apiKey={values.groqAPIKey}
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
                             <div
                                 style={{
-                                    display: values.groqAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    display: values.provider === 'Groq' ? 'block' : 'none',
                                 }}
                             >
                                 <FormItem
-                                    name='groqCustomModelName'
-                                    label={t('Custom Model Name')}
-                                    required={values.provider === 'Groq' && values.groqAPIModel === CUSTOM_MODEL_ID}
+                                    required={values.provider === 'Groq'}
+                                    name='groqAPIKey'
+                                    label='Groq API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://console.groq.com/keys'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                GroqCloud
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem name='groqAPIModel' label={t('API Model')} required={values.provider === 'Groq'}>
+                                    <APIModelSelector
+                                        provider='Groq'
+                                        currentProvider={values.provider}
+                                        apiKey={values.groqAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                                <div
+                                    style={{
+                                        display: values.groqAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    }}
+                                >
+                                    <FormItem
+                                        name='groqCustomModelName'
+                                        label={t('Custom Model Name')}
+                                        required={values.provider === 'Groq' && values.groqAPIModel === CUSTOM_MODEL_ID}
+                                    >
+                                        <Input autoComplete='off' size='compact' />
+                                    </FormItem>
+                                </div>
+                                <FormItem
+                                    name='groqAPIURL'
+                                    label={t('API URL')}
+                                    required={values.provider === 'Groq'}
+                                    caption={t('Generally, there is no need to modify this item.')}
+                                >
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='groqAPIURLPath'
+                                    label={t('API URL Path')}
+                                    required={values.provider === 'Groq'}
+                                    caption={t('Generally, there is no need to modify this item.')}
                                 >
-                                    <Input autoComplete='off' size='compact' />
+                                    <Input size='compact' onBlur={onBlur} />
                                 </FormItem>
                             </div>
-                            <FormItem
-                                name='groqAPIURL'
-                                label={t('API URL')}
-                                required={values.provider === 'Groq'}
-                                caption={t('Generally, there is no need to modify this item.')}
-                            >
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='groqAPIURLPath'
-                                label={t('API URL Path')}
-                                required={values.provider === 'Groq'}
-                                caption={t('Generally, there is no need to modify this item.')}
-                            >
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Claude' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'Claude'}
-                                name='claudeAPIKey'
-                                label='Claude API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://console.anthropic.com/settings/keys'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Anthropic Console
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
-                            >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='claudeAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Claude'}
+                            <div
+                                style={{
+                                    display: values.provider === 'Claude' ? 'block' : 'none',
+                                }}
                             >
-                                <APIModelSelector
-                                    provider='Claude'
-                                    currentProvider={values.provider}
-                                    apiKey={values.claudeAPIKey}
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
+                                <FormItem
+                                    required={values.provider === 'Claude'}
+                                    name='claudeAPIKey'
+                                    label='Claude API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://console.anthropic.com/settings/keys'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Anthropic Console
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='claudeAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Claude'}
+                                >
+                                    <APIModelSelector
+                                        provider='Claude'
+                                        currentProvider={values.provider}
+                                        apiKey={values.claudeAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                                <div
+                                    style={{
+                                        display: values.claudeAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    }}
+                                >
+                                    <FormItem
+                                        name='claudeCustomModelName'
+                                        label={t('Custom Model Name')}
+                                        required={values.provider === 'Claude' && values.claudeAPIModel === CUSTOM_MODEL_ID}
+                                    >
+                                        <Input autoComplete='off' size='compact' />
+                                    </FormItem>
+                                </div>
+                                <FormItem name='claudeThinking' label={t('Enable Extended Thinking')}>
+                                    <MyCheckbox onBlur={onBlur} />
+                                </FormItem>
+                                <div
+                                    style={{
+                                        display: values.claudeThinking ? 'block' : 'none',
+                                    }}
+                                >
+                                    <FormItem name='claudeThinkingLevel' label={t('Thinking Level')}>
+                                        <ThinkingLevelSelector onBlur={onBlur} />
+                                    </FormItem>
+                                </div>
+                                <FormItem
+                                    name='claudeAPIURL'
+                                    label={t('API URL')}
+                                    required={values.provider === 'Claude'}
+                                    caption={t('Generally, there is no need to modify this item.')}
+                                >
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='claudeAPIURLPath'
+                                    label={t('API URL Path')}
+                                    required={values.provider === 'Claude'}
+                                    caption={t('Generally, there is no need to modify this item.')}
+                                >
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                            </div>
                             <div
                                 style={{
-                                    display: values.claudeAPIModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    display: values.provider === 'Kimi' && utils.isDesktopApp() ? 'block' : 'none',
                                 }}
                             >
                                 <FormItem
-                                    name='claudeCustomModelName'
-                                    label={t('Custom Model Name')}
-                                    required={values.provider === 'Claude' && values.claudeAPIModel === CUSTOM_MODEL_ID}
+                                    required={values.provider === 'Kimi' && utils.isDesktopApp()}
+                                    name='kimiRefreshToken'
+                                    label='Kimi Refresh Token'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href={
+                                                    values?.i18n?.toLowerCase().includes('zh')
+                                                        ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi-cn.md'
+                                                        : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi.md'
+                                                }
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Tutorial
+                                            </a>{' '}
+                                            {t('to get your refresh_token.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    required={values.provider === 'Kimi' && utils.isDesktopApp()}
+                                    name='kimiAccessToken'
+                                    label='Kimi Access Token'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href={
+                                                    values?.i18n?.toLowerCase().includes('zh')
+                                                        ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi-cn.md'
+                                                        : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi.md'
+                                                }
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Tutorial
+                                            </a>{' '}
+                                            {t('to get your access_token.')}
+                                        </div>
+                                    }
                                 >
-                                    <Input autoComplete='off' size='compact' />
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
                                 </FormItem>
                             </div>
-                            <FormItem name='claudeThinking' label={t('Enable Extended Thinking')}>
-                                <MyCheckbox onBlur={onBlur} />
-                            </FormItem>
                             <div
                                 style={{
-                                    display: values.claudeThinking ? 'block' : 'none',
+                                    display: values.provider === 'ChatGLM' && utils.isDesktopApp() ? 'block' : 'none',
                                 }}
                             >
-                                <FormItem name='claudeThinkingLevel' label={t('Thinking Level')}>
-                                    <ThinkingLevelSelector onBlur={onBlur} />
+                                <FormItem
+                                    required={values.provider === 'ChatGLM' && utils.isDesktopApp()}
+                                    name='chatglmRefreshToken'
+                                    label={`${t('ChatGLM')} Refresh Token`}
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href={
+                                                    values?.i18n?.toLowerCase().includes('zh')
+                                                        ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm-cn.md'
+                                                        : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm.md'
+                                                }
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Tutorial
+                                            </a>{' '}
+                                            {t('to get your refresh_token.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    required={values.provider === 'ChatGLM' && utils.isDesktopApp()}
+                                    name='chatglmAccessToken'
+                                    label={`${t('ChatGLM')} Token`}
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href={
+                                                    values?.i18n?.toLowerCase().includes('zh')
+                                                        ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm-cn.md'
+                                                        : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm.md'
+                                                }
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Tutorial
+                                            </a>{' '}
+                                            {t('to get your token.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
                                 </FormItem>
                             </div>
-                            <FormItem
-                                name='claudeAPIURL'
-                                label={t('API URL')}
-                                required={values.provider === 'Claude'}
-                                caption={t('Generally, there is no need to modify this item.')}
+                            <div
+                                style={{
+                                    display: values.provider === 'Gemini' ? 'block' : 'none',
+                                }}
                             >
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='claudeAPIURLPath'
-                                label={t('API URL Path')}
-                                required={values.provider === 'Claude'}
-                                caption={t('Generally, there is no need to modify this item.')}
+                                <FormItem name='geminiAPIURL' label={t('API URL')} required={values.provider === 'Gemini'}>
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    required={values.provider === 'Gemini'}
+                                    name='geminiAPIKey'
+                                    label='Gemini API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://makersuite.google.com/app/apikey'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Google AI Studio
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='geminiAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Gemini'}
+                                >
+                                    <APIModelSelector
+                                        provider='Gemini'
+                                        currentProvider={values.provider}
+                                        apiKey={values.geminiAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'Cohere' ? 'block' : 'none',
+                                }}
                             >
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Kimi' && utils.isDesktopApp() ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'Kimi' && utils.isDesktopApp()}
-                                name='kimiRefreshToken'
-                                label='Kimi Refresh Token'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href={
-                                                values?.i18n?.toLowerCase().includes('zh')
-                                                    ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi-cn.md'
-                                                    : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi.md'
-                                            }
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Tutorial
-                                        </a>{' '}
-                                        {t('to get your refresh_token.')}
-                                    </div>
-                                }
-                            >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                required={values.provider === 'Kimi' && utils.isDesktopApp()}
-                                name='kimiAccessToken'
-                                label='Kimi Access Token'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href={
-                                                values?.i18n?.toLowerCase().includes('zh')
-                                                    ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi-cn.md'
-                                                    : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/kimi.md'
-                                            }
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Tutorial
-                                        </a>{' '}
-                                        {t('to get your access_token.')}
-                                    </div>
-                                }
+                                <FormItem
+                                    required={values.provider === 'Cohere'}
+                                    name='cohereAPIKey'
+                                    label='Cohere API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://dashboard.cohere.com/api-keys'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Cohere Dashboard
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='cohereAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Cohere'}
+                                >
+                                    <APIModelSelector
+                                        provider='Cohere'
+                                        currentProvider={values.provider}
+                                        apiKey={values.cohereAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'DeepSeek' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'ChatGLM' && utils.isDesktopApp() ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'ChatGLM' && utils.isDesktopApp()}
-                                name='chatglmRefreshToken'
-                                label={`${t('ChatGLM')} Refresh Token`}
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href={
-                                                values?.i18n?.toLowerCase().includes('zh')
-                                                    ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm-cn.md'
-                                                    : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm.md'
-                                            }
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Tutorial
-                                        </a>{' '}
-                                        {t('to get your refresh_token.')}
-                                    </div>
-                                }
+                                <FormItem
+                                    required={values.provider === 'DeepSeek'}
+                                    name='deepSeekAPIKey'
+                                    label='DeepSeek API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://platform.deepseek.com/api_keys'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                DeepSeek Dashboard
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='deepSeekAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'DeepSeek'}
+                                >
+                                    <APIModelSelector
+                                        provider='DeepSeek'
+                                        currentProvider={values.provider}
+                                        apiKey={values.deepSeekAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'OpenAI' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                required={values.provider === 'ChatGLM' && utils.isDesktopApp()}
-                                name='chatglmAccessToken'
-                                label={`${t('ChatGLM')} Token`}
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href={
-                                                values?.i18n?.toLowerCase().includes('zh')
-                                                    ? 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm-cn.md'
-                                                    : 'https://github.com/nextai-translator/nextai-translator/blob/main/docs/chatglm.md'
-                                            }
-                                            rel='noreferrer'
-                                            style={linkStyle}
+                                <FormItem
+                                    required={values.provider === 'OpenAI'}
+                                    name='apiKeys'
+                                    label={t('API Key')}
+                                    caption={
+                                        <div
+                                            style={{
+                                                display: 'flex',
+                                                flexDirection: 'column',
+                                                gap: 3,
+                                            }}
                                         >
-                                            Tutorial
-                                        </a>{' '}
-                                        {t('to get your token.')}
-                                    </div>
-                                }
+                                            <div>
+                                                {t('Go to the')}{' '}
+                                                <a
+                                                    target='_blank'
+                                                    href='https://platform.openai.com/account/api-keys'
+                                                    rel='noreferrer'
+                                                    style={linkStyle}
+                                                >
+                                                    {t('OpenAI page')}
+                                                </a>{' '}
+                                                {t(
+                                                    'to get your API Key. You can separate multiple API Keys with English commas to achieve quota doubling and load balancing.'
+                                                )}
+                                            </div>
+                                            {openaiAPIKeyPromotion && (
+                                                <div className={styles.promotion}>
+                                                    <div
+                                                        onClick={(e) => {
+                                                            if ((e.target as HTMLElement).tagName === 'A') {
+                                                                const href = (e.target as HTMLAnchorElement).href
+                                                                if (href && href.startsWith('http')) {
+                                                                    e.preventDefault()
+                                                                    e.stopPropagation()
+                                                                    setDisclaimerPromotion(openaiAPIKeyPromotion)
+                                                                    setDisclaimerAgreeLink(href)
+                                                                }
+                                                            }
+                                                        }}
+                                                    >
+                                                        {renderI18nPromotionContent(openaiAPIKeyPromotion.promotion)}
+                                                    </div>
+                                                    {openaiAPIKeyPromotion.configuration_doc_link && (
+                                                        <div
+                                                            style={{
+                                                                display: 'flex',
+                                                                flexDirection: 'row',
+                                                                alignItems: 'center',
+                                                                gap: 3,
+                                                            }}
+                                                        >
+                                                            <IoIosHelpCircleOutline size={12} />
+                                                            <a
+                                                                href={openaiAPIKeyPromotion.configuration_doc_link}
+                                                                target='_blank'
+                                                                rel='noreferrer'
+                                                            >
+                                                                {t('How to Use')}
+                                                            </a>
+                                                        </div>
+                                                    )}
+                                                </div>
+                                            )}
+                                        </div>
+                                    }
+                                >
+                                    <Input
+                                        autoFocus={!openaiAPIKeyPromotion}
+                                        type='password'
+                                        size='compact'
+                                        name='apiKey'
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                                <FormItem
+                                    name='noModelsAPISupport'
+                                    label={t('No models API support')}
+                                    caption={t(
+                                        "Some providers claiming to be compatible with OpenAI's API do not actually support OpenAI's standard model API. Therefore, we have no choice but to offer this option. If you choose this option (and then need to click the save button), we will not attempt to dynamically fetch the latest model list from the model API, but will only use a fixed model list and custom models."
+                                    )}
+                                >
+                                    <MyCheckbox onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem name='apiModel' label={t('API Model')} required={values.provider === 'OpenAI'}>
+                                    <APIModelSelector
+                                        provider='OpenAI'
+                                        currentProvider={values.provider}
+                                        apiKey={values.apiKeys}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                                <div
+                                    style={{
+                                        display: values.apiModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    }}
+                                >
+                                    <FormItem
+                                        name='customModelName'
+                                        label={t('Custom Model Name')}
+                                        required={values.provider === 'OpenAI' && values.apiModel === CUSTOM_MODEL_ID}
+                                    >
+                                        <Input autoComplete='off' size='compact' />
+                                    </FormItem>
+                                </div>
+                                <FormItem name='apiURL' label={t('API URL')} required={values.provider === 'OpenAI'}>
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='apiURLPath'
+                                    label={t('API URL Path')}
+                                    required={values.provider === 'OpenAI'}
+                                >
+                                    <Input size='compact' />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'Azure' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Gemini' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem name='geminiAPIURL' label={t('API URL')} required={values.provider === 'Gemini'}>
-                                <Input size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                required={values.provider === 'Gemini'}
-                                name='geminiAPIKey'
-                                label='Gemini API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://makersuite.google.com/app/apikey'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Google AI Studio
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                <FormItem
+                                    required={values.provider === 'Azure'}
+                                    name='azureAPIKeys'
+                                    label={t('API Key')}
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://learn.microsoft.com/en-us/azure/cognitive-services/openai/chatgpt-quickstart?tabs=command-line&pivots=rest-api#retrieve-key-and-endpoint'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                {t('Azure OpenAI Service page')}
+                                            </a>{' '}
+                                            {t(
+                                                'to get your API Key. You can separate multiple API Keys with English commas to achieve quota doubling and load balancing.'
+                                            )}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='azureAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Azure'}
+                                >
+                                    <APIModelSelector
+                                        provider='Azure'
+                                        currentProvider={values.provider}
+                                        apiKey={values.azureAPIKeys}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                                <FormItem name='azureAPIURL' label={t('API URL')} required={values.provider === 'Azure'}>
+                                    <Input size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='azureAPIURLPath'
+                                    label={t('API URL Path')}
+                                    required={values.provider === 'Azure'}
+                                >
+                                    <Input size='compact' />
+                                </FormItem>
+                                <FormItem name='azMaxWords' label='Max Tokens' required={values.provider === 'Azure'}>
+                                    <NumberInput size='compact' />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'ChatGPT' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='geminiAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Gemini'}
+                                <FormItem
+                                    name='chatgptModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'ChatGPT'}
+                                >
+                                    <APIModelSelector
+                                        provider='ChatGPT'
+                                        currentProvider={values.provider}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'MiniMax' ? 'block' : 'none',
+                                }}
                             >
-                                <APIModelSelector
-                                    provider='Gemini'
-                                    currentProvider={values.provider}
-                                    apiKey={values.geminiAPIKey}
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Cohere' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'Cohere'}
-                                name='cohereAPIKey'
-                                label='Cohere API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://dashboard.cohere.com/api-keys'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Cohere Dashboard
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                <FormItem
+                                    required={values.provider === 'MiniMax'}
+                                    name='miniMaxAPIKey'
+                                    label='MiniMax API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://platform.minimaxi.com/user-center/basic-information/interface-key'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                {t('MiniMax page')}
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='miniMaxAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'MiniMax'}
+                                >
+                                    <APIModelSelector
+                                        provider='MiniMax'
+                                        currentProvider={values.provider}
+                                        onBlur={onBlur}
+                                        apiKey={values.miniMaxAPIKey}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'Moonshot' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='cohereAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Cohere'}
+                                <FormItem
+                                    required={values.provider === 'Moonshot'}
+                                    name='moonshotAPIKey'
+                                    label='Moonshot API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://www.moonshot.cn/'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Moonshot Page
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='moonshotAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Moonshot'}
+                                >
+                                    <APIModelSelector
+                                        provider='Moonshot'
+                                        currentProvider={values.provider}
+                                        onBlur={onBlur}
+                                        apiKey={values.moonshotAPIKey}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'Cerebras' ? 'block' : 'none',
+                                }}
                             >
-                                <APIModelSelector
-                                    provider='Cohere'
-                                    currentProvider={values.provider}
-                                    apiKey={values.cohereAPIKey}
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'DeepSeek' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'DeepSeek'}
-                                name='deepSeekAPIKey'
-                                label='DeepSeek API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://platform.deepseek.com/api_keys'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            DeepSeek Dashboard
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                <FormItem
+                                    required={values.provider === 'Cerebras'}
+                                    name='cerebrasAPIKey'
+                                    label='Cerebras API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://cloud.cerebras.ai/'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                Cerebras Page
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='cerebrasAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'Cerebras'}
+                                >
+                                    <APIModelSelector
+                                        provider='Cerebras'
+                                        currentProvider={values.provider}
+                                        apiKey={values.cerebrasAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'TeamoRouter' ? 'block' : 'none',
+                                }}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
-                            </FormItem>
-                            <FormItem
-                                name='deepSeekAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'DeepSeek'}
+                                <FormItem
+                                    required={values.provider === 'TeamoRouter'}
+                                    name='teamoRouterAPIKey'
+                                    label='TeamoRouter API Key'
+                                    caption={
+                                        <div>
+                                            {t('Go to the')}{' '}
+                                            <a
+                                                target='_blank'
+                                                href='https://teamorouter.com/?utm_source=nextai_translator&utm_medium=referral&utm_campaign=ai_directory'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                TeamoRouter Page
+                                            </a>{' '}
+                                            {t('to get your API Key.')}
+                                        </div>
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='teamoRouterAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'TeamoRouter'}
+                                >
+                                    <APIModelSelector
+                                        provider='TeamoRouter'
+                                        currentProvider={values.provider}
+                                        apiKey={values.teamoRouterAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'OpenRouter' ? 'block' : 'none',
+                                }}
                             >
-                                <APIModelSelector
-                                    provider='DeepSeek'
-                                    currentProvider={values.provider}
-                                    apiKey={values.deepSeekAPIKey}
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'OpenAI' ? 'block' : 'none',
-                            }}
-                        >
-                            <FormItem
-                                required={values.provider === 'OpenAI'}
-                                name='apiKeys'
-                                label={t('API Key')}
-                                caption={
-                                    <div
-                                        style={{
-                                            display: 'flex',
-                                            flexDirection: 'column',
-                                            gap: 3,
-                                        }}
-                                    >
+                                <FormItem
+                                    required={values.provider === 'OpenRouter'}
+                                    name='openRouterAPIKey'
+                                    label='OpenRouter API Key'
+                                    caption={
                                         <div>
                                             {t('Go to the')}{' '}
                                             <a
                                                 target='_blank'
-                                                href='https://platform.openai.com/account/api-keys'
+                                                href='https://openrouter.ai/settings/keys'
                                                 rel='noreferrer'
                                                 style={linkStyle}
                                             >
-                                                {t('OpenAI page')}
+                                                OpenRouter Page
                                             </a>{' '}
-                                            {t(
-                                                'to get your API Key. You can separate multiple API Keys with English commas to achieve quota doubling and load balancing.'
-                                            )}
+                                            {t('to get your API Key.')}
                                         </div>
-                                        {openaiAPIKeyPromotion && (
-                                            <div className={styles.promotion}>
-                                                <div
-                                                    onClick={(e) => {
-                                                        if ((e.target as HTMLElement).tagName === 'A') {
-                                                            const href = (e.target as HTMLAnchorElement).href
-                                                            if (href && href.startsWith('http')) {
-                                                                e.preventDefault()
-                                                                e.stopPropagation()
-                                                                setDisclaimerPromotion(openaiAPIKeyPromotion)
-                                                                setDisclaimerAgreeLink(href)
-                                                            }
-                                                        }
-                                                    }}
-                                                >
-                                                    {renderI18nPromotionContent(openaiAPIKeyPromotion.promotion)}
-                                                </div>
-                                                {openaiAPIKeyPromotion.configuration_doc_link && (
-                                                    <div
-                                                        style={{
-                                                            display: 'flex',
-                                                            flexDirection: 'row',
-                                                            alignItems: 'center',
-                                                            gap: 3,
-                                                        }}
-                                                    >
-                                                        <IoIosHelpCircleOutline size={12} />
-                                                        <a
-                                                            href={openaiAPIKeyPromotion.configuration_doc_link}
-                                                            target='_blank'
-                                                            rel='noreferrer'
-                                                        >
-                                                            {t('How to Use')}
-                                                        </a>
-                                                    </div>
-                                                )}
-                                            </div>
-                                        )}
-                                    </div>
-                                }
+                                    }
+                                >
+                                    <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='openRouterAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'OpenRouter'}
+                                >
+                                    <APIModelSelector
+                                        provider='OpenRouter'
+                                        currentProvider={values.provider}
+                                        apiKey={values.openRouterAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
+                            <div
+                                style={{
+                                    display: values.provider === 'LiteLLM' ? 'block' : 'none',
+                                }}
                             >
-                                <Input
-                                    autoFocus={!openaiAPIKeyPromotion}
-                                    type='password'
-                                    size='compact'
-                                    name='apiKey'
-                                    onBlur={onBlur}
-                                />
-                            </FormItem>
+                                <FormItem
+                                    required={values.provider === 'LiteLLM'}
+                                    name='liteLLMAPIURL'
+                                    label={t('API URL')}
+                                    caption='The base URL of your LiteLLM proxy server, e.g. http://localhost:4000'
+                                >
+                                    <Input autoFocus size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    required={values.provider === 'LiteLLM'}
+                                    name='liteLLMAPIKey'
+                                    label='LiteLLM API Key'
+                                    caption='Your LiteLLM proxy virtual key or master key. Leave blank if your proxy has no authentication.'
+                                >
+                                    <Input type='password' size='compact' onBlur={onBlur} />
+                                </FormItem>
+                                <FormItem
+                                    name='liteLLMAPIModel'
+                                    label={t('API Model')}
+                                    required={values.provider === 'LiteLLM'}
+                                >
+                                    <APIModelSelector
+                                        provider='LiteLLM'
+                                        currentProvider={values.provider}
+                                        apiKey={values.liteLLMAPIKey}
+                                        onBlur={onBlur}
+                                    />
+                                </FormItem>
+                            </div>
                             <FormItem
-                                name='noModelsAPISupport'
-                                label={t('No models API support')}
+                                name='thinkingEnabled'
+                                label={t('Enable Thinking')}
                                 caption={t(
-                                    "Some providers claiming to be compatible with OpenAI's API do not actually support OpenAI's standard model API. Therefore, we have no choice but to offer this option. If you choose this option (and then need to click the save button), we will not attempt to dynamically fetch the latest model list from the model API, but will only use a fixed model list and custom models."
+                                    'Disable thinking for faster translations. Reasoning models think by default, which significantly slows down simple tasks.'
                                 )}
                             >
                                 <MyCheckbox onBlur={onBlur} />
                             </FormItem>
-                            <FormItem name='apiModel' label={t('API Model')} required={values.provider === 'OpenAI'}>
-                                <APIModelSelector
-                                    provider='OpenAI'
-                                    currentProvider={values.provider}
-                                    apiKey={values.apiKeys}
-                                    onBlur={onBlur}
-                                />
+                            <FormItem name='defaultTranslateMode' label={t('Default Action')}>
+                                <TranslateModeSelector onBlur={onBlur} />
                             </FormItem>
-                            <div
+                            <PerActionModelConfig settings={values} />
+                            <FormItem name='defaultTargetLanguage' label={t('Default target language')}>
+                                <LanguageSelector onBlur={onBlur} />
+                            </FormItem>
+                            <FormItem name='languageDetectionEngine' label={t('Language detection engine')}>
+                                <LanguageDetectionEngineSelector onBlur={onBlur} />
+                            </FormItem>
+                            <FormItem name='themeType' label={t('Theme')}>
+                                <ThemeTypeSelector onBlur={onBlur} />
+                            </FormItem>
+                            <FormItem
                                 style={{
-                                    display: values.apiModel === CUSTOM_MODEL_ID ? 'block' : 'none',
+                                    display: isDesktopApp ? 'block' : 'none',
                                 }}
+                                name='enableBackgroundBlur'
+                                label={t('Window background blur')}
+                                caption={t(
+                                    "If the window background blur effect is enabled, please ensure to set the 'Theme' to 'Follow the System', as it is currently not possible to manually switch between light and dark themes when the window background blur is active."
+                                )}
                             >
-                                <FormItem
-                                    name='customModelName'
-                                    label={t('Custom Model Name')}
-                                    required={values.provider === 'OpenAI' && values.apiModel === CUSTOM_MODEL_ID}
-                                >
-                                    <Input autoComplete='off' size='compact' />
-                                </FormItem>
-                            </div>
-                            <FormItem name='apiURL' label={t('API URL')} required={values.provider === 'OpenAI'}>
-                                <Input size='compact' onBlur={onBlur} />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
                             <FormItem
-                                name='apiURLPath'
-                                label={t('API URL Path')}
-                                required={values.provider === 'OpenAI'}
+                                name='fontSize'
+                                label={t('Font size')}
+                                caption={t('Controls the font size of the input box and the translation text.')}
                             >
-                                <Input size='compact' />
+                                <NumberInput min={8} max={40} step={1} />
                             </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Azure' ? 'block' : 'none',
-                            }}
-                        >
                             <FormItem
-                                required={values.provider === 'Azure'}
-                                name='azureAPIKeys'
-                                label={t('API Key')}
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://learn.microsoft.com/en-us/azure/cognitive-services/openai/chatgpt-quickstart?tabs=command-line&pivots=rest-api#retrieve-key-and-endpoint'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            {t('Azure OpenAI Service page')}
-                                        </a>{' '}
-                                        {t(
-                                            'to get your API Key. You can separate multiple API Keys with English commas to achieve quota doubling and load balancing.'
-                                        )}
-                                    </div>
-                                }
+                                name='uiFontSize'
+                                label={t('UI font size')}
+                                caption={t('Controls the font size of the app interface (toolbar, menus, labels).')}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                <NumberInput min={8} max={24} step={1} />
                             </FormItem>
                             <FormItem
-                                name='azureAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Azure'}
+                                name='alwaysShowIcons'
+                                label={t('Show icon when text is selected')}
+                                caption={
+                                    isDesktopApp && (
+                                        <div
+                                            style={{
+                                                display: 'flex',
+                                                alignItems: 'center',
+                                                gap: 6,
+                                            }}
+                                        >
+                                            {t(
+                                                'It is highly recommended to disable this feature and use the Clip Extension'
+                                            )}
+                                            <a
+                                                href='https://github.com/nextai-translator/nextai-translator/blob/main/CLIP-EXTENSIONS.md'
+                                                target='_blank'
+                                                rel='noreferrer'
+                                                style={linkStyle}
+                                            >
+                                                {t('Clip Extension')}
+                                            </a>
+                                        </div>
+                                    )
+                                }
                             >
-                                <APIModelSelector
-                                    provider='Azure'
-                                    currentProvider={values.provider}
-                                    apiKey={values.azureAPIKeys}
-                                    onBlur={onBlur}
-                                />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
-                            <FormItem name='azureAPIURL' label={t('API URL')} required={values.provider === 'Azure'}>
-                                <Input size='compact' onBlur={onBlur} />
+                            <FormItem name='autoTranslate' label={t('Auto Translate')}>
+                                <AutoTranslateCheckbox onBlur={onBlur} />
                             </FormItem>
                             <FormItem
-                                name='azureAPIURLPath'
-                                label={t('API URL Path')}
-                                required={values.provider === 'Azure'}
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='restorePreviousPosition'
+                                label={t('Fixed Position')}
                             >
-                                <Input size='compact' />
+                                <RestorePreviousPositionCheckbox onBlur={onBlur} />
                             </FormItem>
-                            <FormItem name='azMaxWords' label='Max Tokens' required={values.provider === 'Azure'}>
-                                <NumberInput size='compact' />
+                            <FormItem name='selectInputElementsText' label={t('Word selection in input')}>
+                                <SelectInputElementsCheckbox onBlur={onBlur} />
                             </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'ChatGPT' ? 'block' : 'none',
-                            }}
-                        >
+                            {isTauri && (
+                                <FormItem name='runAtStartup' label={t('Run at startup')}>
+                                    <RunAtStartupCheckbox onBlur={onBlur} />
+                                </FormItem>
+                            )}
                             <FormItem
-                                name='chatgptModel'
-                                label={t('API Model')}
-                                required={values.provider === 'ChatGPT'}
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='hideTheIconInTheDock'
+                                label={isMacOS ? t('Hide the icon in the Dock bar') : t('Hide the icon in the taskbar')}
                             >
-                                <APIModelSelector
-                                    provider='ChatGPT'
-                                    currentProvider={values.provider}
-                                    onBlur={onBlur}
-                                />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'MiniMax' ? 'block' : 'none',
-                            }}
-                        >
                             <FormItem
-                                required={values.provider === 'MiniMax'}
-                                name='miniMaxAPIKey'
-                                label='MiniMax API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://platform.minimaxi.com/user-center/basic-information/interface-key'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            {t('MiniMax page')}
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='autoHideWindowWhenOutOfFocus'
+                                label={t('Auto hide window when out of focus')}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
                             <FormItem
-                                name='miniMaxAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'MiniMax'}
+                                name='useCompactLookup'
+                                label={t('Compact inline lookup mode')}
+                                caption={t(
+                                    'When enabled, text selection translation shows a compact popup with only the translated result'
+                                )}
                             >
-                                <APIModelSelector
-                                    provider='MiniMax'
-                                    currentProvider={values.provider}
-                                    onBlur={onBlur}
-                                    apiKey={values.miniMaxAPIKey}
-                                />
+                                <UseCompactLookupCheckbox onBlur={onBlur} />
                             </FormItem>
-                        </div>
-                        <div
-                            style={{
-                                display: values.provider === 'Moonshot' ? 'block' : 'none',
-                            }}
-                        >
                             <FormItem
-                                required={values.provider === 'Moonshot'}
-                                name='moonshotAPIKey'
-                                label='Moonshot API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://www.moonshot.cn/'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Moonshot Page
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='automaticCheckForUpdates'
+                                label={t('Automatic check for updates')}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
                             <FormItem
-                                name='moonshotAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Moonshot'}
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='disableCollectingStatistics'
+                                label={t('disable collecting statistics')}
                             >
-                                <APIModelSelector
-                                    provider='Moonshot'
-                                    currentProvider={values.provider}
-                                    onBlur={onBlur}
-                                    apiKey={values.moonshotAPIKey}
-                                />
+                                <MyCheckbox onBlur={onBlur} />
                             </FormItem>
                         </div>
                         <div
                             style={{
-                                display: values.provider === 'Cerebras' ? 'block' : 'none',
+                                display: isTauri && activeTab === 'proxy' ? 'block' : 'none',
                             }}
                         >
-                            <FormItem
-                                required={values.provider === 'Cerebras'}
-                                name='cerebrasAPIKey'
-                                label='Cerebras API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://cloud.cerebras.ai/'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            Cerebras Page
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
-                            >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                            <FormItem name={['proxy', 'enabled']} label={t('Enabled')}>
+                                <MyCheckbox />
                             </FormItem>
-                            <FormItem
-                                name='cerebrasAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'Cerebras'}
-                            >
-                                <APIModelSelector
-                                    provider='Cerebras'
-                                    currentProvider={values.provider}
-                                    apiKey={values.cerebrasAPIKey}
-                                    onBlur={onBlur}
-                                />
+                            <FormItem name={['proxy', 'protocol']} label={t('Protocol')}>
+                                <ProxyProtocolSelector />
+                            </FormItem>
+                            <FormItem name={['proxy', 'server']} label={t('Server')}>
+                                <Input size='compact' />
+                            </FormItem>
+                            <FormItem name={['proxy', 'port']} label={t('Port')}>
+                                <Input type='number' size='compact' />
+                            </FormItem>
+                            <FormItem name={['proxy', 'basicAuth', 'username']} label={t('Username')}>
+                                <Input size='compact' />
+                            </FormItem>
+                            <FormItem name={['proxy', 'basicAuth', 'password']} label={t('Password')}>
+                                <Input type='password' size='compact' />
                             </FormItem>
+                            <FormItem name={['proxy', 'noProxy']} label={t('No proxy')}>
+                                <Textarea size='compact' />
+                            </FormItem>
+                            <ProxyTester proxy={values.proxy} />
                         </div>
                         <div
                             style={{
-                                display: values.provider === 'TeamoRouter' ? 'block' : 'none',
+                                display: activeTab === 'tts' ? 'block' : 'none',
                             }}
                         >
                             <FormItem
-                                required={values.provider === 'TeamoRouter'}
-                                name='teamoRouterAPIKey'
-                                label='TeamoRouter API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://teamorouter.com/?utm_source=nextai_translator&utm_medium=referral&utm_campaign=ai_directory'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            TeamoRouter Page
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                name='readSelectedWordsFromInputElementsText'
+                                label={t('Read the selected words in input')}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                <ReadSelectedWordsFromInputElementsCheckbox onBlur={onBlur} />
                             </FormItem>
-                            <FormItem
-                                name='teamoRouterAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'TeamoRouter'}
-                            >
-                                <APIModelSelector
-                                    provider='TeamoRouter'
-                                    currentProvider={values.provider}
-                                    apiKey={values.teamoRouterAPIKey}
-                                    onBlur={onBlur}
-                                />
+                            <FormItem name='tts' label={t('TTS')}>
+                                <TTSVoicesSettings onBlur={onBlur} />
                             </FormItem>
                         </div>
                         <div
                             style={{
-                                display: values.provider === 'OpenRouter' ? 'block' : 'none',
+                                display: activeTab === 'writing' ? 'block' : 'none',
                             }}
                         >
                             <FormItem
-                                required={values.provider === 'OpenRouter'}
-                                name='openRouterAPIKey'
-                                label='OpenRouter API Key'
-                                caption={
-                                    <div>
-                                        {t('Go to the')}{' '}
-                                        <a
-                                            target='_blank'
-                                            href='https://openrouter.ai/settings/keys'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            OpenRouter Page
-                                        </a>{' '}
-                                        {t('to get your API Key.')}
-                                    </div>
-                                }
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='writingTargetLanguage'
+                                label={t('Writing target language')}
+                            >
+                                <LanguageSelector onBlur={onBlur} />
+                            </FormItem>
+                            <FormItem
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='writingHotkey'
+                                label={t('Writing Hotkey')}
+                                caption={t(
+                                    'Press this shortcut key in the input box of any application, and the text already entered in the input box will be automatically translated into the writing target language.'
+                                )}
                             >
-                                <Input autoFocus type='password' size='compact' onBlur={onBlur} />
+                                <HotkeyRecorder onBlur={onBlur} testId='writing-hotkey-recorder' />
                             </FormItem>
                             <FormItem
-                                name='openRouterAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'OpenRouter'}
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='writingNewlineHotkey'
+                                label={t('Writing line break shortcut')}
+                                caption={t('When writing, which key should be pressed when encountering a line break?')}
                             >
-                                <APIModelSelector
-                                    provider='OpenRouter'
-                                    currentProvider={values.provider}
-                                    apiKey={values.openRouterAPIKey}
-                                    onBlur={onBlur}
-                                />
+                                <HotkeyRecorder onBlur={onBlur} testId='writing-newline-hotkey-recorder' />
                             </FormItem>
                         </div>
                         <div
                             style={{
-                                display: values.provider === 'LiteLLM' ? 'block' : 'none',
+                                display: activeTab === 'shortcuts' ? 'block' : 'none',
                             }}
                         >
-                            <FormItem
-                                required={values.provider === 'LiteLLM'}
-                                name='liteLLMAPIURL'
-                                label={t('API URL')}
-                                caption='The base URL of your LiteLLM proxy server, e.g. http://localhost:4000'
-                            >
-                                <Input autoFocus size='compact' onBlur={onBlur} />
+                            <FormItem name='hotkey' label={t('Hotkey')}>
+                                <HotkeyRecorder onBlur={onBlur} testId='hotkey-recorder' />
+                            </FormItem>
+                            <FormItem name='displayWindowHotkey' label={t('Display window Hotkey')}>
+                                <HotkeyRecorder onBlur={onBlur} testId='display-window-hotkey-recorder' />
                             </FormItem>
                             <FormItem
-                                required={values.provider === 'LiteLLM'}
-                                name='liteLLMAPIKey'
-                                label='LiteLLM API Key'
-                                caption='Your LiteLLM proxy virtual key or master key. Leave blank if your proxy has no authentication.'
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='ocrHotkey'
+                                label={t('OCR Hotkey')}
                             >
-                                <Input type='password' size='compact' onBlur={onBlur} />
+                                <HotkeyRecorder onBlur={onBlur} testId='ocr-hotkey-recorder' />
                             </FormItem>
                             <FormItem
-                                name='liteLLMAPIModel'
-                                label={t('API Model')}
-                                required={values.provider === 'LiteLLM'}
+                                style={{
+                                    display: isDesktopApp ? 'block' : 'none',
+                                }}
+                                name='quickTranslatorHotkey'
+                                label={t('Quick Translator Hotkey')}
+                                caption={t(
+                                    'Open a non-activating panel that auto-detects the word or sentence you are reading.'
+                                )}
                             >
-                                <APIModelSelector
-                                    provider='LiteLLM'
-                                    currentProvider={values.provider}
-                                    apiKey={values.liteLLMAPIKey}
-                                    onBlur={onBlur}
-                                />
+                                <HotkeyRecorder onBlur={onBlur} testId='quick-translator-hotkey-recorder' />
                             </FormItem>
                         </div>
-                        <FormItem
-                            name='thinkingEnabled'
-                            label={t('Enable Thinking')}
-                            caption={t(
-                                'Disable thinking for faster translations. Reasoning models think by default, which significantly slows down simple tasks.'
-                            )}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='defaultTranslateMode' label={t('Default Action')}>
-                            <TranslateModeSelector onBlur={onBlur} />
-                        </FormItem>
-                        <PerActionModelConfig settings={values} />
-                        <FormItem name='defaultTargetLanguage' label={t('Default target language')}>
-                            <LanguageSelector onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='languageDetectionEngine' label={t('Language detection engine')}>
-                            <LanguageDetectionEngineSelector onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='themeType' label={t('Theme')}>
-                            <ThemeTypeSelector onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='enableBackgroundBlur'
-                            label={t('Window background blur')}
-                            caption={t(
-                                "If the window background blur effect is enabled, please ensure to set the 'Theme' to 'Follow the System', as it is currently not possible to manually switch between light and dark themes when the window background blur is active."
-                            )}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            name='fontSize'
-                            label={t('Font size')}
-                            caption={t('Controls the font size of the input box and the translation text.')}
-                        >
-                            <NumberInput min={8} max={40} step={1} />
-                        </FormItem>
-                        <FormItem
-                            name='uiFontSize'
-                            label={t('UI font size')}
-                            caption={t('Controls the font size of the app interface (toolbar, menus, labels).')}
-                        >
-                            <NumberInput min={8} max={24} step={1} />
-                        </FormItem>
-                        <FormItem
-                            name='alwaysShowIcons'
-                            label={t('Show icon when text is selected')}
-                            caption={
-                                isDesktopApp && (
-                                    <div
-                                        style={{
-                                            display: 'flex',
-                                            alignItems: 'center',
-                                            gap: 6,
-                                        }}
-                                    >
-                                        {t(
-                                            'It is highly recommended to disable this feature and use the Clip Extension'
-                                        )}
-                                        <a
-                                            href='https://github.com/nextai-translator/nextai-translator/blob/main/CLIP-EXTENSIONS.md'
-                                            target='_blank'
-                                            rel='noreferrer'
-                                            style={linkStyle}
-                                        >
-                                            {t('Clip Extension')}
-                                        </a>
-                                    </div>
-                                )
-                            }
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='autoTranslate' label={t('Auto Translate')}>
-                            <AutoTranslateCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='restorePreviousPosition'
-                            label={t('Fixed Position')}
-                        >
-                            <RestorePreviousPositionCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='selectInputElementsText' label={t('Word selection in input')}>
-                            <SelectInputElementsCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        {isTauri && (
-                            <FormItem name='runAtStartup' label={t('Run at startup')}>
-                                <RunAtStartupCheckbox onBlur={onBlur} />
-                            </FormItem>
-                        )}
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='hideTheIconInTheDock'
-                            label={isMacOS ? t('Hide the icon in the Dock bar') : t('Hide the icon in the taskbar')}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='autoHideWindowWhenOutOfFocus'
-                            label={t('Auto hide window when out of focus')}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            name='useCompactLookup'
-                            label={t('Compact inline lookup mode')}
-                            caption={t(
-                                'When enabled, text selection translation shows a compact popup with only the translated result'
-                            )}
-                        >
-                            <UseCompactLookupCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='automaticCheckForUpdates'
-                            label={t('Automatic check for updates')}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='disableCollectingStatistics'
-                            label={t('disable collecting statistics')}
-                        >
-                            <MyCheckbox onBlur={onBlur} />
-                        </FormItem>
-                    </div>
-                    <div
-                        style={{
-                            display: isTauri && activeTab === 'proxy' ? 'block' : 'none',
-                        }}
-                    >
-                        <FormItem name={['proxy', 'enabled']} label={t('Enabled')}>
-                            <MyCheckbox />
-                        </FormItem>
-                        <FormItem name={['proxy', 'protocol']} label={t('Protocol')}>
-                            <ProxyProtocolSelector />
-                        </FormItem>
-                        <FormItem name={['proxy', 'server']} label={t('Server')}>
-                            <Input size='compact' />
-                        </FormItem>
-                        <FormItem name={['proxy', 'port']} label={t('Port')}>
-                            <Input type='number' size='compact' />
-                        </FormItem>
-                        <FormItem name={['proxy', 'basicAuth', 'username']} label={t('Username')}>
-                            <Input size='compact' />
-                        </FormItem>
-                        <FormItem name={['proxy', 'basicAuth', 'password']} label={t('Password')}>
-                            <Input type='password' size='compact' />
-                        </FormItem>
-                        <FormItem name={['proxy', 'noProxy']} label={t('No proxy')}>
-                            <Textarea size='compact' />
-                        </FormItem>
-                        <ProxyTester proxy={values.proxy} />
-                    </div>
-                    <div
-                        style={{
-                            display: activeTab === 'tts' ? 'block' : 'none',
-                        }}
-                    >
-                        <FormItem
-                            name='readSelectedWordsFromInputElementsText'
-                            label={t('Read the selected words in input')}
-                        >
-                            <ReadSelectedWordsFromInputElementsCheckbox onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem name='tts' label={t('TTS')}>
-                            <TTSVoicesSettings onBlur={onBlur} />
-                        </FormItem>
-                    </div>
-                    <div
-                        style={{
-                            display: activeTab === 'writing' ? 'block' : 'none',
-                        }}
-                    >
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='writingTargetLanguage'
-                            label={t('Writing target language')}
-                        >
-                            <LanguageSelector onBlur={onBlur} />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='writingHotkey'
-                            label={t('Writing Hotkey')}
-                            caption={t(
-                                'Press this shortcut key in the input box of any application, and the text already entered in the input box will be automatically translated into the writing target language.'
-                            )}
-                        >
-                            <HotkeyRecorder onBlur={onBlur} testId='writing-hotkey-recorder' />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='writingNewlineHotkey'
-                            label={t('Writing line break shortcut')}
-                            caption={t('When writing, which key should be pressed when encountering a line break?')}
-                        >
-                            <HotkeyRecorder onBlur={onBlur} testId='writing-newline-hotkey-recorder' />
-                        </FormItem>
                     </div>
                     <div
                         style={{
-                            display: activeTab === 'shortcuts' ? 'block' : 'none',
+                            position: utils.isBrowserExtensionOptions() ? 'sticky' : 'fixed',
+                            bottom: '7px',
+                            right: '25px',
+                            paddingBottom: utils.isBrowserExtensionOptions() ? '10px' : undefined,
+                            display: 'flex',
+                            alignItems: 'center',
+                            flexDirection: 'row',
+                            zIndex: 1000,
+                            gap: 10,
                         }}
                     >
-                        <FormItem name='hotkey' label={t('Hotkey')}>
-                            <HotkeyRecorder onBlur={onBlur} testId='hotkey-recorder' />
-                        </FormItem>
-                        <FormItem name='displayWindowHotkey' label={t('Display window Hotkey')}>
-                            <HotkeyRecorder onBlur={onBlur} testId='display-window-hotkey-recorder' />
-                        </FormItem>
-                        <FormItem
-                            style={{
-                                display: isDesktopApp ? 'block' : 'none',
-                            }}
-                            name='ocrHotkey'
-                            label={t('OCR Hotkey')}
-                        >
-                            <HotkeyRecorder onBlur={onBlur} testId='ocr-hotkey-recorder' />
-                        </FormItem>
-                        <FormItem
+                        <div
                             style={{
-                                display: isDesktopApp ? 'block' : 'none',
+                                marginRight: 'auto',
                             }}
-                            name='quickTranslatorHotkey'
-                            label={t('Quick Translator Hotkey')}
-                            caption={t(
-                                'Open a non-activating panel that auto-detects the word or sentence you are reading.'
-                            )}
-                        >
-                            <HotkeyRecorder onBlur={onBlur} testId='quick-translator-hotkey-recorder' />
-                        </FormItem>
+                        />
+                        <Button isLoading={loading} size='mini' startEnhancer={<IoIosSave size={12} />}>
+                            {t('Save')}
+                        </Button>
                     </div>
-                </div>
-                <div
-                    style={{
-                        position: utils.isBrowserExtensionOptions() ? 'sticky' : 'fixed',
-                        bottom: '7px',
-                        right: '25px',
-                        paddingBottom: utils.isBrowserExtensionOptions() ? '10px' : undefined,
-                        display: 'flex',
-                        alignItems: 'center',
-                        flexDirection: 'row',
-                        zIndex: 1000,
-                        gap: 10,
-                    }}
-                >
-                    <div
-                        style={{
-                            marginRight: 'auto',
-                        }}
-                    />
-                    <Button isLoading={loading} size='mini' startEnhancer={<IoIosSave size={12} />}>
-                        {t('Save')}
-                    </Button>
-                </div>
-                <Toaster />
-            </Form>
+                    <Toaster />
+                </Form>
+            </LockedFields>
             {showFooter && (
                 <div
                     className={styles.footer}
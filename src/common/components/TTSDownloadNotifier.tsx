@@ -5,11 +5,13 @@ import { useTranslation } from 'react-i18next'
 import { isTauri } from '../utils'
 
 export interface TTSDownloadProgressPayload {
-    lang: 'zh' | 'en'
+    // The model id from the TTS catalog; 'zh' and 'en' are the bundled voices.
+    lang: string
+    name: string
     downloaded: number
     total: number
     bytesPerSecond: number
-    phase: 'downloading' | 'extracting' | 'ready' | 'error'
+    phase: 'downloading' | 'paused' | 'cancelled' | 'extracting' | 'ready' | 'error'
     message?: string
 }
 
@@ -25,11 +27,45 @@ export function formatDownloadSize(bytes: number): string {
     return `${value >= 100 || unit === 0 ? value.toFixed(0) : value.toFixed(1)} ${units[unit]}`
 }
 
+function voiceName(payload: TTSDownloadProgressPayload, t: TranslationFunction): string {
+    if (payload.lang === 'zh') {
+        return t('MeloTTS Chinese voice')
+    }
+    if (payload.lang === 'en') {
+        return t('Kokoro English voice')
+    }
+    return payload.name
+}
+
+async function controlDownload(id: string, action: 'pause' | 'resume' | 'cancel') {
+    const { commands } = await import('@/tauri/bindings')
+    const run = {
+        pause: commands.pauseTtsDownload,
+        resume: commands.resumeTtsDownload,
+        cancel: commands.cancelTtsDownload,
+    }[action]
+    const result = await run(id)
+    if (result.status === 'error') {
+        console.error(`Failed to ${action} TTS model download:`, result.error)
+    }
+}
+
+const controlButtonStyle = {
+    padding: 0,
+    border: 'none',
+    background: 'none',
+    color: 'inherit',
+    font: 'inherit',
+    textDecoration: 'underline',
+    cursor: 'pointer',
+}
+
 function TTSDownloadCard({ payload }: { payload: TTSDownloadProgressPayload }) {
     const { t } = useTranslation()
     const progress = payload.total > 0 ? Math.min(1, payload.downloaded / payload.total) : 0
-    const voice = t(payload.lang === 'zh' ? 'MeloTTS Chinese voice' : 'Kokoro English voice')
+    const voice = voiceName(payload, t)
     const isExtracting = payload.phase === 'extracting'
+    const isPaused = payload.phase === 'paused'
 
     return (
         <div style={{ width: 300 }}>
@@ -76,16 +112,38 @@ function TTSDownloadCard({ payload }: { payload: TTSDownloadProgressPayload }) {
                     {payload.total > 0 ? ` / ${formatDownloadSize(payload.total)}` : ''}
                 </span>
                 <span>
-                    {isExtracting ? t('Verifying and extracting…') : `${formatDownloadSize(payload.bytesPerSecond)}/s`}
+                    {isExtracting
+                        ? t('Verifying and extracting…')
+                        : isPaused
+                          ? t('Paused')
+                          : `${formatDownloadSize(payload.bytesPerSecond)}/s`}
                 </span>
             </div>
+            {!isExtracting && (
+                <div style={{ display: 'flex', justifyContent: 'flex-end', gap: 12, marginTop: 7, fontSize: 11 }}>
+                    <button
+                        type='button'
+                        style={controlButtonStyle}
+                        onClick={() => controlDownload(payload.lang, isPaused ? 'resume' : 'pause')}
+                    >
+                        {t(isPaused ? 'Resume' : 'Pause')}
+                    </button>
+                    <button
+                        type='button'
+                        style={controlButtonStyle}
+                        onClick={() => controlDownload(payload.lang, 'cancel')}
+                    >
+                        {t('Cancel')}
+                    </button>
+                </div>
+            )}
         </div>
     )
 }
 
 function handleProgress({ payload }: Event<TTSDownloadProgressPayload>, t: TranslationFunction) {
     const id = `tts-model-download-${payload.lang}`
-    const voice = t(payload.lang === 'zh' ? 'MeloTTS Chinese voice' : 'Kokoro English voice')
+    const voice = voiceName(payload, t)
     if (payload.phase === 'ready') {
         toast.success(t('{{voice}} is ready', { voice }), {
             id,
@@ -93,6 +151,10 @@ function handleProgress({ payload }: Event<TTSDownloadProgressPayload>, t: Trans
         })
         return
     }
+    if (payload.phase === 'cancelled') {
+        toast.dismiss(id)
+        return
+    }
     if (payload.phase === 'error') {
         console.error(payload.message ?? 'TTS model download failed')
         toast.error(t('Failed to download {{voice}}', { voice }), {
@@ -1074,11 +1074,23 @@ function InnerTranslator(props: IInnerTranslatorProps) {
             // swallowed submits.
             const isTranslate = action.mode === 'translate'
             const currentTargetLang = targetLangRef.current
+            // A per-app rule for the app the text came from may pick its own
+            // target language.
+            const ruleTargetLang = isTauri()
+                ? ((await import('@/tauri/bindings')
+                      .then(({ commands }) => commands.getActiveAppRule())
+                      .then((rule) => rule?.targetLanguage || undefined)
+                      .catch(() => undefined)) as LangCode | undefined)
+                : undefined
+            const defaultTargetLang = ruleTargetLang ?? (settings?.defaultTargetLanguage as LangCode | undefined)
             const newTargetLang = (() => {
                 if (
                     isTranslate &&
                     (!stopAutomaticallyChangeTargetLang.current || newSourceLang === currentTargetLang)
                 ) {
+                    if (ruleTargetLang && ruleTargetLang !== newSourceLang) {
+                        return ruleTargetLang
+                    }
                     return (
                         (newSourceLang === 'zh-Hans' || newSourceLang === 'zh-Hant'
                             ? 'en'
@@ -1086,8 +1098,8 @@ function InnerTranslator(props: IInnerTranslatorProps) {
                     )
                 }
                 if (!currentTargetLang) {
-                    if (settings?.defaultTargetLanguage) {
-                        return settings.defaultTargetLanguage as LangCode
+                    if (defaultTargetLang) {
+                        return defaultTargetLang
                     }
                     return newSourceLang
                 }
@@ -21,7 +21,7 @@ import { Tooltip } from './Tooltip'
 import { isDesktopApp, isTauri } from '../utils'
 import { vocabularyService } from '../services/vocabulary'
 import { VocabularyItem } from '../internal-services/db'
-import { trackEvent } from '@aptabase/tauri'
+import { trackEvent } from '../analysis'
 import { SpeakerIcon } from './SpeakerIcon'
 import { useSettings } from '../hooks/useSettings'
 import { LangCode, detectLang } from '../lang'
@@ -1,6 +1,5 @@
 /* eslint-disable @typescript-eslint/no-unused-vars */
 /* eslint-disable @typescript-eslint/no-explicit-any */
-import { writeTextFile, rename, BaseDirectory } from '@tauri-apps/plugin-fs'
 import { Proxy, ProxyConfig, fetch } from '@tauri-apps/plugin-http'
 import * as utils from '../utils'
 import { IBrowser, ISettings } from '../types'
@@ -31,17 +30,12 @@ class BrowserStorageSync {
         }, {})
         const settings = await getSettings()
         const newSettings = { ...settings, ...newItems }
-        // Write-then-rename so config.json is replaced atomically: overwriting
-        // it in place leaves a truncated, unparsable file if the app dies
-        // mid-write, and since the file survives uninstall/reinstall that used
-        // to brick the app at every subsequent launch.
-        await writeTextFile('config.json.tmp', JSON.stringify(newSettings), {
-            baseDir: BaseDirectory.AppConfig,
-        })
-        await rename('config.json.tmp', 'config.json', {
-            oldPathBaseDir: BaseDirectory.AppConfig,
-            newPathBaseDir: BaseDirectory.AppConfig,
-        })
+        // The backend writes config.json atomically and drops values for keys
+        // locked by an administrator policy.
+        const result = await commands.saveConfigContent(JSON.stringify(newSettings))
+        if (result.status === 'error') {
+            throw new Error(result.error)
+        }
     }
 }
 
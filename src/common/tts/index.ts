@@ -1,6 +1,6 @@
 import { DoSpeakOptions, SpeakOptions, TTSProvider } from './types'
 import { getSettings } from '../utils'
-import { isLocalTTSLanguage, speak as localSpeak } from './local-tts'
+import { isLocalTTSLanguage, loadLocalTTSLanguages, speak as localSpeak } from './local-tts'
 import { LangCode } from '../lang'
 import * as utils from '../utils'
 
@@ -114,6 +114,9 @@ export async function doSpeak({
     // Languages the local engine cannot speak fall through to the system
     // voices below instead of the dead Edge service.
     if (provider === 'LocalTTS' || provider === 'EdgeTTS') {
+        if (utils.isTauri()) {
+            await loadLocalTTSLanguages()
+        }
         if (utils.isTauri() && isLocalTTSLanguage(lang)) {
             return localSpeak({
                 text,
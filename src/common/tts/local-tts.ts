@@ -2,6 +2,7 @@ import { invoke } from '@tauri-apps/api/core'
 import { LangCode } from '../lang'
 import { SpeakOptions } from './types'
 import { getSpeechWordStarts, segmentSpeechText } from './speech-segments'
+import type { TtsModelInfo } from '../../tauri/bindings'
 
 interface LocalTTSOptions extends SpeakOptions {
     lang: LangCode
@@ -22,12 +23,31 @@ const STOP_RAMP_SECONDS = 0.03
 const TEARDOWN_DELAY_MS = 100
 const SUSPEND_AFTER_IDLE_MS = 5000
 
+// Languages of the bundled voices; models added to the TTS catalog extend it
+// once loadLocalTTSLanguages has run.
+let localTTSLanguages = new Set<string>(LOCAL_TTS_LANGUAGES)
+let localTTSLanguagesLoaded: Promise<void> | null = null
 let audioContext: AudioContext | null = null
 let activePlayback: ActivePlayback | null = null
 let suspendTimer: number | null = null
 
 export function isLocalTTSLanguage(lang: LangCode): boolean {
-    return LOCAL_TTS_LANGUAGES.has(lang)
+    return localTTSLanguages.has(lang)
+}
+
+// Only models that are installed or can be downloaded take over a language;
+// the rest would fail where the system voices still work.
+export function loadLocalTTSLanguages(): Promise<void> {
+    localTTSLanguagesLoaded ??= invoke<TtsModelInfo[]>('list_tts_models').then(
+        (models) => {
+            localTTSLanguages = new Set([
+                ...LOCAL_TTS_LANGUAGES,
+                ...models.filter((model) => model.installed || model.downloadable).flatMap((model) => model.languages),
+            ])
+        },
+        () => undefined
+    )
+    return localTTSLanguagesLoaded
 }
 
 export function fetchLocalVoices(): SpeechSynthesisVoice[] {
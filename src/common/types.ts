@@ -106,6 +106,24 @@ export interface ISettings {
     autoCollect?: boolean
     hideTheIconInTheDock?: boolean
     languageDetectionEngine?: LanguageDetectionEngine
+    languageDetectionAllowlist?: string[]
+    languageDetectionBackend?: 'whatlang' | 'fastText'
+    // KiB/s; 0 or unset means unlimited.
+    modelDownloadRateLimit?: number
+    // Local TTS voice ids by language, from listTtsVoices.
+    localTtsVoices?: Record<string, string>
+    // MiB of cached local TTS speech, 100 when unset; 0 turns the cache off.
+    localTtsCacheSize?: number
+    // Seconds a local TTS engine stays loaded unused, 600 when unset; 0 keeps it.
+    localTtsIdleUnload?: number
+    // Local TTS engines kept loaded at most, 2 when unset.
+    localTtsMaxEngines?: number
+    // Threads per local TTS engine; 0 or unset picks them by core count.
+    localTtsThreads?: number
+    // Sentences a local TTS engine synthesizes per batch; 0 or unset is automatic.
+    localTtsMaxSentences?: number
+    // Load the local TTS model for the default target language at startup.
+    localTtsWarmUp?: boolean
     autoHideWindowWhenOutOfFocus?: boolean
     proxy?: {
         enabled?: boolean
@@ -6,6 +6,69 @@ export const commands = {
     async getConfigContent(): Promise<string> {
         return await TAURI_INVOKE('get_config_content')
     },
+    /**
+     * Persists settings edited in the UI. Values for locked keys are discarded.
+     */
+    async saveConfigContent(content: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('save_config_content', { content }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Top-level settings keys fixed by the administrator's policy file. The
+     * settings UI renders them read-only.
+     */
+    async getLockedConfigKeys(): Promise<string[]> {
+        return await TAURI_INVOKE('get_locked_config_keys')
+    },
+    /**
+     * Which layer supplies each setting currently in effect, for debugging
+     * launch overrides and policies.
+     */
+    async getConfigSources(): Promise<Result<ConfigValueSource[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('get_config_sources') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Where config, models and caches currently live, e.g. for an "open
+     * data folder" button or for support requests.
+     */
+    async getAppDirectories(): Promise<Result<AppDirectories, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('get_app_directories') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * The rule applied to the most recent trigger, if any. The translator and
+     * the writing flow use it to pick the rule's target language.
+     */
+    async getActiveAppRule(): Promise<AppRule | null> {
+        return await TAURI_INVOKE('get_active_app_rule')
+    },
+    /**
+     * Shows which rule, if any, would apply to the given app.
+     */
+    async matchAppRule(app: AppInfo): Promise<AppRuleMatch | null> {
+        return await TAURI_INVOKE('match_app_rule', { app })
+    },
+    /**
+     * Checks a settings candidate and returns one entry per problem, so the
+     * settings UI can flag the offending fields before saving. An empty list
+     * means the candidate is fine.
+     */
+    async validateConfig(content: string): Promise<ConfigFieldError[]> {
+        return await TAURI_INVOKE('validate_config', { content })
+    },
     async getUpdateResult(): Promise<[boolean, UpdateResult | null]> {
         return await TAURI_INVOKE('get_update_result')
     },
@@ -72,11 +135,89 @@ export const commands = {
     async rememberActiveWindowCommand(): Promise<boolean> {
         return await TAURI_INVOKE('remember_active_window_command')
     },
-    async detectLang(text: string): Promise<string> {
-        return await TAURI_INVOKE('detect_lang', { text })
+    /**
+     * Detects the language of `text` offline. `allowlist` limits the candidates
+     * (app or ISO 639 codes) and falls back to `languageDetectionAllowlist` from
+     * the config; `alternatives` is how many runner-up languages to report.
+     * Returns `None` when the text has no recognizable language.
+     */
+    async detectLang(
+        text: string,
+        allowlist: string[] | null,
+        alternatives: number | null
+    ): Promise<Result<LangDetection | null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('detect_lang', { text, allowlist, alternatives }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Splits mixed-language text, e.g. English terms inside Chinese prose, into
+     * runs labeled with language and script, so callers can skip runs already in
+     * the target language or route each run to a matching voice. `allowlist`
+     * works as for `detect_lang`.
+     */
+    async segmentLang(text: string, allowlist: string[] | null): Promise<Result<LangSegment[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('segment_lang', { text, allowlist }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Which detector is configured and whether the fastText model is on disk and
+     * in memory.
+     */
+    async getLangModelStatus(): Promise<LangModelStatus> {
+        return await TAURI_INVOKE('get_lang_model_status')
+    },
+    /**
+     * Downloads the fastText language-ID model, reporting progress through
+     * `lang-model-download-progress` events.
+     */
+    async downloadLangModel(): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('download_lang_model') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Converts Chinese text between Simplified (`zh-Hans`, `zh-CN`), standard
+     * Traditional (`zh-Hant`), Taiwan (`zh-TW`) and Hong Kong (`zh-HK`) forms,
+     * including regional vocabulary such as 软件 -> 軟體 for Taiwan.
+     */
+    async convertChinese(text: string, from: string, to: string): Promise<Result<string, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('convert_chinese', { text, from, to }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Phonetic readings of `text` word by word, for ruby-style display: pinyin
+     * with tone marks for Chinese, kana and romaji for Japanese, IPA for English.
+     * `lang` decides how Han characters are read; without it, text containing
+     * kana is read as Japanese. Chinese readings come from the lexicon of the
+     * Chinese TTS model, so that model must be downloaded. English readings come
+     * from the English model's lexicon; with a model that has none, English words
+     * in other text get no reading and English-only text is an error.
+     */
+    async transcribePronunciation(text: string, lang: string | null): Promise<Result<PronunciationToken[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('transcribe_pronunciation', { text, lang }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
     },
-    async screenshot(x: number, y: number): Promise<void> {
-        await TAURI_INVOKE('screenshot', { x, y })
+    async screenshot(x: number, y: number): Promise<string | null> {
+        return await TAURI_INVOKE('screenshot', { x, y })
     },
     async hideTranslatorWindow(): Promise<void> {
         await TAURI_INVOKE('hide_translator_window')
@@ -108,9 +249,316 @@ export const commands = {
     async cutImage(left: number, top: number, width: number, height: number): Promise<void> {
         await TAURI_INVOKE('cut_image', { left, top, width, height })
     },
-    async synthesizeLocalTts(text: string, lang: string, rate: number): Promise<Result<string, string>> {
+    /**
+     * Synthesizes `text` into a WAV, with the sentences it reads and when, for
+     * highlighting along. `speech` sets volume and pitch and turns on markup
+     * such as `<break time="2s"/>` for pacing.
+     */
+    async synthesizeLocalTts(
+        text: string,
+        lang: string,
+        rate: number,
+        voice: string | null,
+        speech: TtsSpeechOptions | null
+    ): Promise<Result<TtsSpeech, string>> {
+        try {
+            return {
+                status: 'ok',
+                data: await TAURI_INVOKE('synthesize_local_tts', { text, lang, rate, voice, speech }),
+            }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Like `synthesize_local_tts`, but emits every sentence as soon as it is
+     * synthesized, as a `tts-stream-segment` event with the stream `id`, a
+     * sequence number counting from 0 and a base64 WAV. Emitting
+     * `abort-local-tts-stream` with the same `id` stops synthesis, also in the
+     * middle of a sentence. Returns the number of segments emitted.
+     */
+    async synthesizeLocalTtsStream(
+        id: string,
+        text: string,
+        lang: string,
+        rate: number,
+        voice: string | null,
+        speech: TtsSpeechOptions | null
+    ): Promise<Result<number, string>> {
+        try {
+            return {
+                status: 'ok',
+                data: await TAURI_INVOKE('synthesize_local_tts_stream', { id, text, lang, rate, voice, speech }),
+            }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Synthesizes `text` into an audio file at `path`, for study material. The
+     * format comes from `options` or the extension of `path`; see
+     * `TtsExportOptions` for splitting into one file per sentence.
+     */
+    async exportLocalTts(
+        text: string,
+        lang: string,
+        rate: number,
+        voice: string | null,
+        path: string,
+        options: TtsExportOptions
+    ): Promise<Result<TtsExport, string>> {
+        try {
+            return {
+                status: 'ok',
+                data: await TAURI_INVOKE('export_local_tts', { text, lang, rate, voice, path, options }),
+            }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Deletes all cached speech, e.g. after replacing a model with another
+     * version under the same id.
+     */
+    async clearTtsCache(): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('clear_tts_cache') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * The loaded TTS engines with the memory they hold. Engines unload after
+     * `localTtsIdleUnload` seconds unused, and at most `localTtsMaxEngines`
+     * stay loaded.
+     */
+    async listLoadedTtsEngines(): Promise<TtsLoadedEngine[]> {
+        return await TAURI_INVOKE('list_loaded_tts_engines')
+    },
+    /**
+     * The local TTS models this build knows, with whether each is downloaded and
+     * loaded and its size on disk.
+     */
+    async listTtsModels(): Promise<Result<TtsModelInfo[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('list_tts_models') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * The voices of the installed models, or of the model `id`. Models whose
+     * speakers the catalog does not list are loaded to count them.
+     */
+    async listTtsVoices(id: string | null): Promise<Result<TtsVoice[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('list_tts_voices', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Downloads a model ahead of its first use, reporting progress through
+     * `tts-download-progress` events. Does nothing when it is already installed.
+     */
+    async downloadTtsModel(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('download_tts_model', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Pauses a running model download; it reports the `paused` phase and keeps
+     * what it has so far.
+     */
+    async pauseTtsDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('pause_tts_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Continues a paused model download with an HTTP Range request.
+     */
+    async resumeTtsDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('resume_tts_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Stops a running or paused model download and deletes the partial file;
+     * it reports the `cancelled` phase.
+     */
+    async cancelTtsDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('cancel_tts_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Checks an installed model's files against the checksums recorded when it
+     * was downloaded.
+     */
+    async verifyTtsModel(id: string): Promise<Result<TtsModelVerification, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('verify_tts_model', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Unloads a model and deletes its files, cancelling a download of it.
+     */
+    async deleteTtsModel(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('delete_tts_model', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Where the models live, the free space there and the space they use, so
+     * the UI can warn before a download.
+     */
+    async getTtsStorage(): Promise<Result<TtsStorage, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('get_tts_storage') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Installs a model from a local `.tar.bz2` or directory, for machines that
+     * cannot download. An archive of a catalog model, e.g. the release tarball of
+     * the bundled Chinese voice, replaces that model; anything else is added to
+     * `catalog.json` under its directory name after its files are checked
+     * against what its family needs. Either way it speaks `languages`.
+     */
+    async importTtsModel(
+        path: string,
+        languages: string[],
+        name: string | null,
+        family: ModelFamily | null
+    ): Promise<Result<TtsModelInfo, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('import_tts_model', { path, languages, name, family }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Transcribes a WAV, MP3, Ogg (Opus or Vorbis) or M4A file into text with
+     * the time of each utterance, using model `model` or the installed one for
+     * `lang`. `lang` also tells the model the language to expect; without it
+     * the model detects it.
+     */
+    async transcribeAudioFile(
+        path: string,
+        lang: string | null,
+        model: string | null
+    ): Promise<Result<AsrTranscript, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('transcribe_audio_file', { path, lang, model }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * The speech recognition models this build knows, with whether each is
+     * installed and loaded and its size on disk.
+     */
+    async listAsrModels(): Promise<Result<AsrModelInfo[], string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('list_asr_models') }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Downloads a model ahead of its first use, reporting progress through
+     * `asr-download-progress` events. Does nothing when it is already installed.
+     */
+    async downloadAsrModel(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('download_asr_model', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Pauses a running model download, like `pause_tts_download`.
+     */
+    async pauseAsrDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('pause_asr_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Continues a paused model download.
+     */
+    async resumeAsrDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('resume_asr_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Stops a running or paused model download and deletes the partial file.
+     */
+    async cancelAsrDownload(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('cancel_asr_download', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Unloads a model and deletes its files, cancelling a download of it.
+     */
+    async deleteAsrModel(id: string): Promise<Result<null, string>> {
+        try {
+            return { status: 'ok', data: await TAURI_INVOKE('delete_asr_model', { id }) }
+        } catch (e) {
+            if (e instanceof Error) throw e
+            else return { status: 'error', error: e as any }
+        }
+    },
+    /**
+     * Installs a catalog model from its release `.tar.bz2` or extracted
+     * directory, for models without a pinned checksum and machines that cannot
+     * download. The model is recognized by the directory name.
+     */
+    async importAsrModel(path: string): Promise<Result<AsrModelInfo, string>> {
         try {
-            return { status: 'ok', data: await TAURI_INVOKE('synthesize_local_tts', { text, lang, rate }) }
+            return { status: 'ok', data: await TAURI_INVOKE('import_asr_model', { path }) }
         } catch (e) {
             if (e instanceof Error) throw e
             else return { status: 'error', error: e as any }
@@ -149,6 +597,77 @@ export const events = __makeEvents__<{
 
 /** user-defined types **/
 
+export type AppDirectories = { portable: boolean; config: string; data: string; cache: string }
+export type AppInfo = { appName: string; bundleId: string; processName: string }
+/**
+ * Which apps a rule applies to. Every field that is set must match; `*`
+ * matches any run of characters and comparison ignores case.
+ */
+export type AppMatcher = {
+    appName: string | null
+    /**
+     * macOS bundle identifier, e.g. `com.tinyspeck.slackmacgap`.
+     */
+    bundleId: string | null
+    /**
+     * Executable file name, e.g. `Code.exe`.
+     */
+    processName: string | null
+}
+export type AppRule = {
+    name: string | null
+    match: AppMatcher
+    targetLanguage: string | null
+    alwaysShowIcons: boolean | null
+    useCompactLookup: boolean | null
+    writingNewlineHotkey: string | null
+    insertionMode: InsertionMode | null
+}
+export type AppRuleMatch = {
+    /**
+     * Position of the rule in `appRules`.
+     */
+    index: number
+    rule: AppRule
+}
+export type AsrModelInfo = {
+    id: string
+    name: string
+    /**
+     * Empty for models that recognize any language.
+     */
+    languages: string[]
+    installed: boolean
+    /**
+     * Whether the catalog pins a checksum, so the model can be downloaded.
+     */
+    downloadable: boolean
+    loaded: boolean
+    /**
+     * Bytes on disk, 0 when not installed.
+     */
+    size: number
+}
+export type AsrSegment = { startMs: number; endMs: number; text: string }
+export type AsrTranscript = {
+    /**
+     * The id of the model that recognized the speech.
+     */
+    model: string
+    durationMs: number
+    /**
+     * The segments joined.
+     */
+    text: string
+    segments: AsrSegment[]
+}
+export type AudioFileFormat =
+    | 'wav'
+    | 'flac'
+    /**
+     * Ogg/Opus, written with the `.opus` extension.
+     */
+    | 'opus'
 export type AxContext = {
     focusedText: string
     focusedRole: string
@@ -164,9 +683,290 @@ export type AxContext = {
 }
 export type CheckUpdateEvent = null
 export type CheckUpdateResultEvent = UpdateResult
+export type ConfigFieldError = {
+    /**
+     * Dot-separated path of the offending setting, e.g. `proxy.port`; empty
+     * when the whole document is unusable.
+     */
+    field: string
+    message: string
+}
+/**
+ * The layer a setting's effective value comes from, in increasing order of
+ * precedence.
+ */
+export type ConfigSource = 'policyDefault' | 'file' | 'environment' | 'commandLine' | 'policy'
 export type ConfigUpdatedEvent = null
+export type ConfigValueSource = {
+    /**
+     * Dot-separated path of the setting, e.g. `proxy.server`.
+     */
+    key: string
+    source: ConfigSource
+}
+export type InsertionMode =
+    /**
+     * Simulate typing the text key by key.
+     */
+    | 'typing'
+    /**
+     * Put the text on the clipboard and paste it; much faster, and immune
+     * to autocomplete and IME interference in editors.
+     */
+    | 'paste'
+export type LangCandidate = {
+    /**
+     * Language code as used by the frontend, e.g. `zh-Hans` or `en`.
+     */
+    lang: string
+    iso639_1: string
+    iso639_3: string
+    /**
+     * Between 0 and 1.
+     */
+    confidence: number
+}
+export type LangDetection = {
+    /**
+     * Language code as used by the frontend, e.g. `zh-Hans` or `en`.
+     */
+    lang: string
+    iso639_1: string
+    iso639_3: string
+    /**
+     * Writing system of the text, e.g. `Latin` or `Mandarin`.
+     */
+    script: string
+    /**
+     * Between 0 and 1.
+     */
+    confidence: number
+    isReliable: boolean
+    /**
+     * The next most likely languages, best first.
+     */
+    alternatives: LangCandidate[]
+}
+export type LangDetectionBackend =
+    /**
+     * Trigram statistics; built in, but unreliable for a few words.
+     */
+    | 'whatlang'
+    /**
+     * fastText's `lid.176` model, much better on short text. Falls back to
+     * whatlang until the model has been downloaded.
+     */
+    | 'fastText'
+export type LangModelStatus = { backend: LangDetectionBackend; installed: boolean; loaded: boolean }
+export type LangSegment = {
+    text: string
+    /**
+     * Offsets of the run in UTF-16 code units, as JavaScript indexes
+     * strings, end exclusive.
+     */
+    start: number
+    end: number
+    /**
+     * Language code as used by the frontend; empty when the run has no
+     * letters, e.g. only digits or code.
+     */
+    lang: string
+    /**
+     * Writing system of the run, e.g. `Latin`; empty like `lang`.
+     */
+    script: string
+    isReliable: boolean
+}
+export type ModelFamily =
+    | 'vits'
+    /**
+     * Piper voices are VITS models phonemized with espeak-ng.
+     */
+    | 'piper'
+    | 'kokoro'
+    | 'matcha'
 export type PinnedFromTrayEvent = { pinned: boolean }
 export type PinnedFromWindowEvent = { pinned: boolean }
+export type PronunciationSystem =
+    /**
+     * Hanyu Pinyin with tone marks, one space-separated syllable per character.
+     */
+    | 'pinyin'
+    /**
+     * Hiragana, with romaji in `romanization`.
+     */
+    | 'kana'
+    | 'ipa'
+export type PronunciationToken = {
+    text: string
+    /**
+     * Offsets in UTF-16 code units, as JavaScript indexes strings, end
+     * exclusive. The tokens cover the whole input, so they can be rendered
+     * in order.
+     */
+    start: number
+    end: number
+    /**
+     * `None` with `reading` for punctuation, spaces and unknown words.
+     */
+    system: PronunciationSystem | null
+    reading: string | null
+    romanization: string | null
+}
+export type TtsExport = {
+    format: AudioFileFormat
+    sampleRate: number
+    files: TtsExportFile[]
+    /**
+     * Path of the manifest when sentences were split.
+     */
+    manifest: string | null
+}
+export type TtsExportFile = {
+    /**
+     * Absolute in the command result, relative in the manifest.
+     */
+    path: string
+    text: string
+    durationMs: number
+}
+export type TtsExportOptions = {
+    /**
+     * Taken from the extension of the path when not given.
+     */
+    format: AudioFileFormat | null
+    /**
+     * The rate of the model when not given, or 48 kHz for Opus.
+     */
+    sampleRate: number | null
+    /**
+     * Writes every sentence to its own file, numbered after the path, and
+     * lists them in a JSON manifest at the path with the extension `.json`.
+     */
+    splitSentences: boolean
+    speech: TtsSpeechOptions
+}
+export type TtsLoadedEngine = {
+    /**
+     * The model id.
+     */
+    id: string
+    name: string
+    /**
+     * Approximate bytes held, from the size of the model weights.
+     */
+    memory: number
+    /**
+     * Seconds since the engine was last used.
+     */
+    idle: number
+    /**
+     * Whether it is synthesizing right now.
+     */
+    inUse: boolean
+}
+export type TtsModelInfo = {
+    id: string
+    name: string
+    languages: string[]
+    installed: boolean
+    /**
+     * Whether the catalog pins a checksum, so the model can be downloaded.
+     */
+    downloadable: boolean
+    loaded: boolean
+    /**
+     * Bytes on disk, 0 when not installed.
+     */
+    size: number
+}
+export type TtsModelVerification = {
+    /**
+     * Whether every file is present and, with checksums, unchanged.
+     */
+    ok: boolean
+    /**
+     * `false` for models downloaded before checksums were recorded; only the
+     * required files are checked for those.
+     */
+    hasChecksums: boolean
+    missing: string[]
+    mismatched: string[]
+}
+export type TtsSpeech = {
+    /**
+     * Base64 WAV.
+     */
+    audio: string
+    /**
+     * The sentences in the order they are read, for highlighting.
+     */
+    segments: TtsSpeechSegment[]
+}
+export type TtsSpeechOptions = {
+    /**
+     * Reads the text as SSML-lite: `<break>`, `<emphasis>` and `<say-as>`
+     * for spelling; see `tts_markup`.
+     */
+    markup: boolean
+    /**
+     * From 0 to 1, 1 when not given.
+     */
+    volume: number | null
+    /**
+     * Shift in semitones, from -12 to 12.
+     */
+    pitch: number | null
+}
+export type TtsSpeechSegment = {
+    /**
+     * The sentence without markdown and markup.
+     */
+    text: string
+    /**
+     * The stretches of the input the sentence is read from. Markdown and
+     * markup inside the sentence fall between them.
+     */
+    ranges: TtsTextRange[]
+    startMs: number
+    endMs: number
+}
+export type TtsStorage = {
+    directory: string
+    /**
+     * Free bytes on the disk holding `directory`, `None` if unknown.
+     */
+    available: number | null
+    /**
+     * Bytes used by downloaded models.
+     */
+    used: number
+    /**
+     * Bytes used by cached speech.
+     */
+    cache: number
+}
+/**
+ * Offsets in UTF-16 code units, as JavaScript indexes strings, `end`
+ * exclusive.
+ */
+export type TtsTextRange = { start: number; end: number }
+export type TtsVoice = {
+    /**
+     * `<model id>:<speaker id>`, as synthesis and the `localTtsVoices`
+     * setting take it.
+     */
+    id: string
+    model: string
+    speaker: number
+    name: string
+    gender: string | null
+    accent: string | null
+    /**
+     * The languages of the model.
+     */
+    languages: string[]
+}
 export type UpdateResult = { version: string; currentVersion: string; body: string | null }
 
 /** tauri-specta globals **/
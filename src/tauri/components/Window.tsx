@@ -21,7 +21,7 @@ import { useSetAtom } from 'jotai'
 
 import { showSettingsAtom } from '@/common/store/setting'
 import { commands } from '../bindings'
-import { trackEvent } from '@aptabase/tauri'
+import { trackEvent } from '../../common/analysis'
 
 addEventListener('unhandledrejection', (e) => {
     trackEvent('promise_rejected', {
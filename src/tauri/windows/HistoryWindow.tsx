@@ -7,7 +7,7 @@ import { HistoryItem } from '../../common/internal-services/db'
 import { emit } from '@tauri-apps/api/event'
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow'
 import { useMemoWindow } from '../../common/hooks/useMemoWindow'
-import { trackEvent } from '@aptabase/tauri'
+import { trackEvent } from '../../common/analysis'
 
 export function HistoryWindow() {
     useMemoWindow({ size: true, position: true, show: true })
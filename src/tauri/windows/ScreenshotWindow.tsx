@@ -1,5 +1,4 @@
-import { trackEvent } from '@aptabase/tauri'
-import { appCacheDir, join } from '@tauri-apps/api/path'
+import { trackEvent } from '../../common/analysis'
 import { convertFileSrc } from '@tauri-apps/api/core'
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow'
 import { currentMonitor } from '@tauri-apps/api/window'
@@ -40,12 +39,12 @@ export function ScreenshotWindow() {
 
             const position = monitor.position
 
-            commands.screenshot(position.x, position.y).then(() => {
-                appCacheDir().then((dir) => {
-                    join(dir, 'ocr_images', 'fullscreen.png').then((path) => {
-                        setImgURL(convertFileSrc(path))
-                    })
-                })
+            commands.screenshot(position.x, position.y).then((path) => {
+                if (!path) {
+                    console.error('screenshot window: failed to capture the screen')
+                    return
+                }
+                setImgURL(convertFileSrc(path))
             })
         })
     }, [])
@@ -126,7 +126,11 @@ export function TranslatorWindow() {
                     writingQueue.current.push(0)
                     writing()
                 }
-                if (!settings?.writingTargetLanguage) {
+                // A per-app rule for the app being written in may pick its own
+                // target language.
+                const activeAppRule = await commands.getActiveAppRule().catch(() => null)
+                const writingTargetLanguage = activeAppRule?.targetLanguage || settings?.writingTargetLanguage
+                if (!writingTargetLanguage) {
                     // eslint-disable-next-line no-console
                     console.warn(
                         '[writing] bailing: settings.writingTargetLanguage is not set — ' +
@@ -148,8 +152,8 @@ export function TranslatorWindow() {
                 // "writing-indicator-finish" event to tear it down — so error
                 // and success paths both go through `enqueueFinish`.
                 // eslint-disable-next-line no-console
-                console.log('[writing] calling showWritingIndicator(', settings.writingTargetLanguage, ')')
-                commands.showWritingIndicator(settings.writingTargetLanguage).catch((err) => {
+                console.log('[writing] calling showWritingIndicator(', writingTargetLanguage, ')')
+                commands.showWritingIndicator(writingTargetLanguage).catch((err) => {
                     // eslint-disable-next-line no-console
                     console.error('[writing] showWritingIndicator failed', err)
                 })
@@ -167,7 +171,7 @@ export function TranslatorWindow() {
 
                 try {
                     const sourceLang = await detectLang(inputText)
-                    const targetLang = intoLangCode(settings.writingTargetLanguage)
+                    const targetLang = intoLangCode(writingTargetLanguage)
                     await translate({
                         writing: true,
                         action: {
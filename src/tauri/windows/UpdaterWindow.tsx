@@ -13,7 +13,7 @@ import { MdBrowserUpdated } from 'react-icons/md'
 import { IoIosCloseCircleOutline } from 'react-icons/io'
 import { useTranslation } from 'react-i18next'
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow'
-import { trackEvent } from '@aptabase/tauri'
+import { trackEvent } from '../../common/analysis'
 import { UpdateResult, commands, events } from '../bindings'
 import type { UnlistenFn } from '@tauri-apps/api/event'
 